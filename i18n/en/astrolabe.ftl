@@ -3,5 +3,72 @@ about = About
 view = View
 file = File
 new-tab = New Tab
+new-window = New Window
+new-private-window = New Private Window
+private-title = Astrolabe Private Browsing
 page-id = Page { $num }
 git-description = Git commit {$hash} on {$date}
+console = Console
+console-clear = Clear
+console-placeholder = Evaluate an expression in this tab
+console-debug = Debug
+console-log = Log
+console-info = Info
+console-warning = Warnings
+console-error = Errors
+history = History
+inspect = Inspect
+inspector = Web Inspector
+search-with = Search {$engine}
+switch-to-tab = Switch to tab
+search-engines = Search Engines
+search-engine-keyword = Keyword: {$keyword}
+opensearch-placeholder = Path to an OpenSearch description
+opensearch-import = Import
+bookmarks = Bookmarks
+bookmark-page = Bookmark Page
+bookmarks-bar = Bookmarks Bar
+manage-bookmarks = Manage Bookmarks
+new-folder = New Folder
+bookmark-title = Name
+bookmark-tags = Tags, separated by commas
+bookmark-save = Save
+bookmarks-path = Path to a bookmarks HTML file
+bookmarks-import = Import
+bookmarks-export = Export
+bookmarks-imported = Imported {$count} bookmarks
+bookmarks-exported = Exported bookmarks to {$path}
+settings = Settings
+startup = On startup
+startup-start-page = Open the start page
+startup-last-session = Continue where you left off
+startup-homepage = Open the homepage
+homepage-placeholder = Homepage address
+recently-closed = Recently Closed
+no-closed-tabs = No closed tabs
+close-tab = Close Tab
+reopen-closed-tab = Reopen Closed Tab
+next-tab = Next Tab
+previous-tab = Previous Tab
+select-tab = Go to Tab {$number}
+last-tab = Go to Last Tab
+focus-address-bar = Focus Address Bar
+back = Back
+forward = Forward
+reload = Reload
+find = Find in Page
+find-next = Find Next
+find-previous = Find Previous
+find-placeholder = Find in page
+find-not-found = No matches
+shortcuts = Shortcuts
+shortcuts-hint = Chords separated by commas, like Ctrl+Shift+T. Leave empty to unbind.
+shortcut-reset = Reset
+shortcut-invalid = Can't read the shortcut {$chords}
+shortcut-not-reserved = {$chord} needs Ctrl, Alt or Super, or a function key, so pages can still get it
+duplicate-tab = Duplicate
+pin-tab = Pin
+unpin-tab = Unpin
+close-other-tabs = Close Others
+close-tabs-below = Close Tabs Below
+move-to-new-window = Move to New Window
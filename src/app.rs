@@ -1,21 +1,40 @@
 // SPDX-License-Identifier: MIT
 
-use crate::config::Config;
+use crate::bookmarks::{self, Bookmarks, Node};
+use crate::bridge;
+use crate::config::{Config, Startup};
+use crate::favicon;
 use crate::fl;
-use crate::startpage;
+use crate::history::{History, Transition};
+use crate::omnibox;
+use crate::pages::{self, Page};
+use crate::search::SearchEngine;
+use crate::session::{self, Session};
+use crate::shortcuts;
 use crate::web;
+use crate::window::BrowserWindow;
 use cosmic::app::{context_drawer, Action, Core, Task};
 use cosmic::cosmic_config::{self, CosmicConfigEntry};
-use cosmic::iced::keyboard::Key;
-use cosmic::iced::{time, Alignment, Length, Subscription};
-use cosmic::widget::menu::key_bind::{KeyBind, Modifier};
+use cosmic::iced::clipboard::mime::AllowedMimeTypes;
+use cosmic::iced::keyboard::{self, key::Named, Key, Modifiers};
+use cosmic::iced::{event, time, window, Alignment, Event, Length, Point, Size, Subscription};
 use cosmic::widget::{self, icon, menu, nav_bar};
 use cosmic::{cosmic_theme, theme, Application, ApplicationExt, Apply, Element};
 use futures_util::SinkExt;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use url::Url;
 
-const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
-const APP_ICON: &[u8] = include_bytes!("../resources/icons/hicolor/scalable/apps/icon.svg");
+pub const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
+pub const APP_ICON: &[u8] = include_bytes!("../resources/icons/hicolor/scalable/apps/icon.svg");
+
+/// Browsing engine backing the web view, swapped for an in-memory one under test.
+#[cfg(not(test))]
+type Engine = web::Ultralight;
+#[cfg(test)]
+type Engine = web::MockEngine;
 
 /// The application model stores app-specific state used to describe its interface and
 /// drive its logic.
@@ -24,22 +43,71 @@ pub struct AppModel {
     core: Core,
     /// Display a context drawer with the designated page if defined.
     context_page: ContextPage,
-    /// Contains items assigned to the nav bar panel.
-    nav: nav_bar::Model,
-    /// Key bindings for the application's menu bar.
+    /// Key bindings for the application's menu bar and shortcuts.
     key_binds: HashMap<menu::KeyBind, MenuAction>,
     // Configuration data that persists between application runs.
     config: Config,
+    // Writes configuration changes, missing when the config directory is unavailable.
+    config_handler: Option<cosmic_config::Config>,
+    // where history, bookmarks, favicons and the session are kept, nowhere when missing
+    data_dir: Option<PathBuf>,
     // Embedded web view
-    webview: web::WebView<web::Ultralight, Message>,
-    // url of the webview
-    webview_url: Option<String>,
-    // the current view
-    current_view: Option<u32>,
-    // view count
-    num_views: u32,
-    // id for search bar
-    search_id: widget::Id,
+    webview: web::WebView<Engine, Message>,
+    // what internal pages read of the settings and bookmarks through the bridge
+    bridge: bridge::Reads,
+    // how far along the page load of each loading tab is, from 0 to 1
+    load_progress: HashMap<web::ViewId, f32>,
+    // pages visited, saved under the data directory
+    history: History,
+    // saved pages, kept under the data directory
+    bookmarks: Bookmarks,
+    // site icons, kept under the data directory
+    favicons: favicon::Cache,
+    // origins whose icon is being downloaded, or couldn't be in this run
+    favicon_requests: HashSet<String>,
+    // the bookmark or folder picked in the bookmark manager
+    bookmark_edit: Option<BookmarkEdit>,
+    // folder of the bookmarks bar whose contents are dropped down
+    bookmark_menu: Option<u64>,
+    // path typed into the bookmark import and export field
+    bookmarks_path: String,
+    // how the navigation each view was last asked to make came about
+    pending_transitions: HashMap<web::ViewId, Transition>,
+    // pages each tab went through, saved as the session
+    tab_sessions: HashMap<web::ViewId, session::Tab>,
+    // restored and reopened tabs whose views are being created, in creation order
+    restoring: VecDeque<RestoringTab>,
+    // tabs closed in this window, to reopen them
+    closed_tabs: session::ClosedTabs,
+    // restored tabs, whose back and forward the session drives as the engine's history of
+    // them starts at the restart
+    session_driven: HashSet<web::ViewId>,
+    // restored background tabs that load their page once shown
+    unloaded: HashSet<web::ViewId>,
+    // scroll positions restored pages go back to once loaded
+    pending_scrolls: HashMap<web::ViewId, (f64, f64)>,
+    // chords typed for an action in the shortcuts editor, by its position there
+    shortcut_edit: Option<(usize, String)>,
+    // notifications for errors that should not take down the browser
+    toasts: widget::toaster::Toasts<Message>,
+    // least severe console level shown in the console drawer
+    console_level: web::ConsoleLevel,
+    // names of the console levels, in `CONSOLE_LEVELS` order
+    console_levels: Vec<String>,
+    // expression typed into the console
+    console_input: String,
+    // expressions evaluated from the console and their results once known
+    console_history: Vec<(String, Option<Result<String, web::EngineError>>)>,
+    // window showing the web inspector of a tab
+    inspector_window: Option<window::Id>,
+    // browser window last focused, whose active tab the web view shows, `None` for the main one
+    window: Option<window::Id>,
+    // every browser window, the main one always among them
+    windows: BTreeMap<Option<window::Id>, BrowserWindow>,
+    // browser windows the tabs being created go to, in creation order
+    opening: VecDeque<Option<window::Id>>,
+    // path typed into the search engine import field
+    opensearch_path: String,
 }
 
 /// Messages emitted by the application and its widgets.
@@ -51,15 +119,74 @@ pub enum Message {
     UpdateConfig(Config),
     LaunchUrl(String),
     WebView(web::Action),
-    WebViewCreated,
-    WebViewClosed,
+    WebViewCreated(web::ViewId),
     UrlChanged(String),
-    TitleChanged(String),
-    CycleWebView,
-    GotoTab(u32),
+    UrlSubmitted(String),
+    SuggestionChosen(usize),
+    SuggestionKey(SuggestionKey),
+    SuggestionRemoved(usize),
+    ViewEvent(web::ViewId, web::ViewEvent),
+    GotoTab(web::ViewId),
+    OpenHistory,
+    Stop,
     NewTab,
     CloseTab(nav_bar::Id),
     Update,
+    WebViewError(web::EngineError),
+    CloseToast(widget::ToastId),
+    ScriptResult(Result<String, web::EngineError>),
+    ConsoleFilter(usize),
+    ConsoleInput(String),
+    ConsoleSubmit,
+    ConsoleClear,
+    Inspect,
+    WindowClosed(window::Id),
+    WindowEvent(window::Id, window::Event),
+    /// A message from what browser window `window` shows, which it acts on.
+    InWindow(Option<window::Id>, Box<Message>),
+    NewWindow,
+    NewPrivateWindow,
+    ActivateTab(nav_bar::Id),
+    SearchEngineDefault(usize),
+    SearchEngineRemove(usize),
+    OpenSearchPath(String),
+    OpenSearchImport,
+    ToggleBookmark,
+    ToggleBookmarksBar,
+    OpenBookmark(u64),
+    BookmarkMenu(Option<u64>),
+    BookmarkSelect(u64),
+    BookmarkTitle(String),
+    BookmarkTags(String),
+    BookmarkFolder(usize),
+    BookmarkSave,
+    BookmarkMove(u64, isize),
+    BookmarkRemove(u64),
+    BookmarkNewFolder,
+    BookmarksPath(String),
+    BookmarksImport,
+    BookmarksExport,
+    SaveSession,
+    ReopenClosedTab(usize),
+    Startup(usize),
+    Homepage(String),
+    FaviconFetched(String, Option<favicon::Icon>),
+    TabAction(TabAction),
+    TabDropped(nav_bar::Id, Option<TabDrag>),
+    Key(window::Id, Modifiers, Key),
+    CloseActiveTab,
+    CycleTab(isize),
+    SelectTab(usize),
+    SelectLastTab,
+    FocusAddressBar,
+    Find,
+    FindInput(String),
+    FindNext,
+    FindPrevious,
+    FindClose,
+    ShortcutInput(usize, String),
+    ShortcutSave(usize),
+    ShortcutReset(usize),
 }
 
 /// Create a COSMIC application from the app model
@@ -68,7 +195,7 @@ impl Application for AppModel {
     type Executor = cosmic::executor::Default;
 
     /// Data that your application receives to its init method.
-    type Flags = ();
+    type Flags = Flags;
 
     /// Messages which the application and its widgets will emit.
     type Message = Message;
@@ -85,19 +212,24 @@ impl Application for AppModel {
     }
 
     /// Initializes the application with any given flags and startup commands.
-    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
-        // Create a nav bar with three page items.
-        let nav = nav_bar::Model::default();
+    fn init(core: Core, flags: Self::Flags) -> (Self, Task<Self::Message>) {
+        // Optional configuration file for an application.
+        let config_handler = flags
+            .config
+            .then(|| cosmic_config::Config::new(Self::APP_ID, Config::VERSION).ok())
+            .flatten();
+        let data_file = |name: &str| flags.data_dir.as_ref().map(|dir| dir.join(name));
+        // shown once the app is up, it starts with what could be read
+        let mut load_errors = Vec::new();
 
         // Construct the app model with the runtime's core.
         let mut app = AppModel {
             core,
             context_page: ContextPage::default(),
-            nav,
             key_binds: HashMap::new(),
-            // Optional configuration file for an application.
-            config: cosmic_config::Config::new(Self::APP_ID, Config::VERSION)
-                .map(|context| match Config::get_entry(&context) {
+            config: config_handler
+                .as_ref()
+                .map(|context| match Config::get_entry(context) {
                     Ok(config) => config,
                     Err((_errors, config)) => {
                         // for why in errors {
@@ -108,100 +240,177 @@ impl Application for AppModel {
                     }
                 })
                 .unwrap_or_default(),
+            config_handler,
+            data_dir: flags.data_dir.clone(),
             webview: web::WebView::new()
                 .on_create_view(Message::WebViewCreated)
-                .on_close_view(Message::WebViewClosed)
-                .on_url_change(Message::UrlChanged)
-                .on_title_change(Message::TitleChanged),
-            webview_url: None,
-            current_view: Some(0), // this will lead to a crash if init isnt called
-            num_views: 1,
-            search_id: widget::Id::unique(),
+                .on_view_event(Message::ViewEvent)
+                .on_script_result(Message::ScriptResult)
+                .on_error(Message::WebViewError),
+            bridge: bridge::Reads::default(),
+            load_progress: HashMap::new(),
+            history: data_file("history.jsonl")
+                .map(|path| {
+                    History::open(path).unwrap_or_else(|err| {
+                        load_errors.push(format!("failed to load history: {err}"));
+                        History::default()
+                    })
+                })
+                .unwrap_or_default(),
+            bookmarks: data_file("bookmarks.json")
+                .map(|path| {
+                    Bookmarks::open(path).unwrap_or_else(|err| {
+                        load_errors.push(format!("failed to load bookmarks: {err}"));
+                        Bookmarks::default()
+                    })
+                })
+                .unwrap_or_default(),
+            favicons: data_file("favicons")
+                .map(|dir| {
+                    favicon::Cache::open(dir).unwrap_or_else(|err| {
+                        load_errors.push(format!("failed to load favicons: {err}"));
+                        favicon::Cache::default()
+                    })
+                })
+                .unwrap_or_default(),
+            favicon_requests: HashSet::new(),
+            bookmark_edit: None,
+            bookmark_menu: None,
+            bookmarks_path: String::new(),
+            pending_transitions: HashMap::new(),
+            tab_sessions: HashMap::new(),
+            restoring: VecDeque::new(),
+            closed_tabs: session::ClosedTabs::default(),
+            session_driven: HashSet::new(),
+            unloaded: HashSet::new(),
+            pending_scrolls: HashMap::new(),
+            shortcut_edit: None,
+            toasts: widget::toaster::Toasts::new(Message::CloseToast),
+            console_level: web::ConsoleLevel::default(),
+            console_levels: vec![
+                fl!("console-debug"),
+                fl!("console-log"),
+                fl!("console-info"),
+                fl!("console-warning"),
+                fl!("console-error"),
+            ],
+            console_input: String::new(),
+            console_history: Vec::new(),
+            inspector_window: None,
+            window: None,
+            windows: BTreeMap::from([(None, BrowserWindow::new())]),
+            opening: VecDeque::new(),
+            opensearch_path: String::new(),
         };
 
         // map keybinds
-        macro_rules! bind {
-            ([$($modifier:ident),* $(,)?], $key:expr, $action:ident) => {{
-                app.key_binds.insert(
-                    KeyBind {
-                        modifiers: vec![$(Modifier::$modifier),*],
-                        key: $key,
-                    },
-                    MenuAction::$action,
-                );
-            }};
-        }
-        bind!([Ctrl], Key::Character("t".into()), NewTab);
-
-        app.webview.init();
-        // Create a startup command that sets the window title.
-        let command = app.update_title();
+        app.key_binds = shortcuts::key_binds(&app.config.shortcuts);
 
-        app.nav
-            .insert()
-            .text("New Tab")
-            .data::<u32>(0)
-            .icon(icon::from_name("text-html-symbolic"))
-            .closable()
-            .activate();
+        app.webview.register_scheme(pages::SCHEME, pages::serve);
+        app.bridge = bridge::register(&mut app.webview);
+        app.bridge.set_settings(&app.config);
+        app.bridge.set_bookmarks(&app.bookmarks);
+        let mut commands: Vec<_> = load_errors
+            .into_iter()
+            .map(|message| app.toast(message))
+            .collect();
+        commands.push(app.startup());
 
-        (app, command)
+        (app, Task::batch(commands))
     }
 
     /// Elements to pack at the start of the header bar.
     fn header_start(&self) -> Vec<Element<Self::Message>> {
+        let recently_closed = if self.closed_tabs.is_empty() {
+            vec![menu::Item::ButtonDisabled(
+                fl!("no-closed-tabs"),
+                None,
+                MenuAction::ReopenClosedTab(0),
+            )]
+        } else {
+            self.closed_tabs
+                .iter()
+                .enumerate()
+                .map(|(index, closed)| {
+                    let title = match closed.tab.current() {
+                        Some(entry) if !entry.title.is_empty() => entry.title.clone(),
+                        Some(entry) => entry.url.clone(),
+                        None => String::from("New Tab"),
+                    };
+                    menu::Item::Button(title, None, MenuAction::ReopenClosedTab(index))
+                })
+                .collect()
+        };
         let menu_bar = menu::bar(vec![
             menu::Tree::with_children(
                 menu::root(fl!("file")),
                 menu::items(
                     &self.key_binds,
-                    vec![menu::Item::Button(fl!("new-tab"), None, MenuAction::NewTab)],
+                    vec![
+                        menu::Item::Button(fl!("new-tab"), None, MenuAction::NewTab),
+                        menu::Item::Button(fl!("new-window"), None, MenuAction::NewWindow),
+                        menu::Item::Button(
+                            fl!("new-private-window"),
+                            None,
+                            MenuAction::NewPrivateWindow,
+                        ),
+                        menu::Item::Button(fl!("close-tab"), None, MenuAction::CloseTab),
+                        menu::Item::Folder(fl!("recently-closed"), recently_closed),
+                    ],
                 ),
             ),
             menu::Tree::with_children(
                 menu::root(fl!("view")),
                 menu::items(
                     &self.key_binds,
-                    vec![menu::Item::Button(fl!("about"), None, MenuAction::About)],
+                    vec![
+                        menu::Item::Button(fl!("find"), None, MenuAction::Find),
+                        menu::Item::Button(fl!("console"), None, MenuAction::Console),
+                        menu::Item::Button(fl!("history"), None, MenuAction::History),
+                        menu::Item::Button(fl!("inspect"), None, MenuAction::Inspect),
+                        menu::Item::Button(fl!("search-engines"), None, MenuAction::SearchEngines),
+                        menu::Item::Button(fl!("settings"), None, MenuAction::Settings),
+                        menu::Item::Button(fl!("shortcuts"), None, MenuAction::Shortcuts),
+                        menu::Item::Button(fl!("about"), None, MenuAction::About),
+                    ],
+                ),
+            ),
+            menu::Tree::with_children(
+                menu::root(fl!("bookmarks")),
+                menu::items(
+                    &self.key_binds,
+                    vec![
+                        menu::Item::Button(fl!("bookmark-page"), None, MenuAction::BookmarkPage),
+                        menu::Item::CheckBox(
+                            fl!("bookmarks-bar"),
+                            None,
+                            self.config.show_bookmarks_bar,
+                            MenuAction::BookmarksBar,
+                        ),
+                        menu::Item::Button(fl!("manage-bookmarks"), None, MenuAction::Bookmarks),
+                    ],
                 ),
             ),
         ]);
 
-        vec![menu_bar.into()]
+        let mut elements = vec![menu_bar.into()];
+        if let Some(browser) = self.windows.get(&None) {
+            elements.push(self.navigation(browser));
+        }
+        elements
+            .into_iter()
+            .map(|element| in_window(None, element))
+            .collect()
     }
 
     fn header_center(&self) -> Vec<Element<Self::Message>> {
-        let mut elements = Vec::with_capacity(2);
-
-        if let Some(term) = self.webview_url.clone() {
-            if self.core.is_condensed() {
-                elements.push(
-                    widget::button::icon(widget::icon::from_name("system-search-symbolic"))
-                        .on_press(Message::GotoTab(0))
-                        .padding(8)
-                        .selected(true)
-                        .into(),
-                );
-            } else {
-                elements.push(
-                    widget::text_input::search_input("", term)
-                        .width(Length::Fill)
-                        .id(self.search_id.clone())
-                        .on_clear(Message::NewTab)
-                        .on_input(Message::UrlChanged)
-                        .into(),
-                );
-            }
-        } else {
-            elements.push(
-                widget::button::icon(widget::icon::from_name("system-search-symbolic"))
-                    .on_press(Message::NewTab)
-                    .padding(8)
-                    .into(),
-            );
-        }
-
-        elements
+        let Some(browser) = self.windows.get(&None) else {
+            return Vec::new();
+        };
+        self.address_bar(browser)
+            .into_iter()
+            .map(|element| in_window(None, element))
+            .collect()
     }
 
     fn nav_bar(&self) -> Option<Element<cosmic::Action<Self::Message>>> {
@@ -219,7 +428,15 @@ impl Application for AppModel {
                         .size(16)
                         .icon(),
                 )
-                .on_close(|id| cosmic::Action::App(Message::CloseTab(id)))
+                .on_close(|id| {
+                    cosmic::Action::App(Message::InWindow(None, Box::new(Message::CloseTab(id))))
+                })
+                .context_menu(self.nav_context_menu(self.core().nav_bar_context()))
+                .enable_tab_drag(String::from(TAB_MIME))
+                .on_dnd_drop(|target, drag: Option<TabDrag>, _| {
+                    let dropped = Message::TabDropped(target, drag);
+                    cosmic::Action::App(Message::InWindow(None, Box::new(dropped)))
+                })
                 .into_container()
                 .width(Length::Shrink)
                 .height(Length::Shrink);
@@ -233,7 +450,56 @@ impl Application for AppModel {
 
     /// Enables the COSMIC application to create a nav bar with this model.
     fn nav_model(&self) -> Option<&nav_bar::Model> {
-        Some(&self.nav)
+        self.windows.get(&None).map(|browser| &browser.nav)
+    }
+
+    /// The menu of the tab `id` in the nav bar.
+    fn nav_context_menu(
+        &self,
+        id: nav_bar::Id,
+    ) -> Option<Vec<menu::Tree<cosmic::Action<Self::Message>>>> {
+        let browser = self.windows.get(&None)?;
+        let nav = &browser.nav;
+        nav.data::<web::ViewId>(id)?;
+        let is_pinned = |tab| browser.is_pinned(tab);
+        let item = |label, enabled, action| {
+            if enabled {
+                menu::Item::Button(label, None, action)
+            } else {
+                menu::Item::ButtonDisabled(label, None, action)
+            }
+        };
+        let pin = if is_pinned(id) {
+            menu::Item::Button(fl!("unpin-tab"), None, TabAction::Unpin(id))
+        } else {
+            menu::Item::Button(fl!("pin-tab"), None, TabAction::Pin(id))
+        };
+        let others = nav.iter().any(|tab| tab != id && !is_pinned(tab));
+        let below = nav
+            .iter()
+            .skip_while(|&tab| tab != id)
+            .skip(1)
+            .any(|tab| !is_pinned(tab));
+        // the main window keeps its last tab
+        let alone = nav.iter().count() <= 1;
+
+        Some(menu::items(
+            &HashMap::new(),
+            vec![
+                menu::Item::Button(fl!("reload"), None, TabAction::Reload(id)),
+                menu::Item::Button(fl!("duplicate-tab"), None, TabAction::Duplicate(id)),
+                pin,
+                item(
+                    fl!("move-to-new-window"),
+                    !alone,
+                    TabAction::MoveToNewWindow(id),
+                ),
+                menu::Item::Divider,
+                item(fl!("close-other-tabs"), others, TabAction::CloseOthers(id)),
+                item(fl!("close-tabs-below"), below, TabAction::CloseBelow(id)),
+                menu::Item::Button(fl!("close-tab"), None, TabAction::Close(id)),
+            ],
+        ))
     }
 
     /// Display a context drawer if the context page is requested.
@@ -248,6 +514,31 @@ impl Application for AppModel {
                 Message::ToggleContextPage(ContextPage::About),
             )
             .title(fl!("about")),
+            ContextPage::Console => context_drawer::context_drawer(
+                self.console(),
+                Message::ToggleContextPage(ContextPage::Console),
+            )
+            .title(fl!("console")),
+            ContextPage::SearchEngines => context_drawer::context_drawer(
+                self.search_engines(),
+                Message::ToggleContextPage(ContextPage::SearchEngines),
+            )
+            .title(fl!("search-engines")),
+            ContextPage::Bookmarks => context_drawer::context_drawer(
+                self.bookmark_manager(),
+                Message::ToggleContextPage(ContextPage::Bookmarks),
+            )
+            .title(fl!("bookmarks")),
+            ContextPage::Settings => context_drawer::context_drawer(
+                self.settings(),
+                Message::ToggleContextPage(ContextPage::Settings),
+            )
+            .title(fl!("settings")),
+            ContextPage::Shortcuts => context_drawer::context_drawer(
+                self.shortcuts(),
+                Message::ToggleContextPage(ContextPage::Shortcuts),
+            )
+            .title(fl!("shortcuts")),
         })
     }
 
@@ -256,7 +547,25 @@ impl Application for AppModel {
     /// Application events will be processed through the view. Any messages emitted by
     /// events received by widgets will be passed to the update method.
     fn view(&self) -> Element<Self::Message> {
-        self.webview.view().map(Message::WebView).into()
+        let content = match self.windows.get(&None) {
+            Some(browser) => in_window(None, self.page(None, browser)),
+            None => widget::horizontal_space().into(),
+        };
+        widget::toaster(&self.toasts, content)
+    }
+
+    /// Describes the interface of windows other than the main one.
+    fn view_window(&self, id: window::Id) -> Element<Self::Message> {
+        match self.webview.inspector_view() {
+            Some(inspector) if self.inspector_window == Some(id) => inspector.map(Message::WebView),
+            _ => match self.windows.get(&Some(id)) {
+                Some(browser) => {
+                    let content = in_window(Some(id), self.window_view(Some(id), browser));
+                    widget::toaster(&self.toasts, content)
+                }
+                None => widget::horizontal_space().into(),
+            },
+        }
     }
 
     /// Register subscriptions for this application.
@@ -267,7 +576,7 @@ impl Application for AppModel {
     fn subscription(&self) -> Subscription<Self::Message> {
         struct MySubscription;
 
-        Subscription::batch(vec![
+        let mut subscriptions = vec![
             // Create a subscription which emits updates through a channel.
             Subscription::run_with_id(
                 std::any::TypeId::of::<MySubscription>(),
@@ -286,10 +595,24 @@ impl Application for AppModel {
                     // }
                     Message::UpdateConfig(update.config)
                 }),
-            time::every(std::time::Duration::from_millis(10))
-                .map(|_| web::Action::Update)
-                .map(Message::WebView),
-        ])
+            window::close_events().map(Message::WindowClosed),
+            window::events().map(|(id, event)| Message::WindowEvent(id, event)),
+            time::every(self.webview.update_interval())
+                .map(|_| Message::WebView(web::Action::Update)),
+            time::every(SESSION_SAVE_INTERVAL).map(|_| Message::SaveSession),
+            event::listen_with(shortcut_key),
+        ];
+
+        if self.webview.is_busy() {
+            // paint in step with the display, the timer above keeps hidden windows loading
+            subscriptions.push(window::frames().map(|_| Message::WebView(web::Action::Update)));
+        }
+
+        if !self.focused().suggestions.is_empty() {
+            subscriptions.push(event::listen_with(suggestion_key));
+        }
+
+        Subscription::batch(subscriptions)
     }
 
     /// Handles messages emitted by the application and its widgets.
@@ -318,6 +641,8 @@ impl Application for AppModel {
             }
 
             Message::UpdateConfig(config) => {
+                self.key_binds = shortcuts::key_binds(&config.shortcuts);
+                self.bridge.set_settings(&config);
                 self.config = config;
             }
 
@@ -329,105 +654,682 @@ impl Application for AppModel {
             },
 
             Message::WebView(msg) => {
+                // the widget asking for a size is the one of the focused window
+                if let web::Action::Resize(size) = msg {
+                    self.focused_mut().view_size = Some(size);
+                }
+                // reserved chords run their action from the key subscription instead
+                if let web::Action::SendKeyboardEvent(
+                    keyboard::Event::KeyPressed { key, modifiers, .. }
+                    | keyboard::Event::KeyReleased { key, modifiers, .. },
+                ) = &msg
+                {
+                    if shortcuts::reserved_action(&self.key_binds, *modifiers, key).is_some() {
+                        return Task::none();
+                    }
+                }
+                let transition = match msg {
+                    web::Action::GoBack | web::Action::GoForward => Some(Transition::BackForward),
+                    web::Action::Refresh => Some(Transition::Reload),
+                    _ => None,
+                };
+                let offset = match msg {
+                    web::Action::GoBack => Some(-1),
+                    web::Action::GoForward => Some(1),
+                    _ => None,
+                };
+                let current = self.webview.current_view();
+                let driven = current.filter(|view| self.session_driven.contains(view));
+                if let (Some(offset), Some(view)) = (offset, driven) {
+                    let url = self
+                        .tab_sessions
+                        .get(&view)
+                        .and_then(|tab| tab.entry_at(offset))
+                        .and_then(|entry| Url::parse(&entry.url).ok());
+                    return match url {
+                        Some(url) => self.go_to(url, Transition::BackForward),
+                        None => Task::none(),
+                    };
+                }
+                if let (Some(transition), Some(view)) = (transition, current) {
+                    self.pending_transitions.insert(view, transition);
+                }
                 return self.webview.update(msg);
             }
 
-            Message::WebViewCreated => {
-                self.num_views += 1;
-                return cosmic::Task::done(Message::CycleWebView).map(cosmic::Action::from);
+            Message::WebViewCreated(view) => {
+                let window = self.opening.pop_front().unwrap_or(self.window);
+                let focus = self.focus_window(window);
+                let scale = self.scale_factor(window);
+                let rescale = self.webview.update(web::Action::RescaleView(view, scale));
+                return Task::batch([focus, rescale, self.add_tab(view)]);
             }
 
-            Message::WebViewClosed => {
-                let mut tab_id = None;
-                for tab in self.nav.iter() {
-                    if let Some(cv) = self.current_view {
-                        if let Some(data) = self.nav.data::<u32>(tab) {
-                            if *data == cv {
-                                tab_id = Some(tab);
-                            }
-                        }
+            Message::UrlChanged(url) => {
+                // only typing more gets completed, deleting the completion leaves it off
+                let typed = self.focused().suggestions.text();
+                let complete = url.len() > typed.len() && url.starts_with(typed);
+                self.suggest(&url, complete);
+                let title = self.webview.get_current_view_title();
+                let browser = self.focused_mut();
+                let tab = browser.nav.active();
+                browser.set_tab_title(tab, title);
+                match browser.suggestions.completion() {
+                    Some(completion) => {
+                        let text = completion.text.clone();
+                        let (start, end) = (url.chars().count(), text.chars().count());
+                        browser.url = Some(text);
+                        let search_id = browser.search_id.clone();
+                        return widget::text_input::select_range(search_id, start, end);
                     }
+                    None => browser.url = Some(url),
                 }
+            }
 
-                if tab_id.is_some() {
-                    self.nav.activate(tab_id.expect("tab id not found"));
+            Message::UrlSubmitted(text) => {
+                let chosen = std::mem::take(&mut self.focused_mut().suggestions)
+                    .chosen()
+                    .cloned();
+                match chosen {
+                    Some(suggestion) => return self.open_suggestion(suggestion),
+                    None => return self.open_input(&text),
                 }
             }
 
-            Message::UrlChanged(url) => {
-                self.webview_url = Some(url);
-                self.nav
-                    .text_set(self.nav.active(), self.webview.get_current_view_title());
+            Message::SuggestionChosen(index) => {
+                let suggestions = std::mem::take(&mut self.focused_mut().suggestions);
+                if let Some(suggestion) = suggestions.items().get(index) {
+                    return self.open_suggestion(suggestion.clone());
+                }
             }
 
-            Message::TitleChanged(title) => {
-                self.nav.text_set(self.nav.active(), title);
+            Message::SuggestionKey(key) => match key {
+                SuggestionKey::Down => self.focused_mut().suggestions.select_next(),
+                SuggestionKey::Up => self.focused_mut().suggestions.select_previous(),
+                SuggestionKey::Complete => {
+                    let completion = self.focused().suggestions.completion().cloned();
+                    if let Some(completion) = completion {
+                        self.suggest(&completion.text, true);
+                        let browser = self.focused_mut();
+                        browser.url = Some(completion.text);
+                        return widget::text_input::move_cursor_to_end(browser.search_id.clone());
+                    }
+                }
+                SuggestionKey::Dismiss => {
+                    self.focused_mut().suggestions = omnibox::Suggestions::default();
+                }
+                SuggestionKey::Delete => {
+                    if let Some(index) = self.focused().suggestions.selected() {
+                        return self.update(Message::SuggestionRemoved(index));
+                    }
+                }
+            },
+
+            Message::SuggestionRemoved(index) => {
+                let suggestions = &mut self.focused_mut().suggestions;
+                let is_history = suggestions
+                    .items()
+                    .get(index)
+                    .is_some_and(|item| item.kind == omnibox::SuggestionKind::History);
+                if is_history {
+                    if let Some(suggestion) = suggestions.remove(index) {
+                        let removed = self.history.remove(suggestion.url.as_str());
+                        return self.report_history_error(removed);
+                    }
+                }
             }
 
-            Message::CycleWebView => {
-                self.current_view = Some(self.num_views - 1);
-                return self
-                    .webview
-                    .update(web::Action::ChangeView(self.num_views - 1));
+            Message::ViewEvent(view, event) => return self.view_event(view, event),
+
+            Message::GotoTab(view) => {
+                if let Some(tab) = self.focused().tab_for_view(view) {
+                    return self.show_tab(tab);
+                }
+            }
+
+            Message::OpenHistory => {
+                return self.go_to(Page::History.url(), Transition::Typed);
             }
 
-            Message::GotoTab(tab) => {
-                self.current_view = Some(tab);
-                if tab <= self.num_views {
-                    return self.webview.update(web::Action::ChangeView(tab));
+            Message::Stop => {
+                if let Some(view) = self.webview.current_view() {
+                    self.load_progress.remove(&view);
+                    let icon = self.tab_icon(view);
+                    let browser = self.focused_mut();
+                    let tab = browser.nav.active();
+                    browser.nav.icon_set(tab, icon);
                 }
+                return self.webview.update(web::Action::Stop);
             }
 
             Message::Update => {
                 return self.webview.update(web::Action::Update);
             }
 
-            Message::NewTab => {
-                self.nav
-                    .insert()
-                    .text("New Tab")
-                    .data::<u32>(self.num_views)
-                    .icon(icon::from_name("text-html-symbolic"))
-                    .closable()
-                    .activate();
-
-                return self
-                    .webview
-                    .update(web::Action::CreateView(web::PageType::Html(
-                        startpage::get_startpage(),
-                    )))
-                    .map(cosmic::Action::from);
+            Message::WebViewError(error) => {
+                return self.toast(error.to_string());
             }
 
-            Message::CloseTab(id) => {
-                if let Some(view_index) = self.nav.data::<u32>(id) {
-                    self.num_views -= 1;
-                    // if they close the last tab exit gracefully
-                    if self.num_views < 1 {
-                        return cosmic::iced::exit();
+            Message::CloseToast(id) => {
+                self.toasts.remove(id);
+            }
+
+            Message::ScriptResult(result) => {
+                if let Some((_, output)) = self
+                    .console_history
+                    .iter_mut()
+                    .find(|(_, output)| output.is_none())
+                {
+                    *output = Some(result);
+                }
+            }
+
+            Message::ConsoleFilter(index) => {
+                if let Some(level) = CONSOLE_LEVELS.get(index) {
+                    self.console_level = *level;
+                }
+            }
+
+            Message::ConsoleInput(input) => {
+                self.console_input = input;
+            }
+
+            Message::ConsoleSubmit => {
+                let input = std::mem::take(&mut self.console_input);
+                if !input.trim().is_empty() {
+                    self.console_history.push((input.clone(), None));
+                    return self.webview.update(web::Action::EvaluateScript(input));
+                }
+            }
+
+            Message::ConsoleClear => {
+                self.console_history.clear();
+                if let Err(err) = self.webview.clear_console() {
+                    return self.toast(err.to_string());
+                }
+            }
+
+            Message::Inspect => {
+                if let Some(id) = self.inspector_window {
+                    return window::gain_focus(id);
+                }
+                let task = self.webview.update(web::Action::OpenInspector);
+                if !self.webview.has_inspector() {
+                    return task;
+                }
+                let (id, open) = window::open(window::Settings {
+                    size: Size::new(1024.0, 768.0),
+                    ..Default::default()
+                });
+                self.inspector_window = Some(id);
+                return Task::batch([
+                    task,
+                    open.discard(),
+                    self.set_window_title(fl!("inspector"), id),
+                ]);
+            }
+
+            Message::WindowClosed(id) => {
+                if self.inspector_window == Some(id) {
+                    self.inspector_window = None;
+                    return self.webview.update(web::Action::CloseInspector);
+                }
+                if let Some(window @ Some(_)) = self.browser_window(id) {
+                    return self.close_window(window);
+                }
+            }
+
+            Message::WindowEvent(id, event) => {
+                if let window::Event::Rescaled(scale) = event {
+                    if self.inspector_window == Some(id) {
+                        let rescale = web::Action::Rescale(scale.into());
+                        return self
+                            .webview
+                            .update(web::Action::Inspector(Box::new(rescale)));
+                    }
+                }
+                let Some(window) = self.browser_window(id) else {
+                    return Task::none();
+                };
+                match event {
+                    window::Event::Focused => return self.focus_window(window),
+                    window::Event::Opened { position, size } => {
+                        if let Some(browser) = self.windows.get_mut(&window) {
+                            browser.geometry = Some(session::Geometry {
+                                position: position.map(|position| (position.x, position.y)),
+                                size: (size.width, size.height),
+                            });
+                        }
+                        // not every window system reports the scale before it changes
+                        return window::get_scale_factor(id).map(move |scale| {
+                            cosmic::Action::from(Message::WindowEvent(
+                                id,
+                                window::Event::Rescaled(scale),
+                            ))
+                        });
                     }
-                    let task: Task<Message> = self
-                        .webview
-                        .update(web::Action::CloseView(*view_index))
-                        .map(cosmic::Action::from);
-
-                    // shift down the index of every tab above the one removed
-                    let mut updates = Vec::new();
-                    for tab in self.nav.iter() {
-                        if let Some(index) = self.nav.data::<u32>(tab) {
-                            if index > view_index {
-                                updates.push((tab, index - 1));
+                    window::Event::Rescaled(scale) => return self.rescale_window(window, scale),
+                    window::Event::Moved(position) => {
+                        if let Some(browser) = self.windows.get_mut(&window) {
+                            let geometry = browser.geometry.get_or_insert_with(Default::default);
+                            geometry.position = Some((position.x, position.y));
+                        }
+                    }
+                    window::Event::Resized(size) => {
+                        if let Some(browser) = self.windows.get_mut(&window) {
+                            let geometry = browser.geometry.get_or_insert_with(Default::default);
+                            geometry.size = (size.width, size.height);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            Message::InWindow(window, message) => {
+                let focus = self.focus_window(window);
+                return Task::batch([focus, self.update(*message)]);
+            }
+
+            Message::NewWindow => return self.new_window(false),
+
+            Message::NewPrivateWindow => return self.new_window(true),
+
+            Message::ActivateTab(tab) => return self.show_tab(tab),
+
+            Message::SearchEngineDefault(index) => {
+                if let Some(engine) = self.config.search_engines.get(index) {
+                    let engines = self.config.search_engines.clone();
+                    let default = engine.name.clone();
+                    return self.save_search_engines(engines, default);
+                }
+            }
+
+            Message::SearchEngineRemove(index) => {
+                // something has to run searches that aren't addresses
+                if index < self.config.search_engines.len() && self.config.search_engines.len() > 1
+                {
+                    let mut engines = self.config.search_engines.clone();
+                    let removed = engines.remove(index);
+                    let default = if removed.name == self.config.default_search_engine {
+                        engines[0].name.clone()
+                    } else {
+                        self.config.default_search_engine.clone()
+                    };
+                    return self.save_search_engines(engines, default);
+                }
+            }
+
+            Message::OpenSearchPath(path) => {
+                self.opensearch_path = path;
+            }
+
+            Message::OpenSearchImport => {
+                let path = self.opensearch_path.trim().to_string();
+                match SearchEngine::import_opensearch(Path::new(&path)) {
+                    Ok(engine) => {
+                        self.opensearch_path.clear();
+                        let mut engines = self.config.search_engines.clone();
+                        // importing an engine again updates it
+                        match engines.iter_mut().find(|known| known.name == engine.name) {
+                            Some(known) => {
+                                let keyword = known.keyword.take();
+                                *known = SearchEngine { keyword, ..engine };
                             }
+                            None => engines.push(engine),
                         }
+                        let default = self.config.default_search_engine.clone();
+                        return self.save_search_engines(engines, default);
+                    }
+                    Err(err) => return self.toast(format!("{path}: {err}")),
+                }
+            }
+
+            Message::ToggleBookmark => {
+                let Some(view) = self.webview.current_view() else {
+                    return Task::none();
+                };
+                let url = self.webview.get_url(view);
+                if !has_address(&url) {
+                    return Task::none();
+                }
+                let result = match self.bookmarks.find_url(&url).map(|bookmark| bookmark.id) {
+                    Some(id) => self.bookmarks.remove(id).map(drop),
+                    None => {
+                        let title = match self.focused().active_title() {
+                            "" => url.clone(),
+                            title => title.to_string(),
+                        };
+                        self.bookmarks
+                            .add(bookmarks::ROOT, &title, &url, SystemTime::now())
+                            .map(drop)
                     }
+                };
+                return self.bookmarks_changed(result);
+            }
+
+            Message::ToggleBookmarksBar => {
+                return self.update_config(|config| {
+                    config.show_bookmarks_bar = !config.show_bookmarks_bar;
+                });
+            }
 
-                    for (tab, new_index) in updates {
-                        self.nav.data_set::<u32>(tab, new_index);
+            Message::OpenBookmark(id) => {
+                self.bookmark_menu = None;
+                if let Some(Node::Bookmark(bookmark)) = self.bookmarks.get(id) {
+                    match Url::parse(&bookmark.url) {
+                        Ok(url) => return self.go_to(url, Transition::Bookmark),
+                        Err(err) => return self.toast(format!("{}: {err}", bookmark.url)),
                     }
+                }
+            }
 
-                    self.nav.remove(id);
-                    return task;
+            Message::BookmarkMenu(folder) => {
+                self.bookmark_menu = folder;
+            }
+
+            Message::BookmarkSelect(id) => {
+                self.select_bookmark(id);
+            }
+
+            Message::BookmarkTitle(title) => {
+                if let Some(edit) = &mut self.bookmark_edit {
+                    edit.title = title;
+                }
+            }
+
+            Message::BookmarkTags(tags) => {
+                if let Some(edit) = &mut self.bookmark_edit {
+                    edit.tags = tags;
+                }
+            }
+
+            Message::BookmarkFolder(index) => {
+                let Some(edit) = &self.bookmark_edit else {
+                    return Task::none();
+                };
+                if let Some(&folder) = edit.folder_ids.get(index) {
+                    let result = self.bookmarks.move_to(edit.id, folder, usize::MAX);
+                    return self.bookmarks_changed(result);
+                }
+            }
+
+            Message::BookmarkSave => {
+                let Some(edit) = &self.bookmark_edit else {
+                    return Task::none();
+                };
+                let tags = edit
+                    .tags
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from)
+                    .collect();
+                let result = self.bookmarks.update(edit.id, edit.title.trim(), tags);
+                return self.bookmarks_changed(result);
+            }
+
+            Message::BookmarkMove(id, offset) => {
+                let result = self.bookmarks.move_by(id, offset);
+                return self.bookmarks_changed(result);
+            }
+
+            Message::BookmarkRemove(id) => {
+                let result = self.bookmarks.remove(id);
+                return self.bookmarks_changed(result);
+            }
+
+            Message::BookmarkNewFolder => {
+                let result = self
+                    .bookmarks
+                    .add_folder(bookmarks::ROOT, &fl!("new-folder"));
+                if let Ok(Some(id)) = result {
+                    self.select_bookmark(id);
+                }
+                return self.bookmarks_changed(result);
+            }
+
+            Message::BookmarksPath(path) => {
+                self.bookmarks_path = path;
+            }
+
+            Message::BookmarksImport => {
+                let path = self.bookmarks_path.trim().to_string();
+                let imported = std::fs::read_to_string(&path)
+                    .and_then(|html| self.bookmarks.import_html(&html));
+                match imported {
+                    Ok(count) => {
+                        self.bookmarks_path.clear();
+                        let changed = self.bookmarks_changed(Ok(count));
+                        let toast = self.toast(fl!("bookmarks-imported", count = count));
+                        return Task::batch([changed, toast]);
+                    }
+                    Err(err) => return self.toast(format!("{path}: {err}")),
+                }
+            }
+
+            Message::BookmarksExport => {
+                let path = self.bookmarks_path.trim().to_string();
+                match std::fs::write(&path, self.bookmarks.to_html()) {
+                    Ok(()) => {
+                        self.bookmarks_path.clear();
+                        return self.toast(fl!("bookmarks-exported", path = path.as_str()));
+                    }
+                    Err(err) => return self.toast(format!("{path}: {err}")),
+                }
+            }
+
+            Message::SaveSession => return self.save_session(),
+
+            Message::ReopenClosedTab(index) => {
+                let Some(closed) = self.closed_tabs.take(index) else {
+                    return Task::none();
+                };
+                let title = closed
+                    .tab
+                    .current()
+                    .map(|entry| entry.title.clone())
+                    .unwrap_or_default();
+                self.restoring.push_back(RestoringTab {
+                    tab: closed.tab,
+                    active: true,
+                    position: Some(closed.position),
+                });
+                return self.open_tab(web::PageType::Html(placeholder_page(&title)));
+            }
+
+            Message::Startup(index) => {
+                if let Some(&startup) = STARTUP_OPTIONS.get(index) {
+                    return self.update_config(|config| config.startup = startup);
+                }
+            }
+
+            Message::Homepage(homepage) => {
+                return self.update_config(|config| config.homepage = homepage);
+            }
+
+            Message::NewTab => {
+                return self.open_tab(new_tab_page());
+            }
+
+            Message::CloseTab(id) => {
+                let browser = self.focused();
+                let Some(&view) = browser.nav.data::<web::ViewId>(id) else {
+                    return Task::none();
+                };
+                let was_active = browser.nav.is_active(id);
+                let Some(next) = browser.successor(id) else {
+                    return self.close_last_tab(id, view);
+                };
+                let mut tasks = vec![self.forget_tab(self.window, id, view)];
+                if was_active {
+                    tasks.push(self.show_tab(next));
+                }
+                return Task::batch(tasks);
+            }
+
+            Message::FaviconFetched(origin, icon) => {
+                // origins without an icon stay requested, so they aren't tried again
+                let Some(icon) = icon else {
+                    return Task::none();
+                };
+                self.favicon_requests.remove(&origin);
+                let mut saved = Task::none();
+                if let Err(err) = self.favicons.insert(&origin, icon) {
+                    saved = self.toast(format!("failed to save favicon: {err}"));
+                }
+                // loading tabs get theirs once loaded
+                let icons: Vec<_> = self
+                    .windows
+                    .iter()
+                    .flat_map(|(&window, browser)| {
+                        browser.tabs().into_iter().map(move |tab| (window, tab))
+                    })
+                    .filter(|(_, (_, view))| !self.load_progress.contains_key(view))
+                    .map(|(window, (tab, view))| (window, tab, self.tab_icon(view)))
+                    .collect();
+                for (window, tab, icon) in icons {
+                    if let Some(browser) = self.windows.get_mut(&window) {
+                        browser.nav.icon_set(tab, icon);
+                    }
+                }
+                return saved;
+            }
+
+            Message::TabAction(action) => return self.tab_action(action),
+
+            Message::TabDropped(target, drag) => {
+                let position = self.focused().nav.position(target);
+                let (Some(TabDrag(view)), Some(position)) = (drag, position) else {
+                    return Task::none();
+                };
+                let position = usize::from(position);
+                // tabs dragged over from another window move into this one
+                let Some((from, dragged)) = self.find_tab(view) else {
+                    return Task::none();
+                };
+                if from == self.window {
+                    self.focused_mut().move_tab(dragged, position);
+                    return Task::none();
+                }
+                // private tabs stay among private windows, and the others out of them
+                if self.is_private(from) != self.focused().private {
+                    return Task::none();
+                }
+                if let Some((moving, detach)) = self.detach_tab(from, dragged) {
+                    return Task::batch([detach, self.attach_tab(moving, position)]);
+                }
+            }
+
+            Message::Key(window, modifiers, key) => {
+                // the inspector window keeps its keys
+                if self.inspector_window == Some(window) {
+                    return Task::none();
+                }
+                let focus = match self.browser_window(window) {
+                    Some(window) => self.focus_window(window),
+                    None => Task::none(),
+                };
+                if let Some(action) = shortcuts::reserved_action(&self.key_binds, modifiers, &key) {
+                    let message = menu::action::MenuAction::message(&action);
+                    return Task::batch([focus, self.update(message)]);
+                }
+                return focus;
+            }
+
+            Message::CloseActiveTab => {
+                return self.update(Message::CloseTab(self.focused().nav.active()));
+            }
+
+            Message::CycleTab(offset) => {
+                let nav = &self.focused().nav;
+                let tabs: Vec<nav_bar::Id> = nav.iter().collect();
+                if let Some(position) = tabs.iter().position(|&tab| nav.is_active(tab)) {
+                    let next = (position as isize + offset).rem_euclid(tabs.len() as isize);
+                    return self.show_tab(tabs[next as usize]);
+                }
+            }
+
+            Message::SelectTab(index) => {
+                if let Some(tab) = self.focused().nav.iter().nth(index) {
+                    return self.show_tab(tab);
+                }
+            }
+
+            Message::SelectLastTab => {
+                if let Some(tab) = self.focused().nav.iter().last() {
+                    return self.show_tab(tab);
+                }
+            }
+
+            Message::FocusAddressBar => {
+                let search_id = self.focused().search_id.clone();
+                return Task::batch([
+                    widget::text_input::focus(search_id.clone()),
+                    widget::text_input::select_all(search_id),
+                ]);
+            }
+
+            Message::Find => {
+                let browser = self.focused_mut();
+                browser.find.get_or_insert_with(String::new);
+                return widget::text_input::focus(browser.find_id.clone());
+            }
+
+            Message::FindInput(text) => {
+                self.focused_mut().find = Some(text);
+                return self.find_in_page(false, true);
+            }
+
+            Message::FindNext | Message::FindPrevious if self.focused().find.is_none() => {
+                return self.update(Message::Find);
+            }
+
+            Message::FindNext => return self.find_in_page(false, false),
+
+            Message::FindPrevious => return self.find_in_page(true, false),
+
+            Message::FindClose => {
+                let browser = self.focused_mut();
+                browser.find = None;
+                browser.find_failed = false;
+            }
+
+            Message::ShortcutInput(index, text) => {
+                self.shortcut_edit = Some((index, text));
+            }
+
+            Message::ShortcutSave(index) => {
+                let edited = self
+                    .shortcut_edit
+                    .clone()
+                    .filter(|(edited, _)| *edited == index);
+                let action = shortcuts::actions().get(index).copied();
+                let (Some((_, text)), Some(action)) = (edited, action) else {
+                    return Task::none();
+                };
+                let Some(binds) = shortcuts::parse_list(&text) else {
+                    return self.toast(fl!("shortcut-invalid", chords = text.as_str()));
+                };
+                if let Some(bind) = binds.iter().find(|bind| !shortcuts::is_reserved(bind)) {
+                    let chord = shortcuts::format(bind);
+                    return self.toast(fl!("shortcut-not-reserved", chord = chord.as_str()));
                 }
+                let chords: Vec<String> = binds.iter().map(shortcuts::format).collect();
+                let name = shortcuts::name(action);
+                self.shortcut_edit = None;
+                return self.update_config(|config| {
+                    config.shortcuts.insert(name, chords.join(", "));
+                });
+            }
+
+            Message::ShortcutReset(index) => {
+                let Some(&action) = shortcuts::actions().get(index) else {
+                    return Task::none();
+                };
+                self.shortcut_edit = None;
+                return self.update_config(|config| {
+                    config.shortcuts.remove(&shortcuts::name(action));
+                });
             }
 
             _ => (),
@@ -435,19 +1337,18 @@ impl Application for AppModel {
         Task::none()
     }
 
+    /// Saves the session before the windows close.
+    fn on_app_exit(&mut self) -> Option<Self::Message> {
+        // nothing is left to show an error in
+        let _ = self.save_session();
+        None
+    }
+
     /// Called when a nav item is selected.
     fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<Self::Message> {
-        // Activate the page in the model.
-        self.nav.activate(id);
-
-        // change current web view
-        let mut tasks = Vec::new();
-        if let Some(tab) = self.nav.data::<u32>(id) {
-            tasks.push(cosmic::Task::done(Message::GotoTab(*tab)).map(cosmic::Action::from))
-        }
-        tasks.push(self.update_title());
-
-        Task::batch(tasks)
+        // the nav bar is the main window's
+        let focus = self.focus_window(None);
+        Task::batch([focus, self.show_tab(id)])
     }
 }
 
@@ -486,16 +1387,1730 @@ impl AppModel {
             .into()
     }
 
-    /// Updates the header and window titles.
-    pub fn update_title(&mut self) -> Task<Message> {
-        let mut window_title = fl!("app-title");
+    /// The developer console for the current tab.
+    pub fn console(&self) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = theme::active().cosmic().spacing;
 
-        if let Some(page) = self.nav.text(self.nav.active()) {
-            window_title.push_str(" — ");
+        let mut log = widget::column().spacing(space_xxs);
+        for message in self
+            .webview
+            .console_messages()
+            .into_iter()
+            .filter(|message| message.level >= self.console_level)
+        {
+            let level = &self.console_levels[message.level as usize];
+            log = log.push(
+                widget::column()
+                    .push(widget::text::body(format!("{level}: {}", message.message)))
+                    .push(widget::text::caption(format!(
+                        "{}:{}",
+                        message.source, message.line
+                    ))),
+            );
+        }
+        for (input, output) in &self.console_history {
+            log = log.push(widget::text::monotext(format!("> {input}")));
+            match output {
+                Some(Ok(json)) => log = log.push(widget::text::monotext(json.clone())),
+                Some(Err(error)) => log = log.push(widget::text::monotext(error.to_string())),
+                None => (),
+            }
+        }
+
+        let toolbar = widget::row()
+            .push(widget::dropdown(
+                &self.console_levels,
+                Some(self.console_level as usize),
+                Message::ConsoleFilter,
+            ))
+            .push(widget::horizontal_space())
+            .push(widget::button::standard(fl!("console-clear")).on_press(Message::ConsoleClear))
+            .align_y(Alignment::Center);
+
+        let input = widget::text_input(fl!("console-placeholder"), &self.console_input)
+            .on_input(Message::ConsoleInput)
+            .on_submit(|_| Message::ConsoleSubmit);
+
+        widget::column()
+            .push(toolbar)
+            .push(widget::scrollable(log).height(Length::Fill))
+            .push(input)
+            .spacing(space_s)
+            .into()
+    }
+
+    /// The configured search engines, with the default one picked.
+    pub fn search_engines(&self) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = theme::active().cosmic().spacing;
+
+        let engines = self.config.search_engines();
+        let default = engines.default_engine().and_then(|default| {
+            engines
+                .engines()
+                .iter()
+                .position(|engine| engine == default)
+        });
+        let removable = engines.engines().len() > 1;
+
+        let mut list = widget::column().spacing(space_xxs);
+        for (index, engine) in engines.engines().iter().enumerate() {
+            let mut details = widget::column().push(widget::text::caption(engine.url.clone()));
+            if let Some(keyword) = &engine.keyword {
+                details = details.push(widget::text::caption(fl!(
+                    "search-engine-keyword",
+                    keyword = keyword.as_str()
+                )));
+            }
+            list = list.push(
+                widget::row()
+                    .push(widget::radio(
+                        widget::text::body(engine.name.clone()),
+                        index,
+                        default,
+                        Message::SearchEngineDefault,
+                    ))
+                    .push(details.width(Length::Fill))
+                    .push(
+                        widget::button::icon(icon::from_name("edit-delete-symbolic"))
+                            .on_press_maybe(
+                                removable.then_some(Message::SearchEngineRemove(index)),
+                            ),
+                    )
+                    .align_y(Alignment::Center)
+                    .spacing(space_s),
+            );
+        }
+
+        let import = widget::row()
+            .push(
+                widget::text_input(fl!("opensearch-placeholder"), &self.opensearch_path)
+                    .on_input(Message::OpenSearchPath)
+                    .on_submit(|_| Message::OpenSearchImport),
+            )
+            .push(
+                widget::button::standard(fl!("opensearch-import"))
+                    .on_press(Message::OpenSearchImport),
+            )
+            .align_y(Alignment::Center)
+            .spacing(space_xxs);
+
+        widget::column()
+            .push(widget::scrollable(list).height(Length::Fill))
+            .push(import)
+            .spacing(space_s)
+            .into()
+    }
+
+    /// Settings that don't have a page of their own.
+    pub fn settings(&self) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = theme::active().cosmic().spacing;
+
+        let startup = STARTUP_OPTIONS
+            .iter()
+            .position(|&startup| startup == self.config.startup);
+        let labels = [
+            fl!("startup-start-page"),
+            fl!("startup-last-session"),
+            fl!("startup-homepage"),
+        ];
+        let mut options = widget::column().spacing(space_xxs);
+        for (index, label) in labels.into_iter().enumerate() {
+            options = options.push(widget::radio(
+                widget::text::body(label),
+                index,
+                startup,
+                Message::Startup,
+            ));
+        }
+
+        widget::column()
+            .push(widget::text::heading(fl!("startup")))
+            .push(options)
+            .push(
+                widget::text_input(fl!("homepage-placeholder"), &self.config.homepage)
+                    .on_input(Message::Homepage),
+            )
+            .spacing(space_s)
+            .into()
+    }
+
+    /// The chords of every action, each editable as a comma separated list.
+    pub fn shortcuts(&self) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = theme::active().cosmic().spacing;
+
+        let mut list = widget::column().spacing(space_xxs);
+        for (index, action) in shortcuts::actions().into_iter().enumerate() {
+            let chords = match &self.shortcut_edit {
+                Some((edited, text)) if *edited == index => text.clone(),
+                _ => shortcuts::chords(&self.key_binds, action).join(", "),
+            };
+            let changed = self.config.shortcuts.contains_key(&shortcuts::name(action));
+            list = list.push(
+                widget::row()
+                    .push(widget::text::body(action_label(action)).width(Length::Fill))
+                    .push(
+                        widget::text_input("", chords)
+                            .width(Length::Fixed(180.0))
+                            .on_input(move |text| Message::ShortcutInput(index, text))
+                            .on_submit(move |_| Message::ShortcutSave(index)),
+                    )
+                    .push(
+                        widget::button::text(fl!("shortcut-reset"))
+                            .on_press_maybe(changed.then_some(Message::ShortcutReset(index))),
+                    )
+                    .align_y(Alignment::Center)
+                    .spacing(space_xxs),
+            );
+        }
+
+        widget::column()
+            .push(widget::text::caption(fl!("shortcuts-hint")))
+            .push(widget::scrollable(list).height(Length::Fill))
+            .spacing(space_s)
+            .into()
+    }
+
+    /// Back, forward and reload or stop for the page a window shows.
+    fn navigation(&self, browser: &BrowserWindow) -> Element<Message> {
+        let view = browser.view();
+        let can_go = |offset| view.is_some_and(|view| self.can_go(view, offset));
+        widget::row()
+            .push(
+                widget::button::icon(icon::from_name("go-previous-symbolic"))
+                    .on_press_maybe(can_go(-1).then_some(Message::WebView(web::Action::GoBack))),
+            )
+            .push(
+                widget::button::icon(icon::from_name("go-next-symbolic"))
+                    .on_press_maybe(can_go(1).then_some(Message::WebView(web::Action::GoForward))),
+            )
+            .push(
+                if view.is_some_and(|view| self.webview.view_is_loading(view)) {
+                    widget::button::icon(icon::from_name("process-stop-symbolic"))
+                        .on_press(Message::Stop)
+                } else {
+                    widget::button::icon(icon::from_name("view-refresh-symbolic"))
+                        .on_press(Message::WebView(web::Action::Refresh))
+                },
+            )
+            .align_y(Alignment::Center)
+            .into()
+    }
+
+    /// The address bar of a window and its bookmark star, or a search button without tabs.
+    fn address_bar<'a>(&'a self, browser: &'a BrowserWindow) -> Vec<Element<'a, Message>> {
+        let mut elements = Vec::with_capacity(2);
+
+        if let Some(term) = &browser.url {
+            if self.core.is_condensed() {
+                let first_tab = browser.nav.iter().next();
+                elements.push(
+                    widget::button::icon(widget::icon::from_name("system-search-symbolic"))
+                        .on_press_maybe(
+                            first_tab
+                                .and_then(|tab| browser.nav.data::<web::ViewId>(tab))
+                                .map(|&view| Message::GotoTab(view)),
+                        )
+                        .padding(8)
+                        .selected(true)
+                        .into(),
+                );
+            } else {
+                let input = widget::text_input::search_input("", term.to_string())
+                    .width(Length::Fill)
+                    .id(browser.search_id.clone())
+                    .on_clear(Message::UrlChanged(String::new()))
+                    .on_input(Message::UrlChanged)
+                    .on_submit(Message::UrlSubmitted);
+
+                let mut address_bar =
+                    widget::popover(input).position(widget::popover::Position::Bottom);
+                if !browser.suggestions.is_empty() {
+                    address_bar = address_bar
+                        .popup(self.suggestion_list(&browser.suggestions))
+                        .on_close(Message::SuggestionKey(SuggestionKey::Dismiss));
+                }
+                elements.push(address_bar.into());
+
+                let url = browser.view().map(|view| self.webview.get_url(view));
+                let starred = url.as_deref().and_then(|url| self.bookmarks.find_url(url));
+                elements.push(
+                    widget::button::icon(icon::from_name(if starred.is_some() {
+                        "starred-symbolic"
+                    } else {
+                        "non-starred-symbolic"
+                    }))
+                    .on_press_maybe(
+                        url.as_deref()
+                            .is_some_and(has_address)
+                            .then_some(Message::ToggleBookmark),
+                    )
+                    .into(),
+                );
+            }
+        } else {
+            elements.push(
+                widget::button::icon(widget::icon::from_name("system-search-symbolic"))
+                    .on_press(Message::NewTab)
+                    .padding(8)
+                    .into(),
+            );
+        }
+
+        elements
+    }
+
+    /// The page browser window `window` shows, under its bookmarks and find bars.
+    fn page<'a>(
+        &'a self,
+        window: Option<window::Id>,
+        browser: &'a BrowserWindow,
+    ) -> Element<'a, Message> {
+        let view = browser.view();
+        // the bar keeps its height while idle so the page doesn't shift when loads start
+        let progress: Element<Message> = match view.and_then(|view| self.load_progress.get(&view)) {
+            Some(&progress) => widget::progress_bar(0.0..=1.0, progress)
+                .height(Length::Fixed(PROGRESS_HEIGHT))
+                .into(),
+            None => widget::vertical_space()
+                .height(Length::Fixed(PROGRESS_HEIGHT))
+                .into(),
+        };
+        let focused = window == self.window;
+        let mut content = widget::column();
+        if self.config.show_bookmarks_bar && !self.bookmarks.root().children.is_empty() {
+            content = content.push(self.bookmarks_bar(focused));
+        }
+        if browser.find.is_some() {
+            content = content.push(self.find_bar(browser));
+        }
+        let page = match view {
+            _ if focused => self.webview.view(),
+            Some(view) => self.webview.view_of(view),
+            None => widget::horizontal_space().into(),
+        };
+        content
+            .push(progress)
+            .push(page.map(Message::WebView))
+            .into()
+    }
+
+    /// The tabs, address bar and page of a browser window besides the main one, which has
+    /// no header bar of its own.
+    fn window_view<'a>(
+        &'a self,
+        window: Option<window::Id>,
+        browser: &'a BrowserWindow,
+    ) -> Element<'a, Message> {
+        let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+        let mut header = widget::row().push(self.navigation(browser));
+        for element in self.address_bar(browser) {
+            header = header.push(element);
+        }
+        let header = header
+            .push(
+                widget::button::icon(icon::from_name("tab-new-symbolic")).on_press(Message::NewTab),
+            )
+            .align_y(Alignment::Center)
+            .spacing(space_xxs)
+            .padding(space_xxs);
+        let tabs = widget::tab_bar::horizontal(&browser.nav)
+            .on_activate(Message::ActivateTab)
+            .on_close(Message::CloseTab)
+            .enable_tab_drag(String::from(TAB_MIME))
+            .on_dnd_drop(|target, drag: Option<TabDrag>, _| Message::TabDropped(target, drag));
+
+        widget::column()
+            .push(header)
+            .push(tabs)
+            .push(self.page(window, browser))
+            .into()
+    }
+
+    /// Searches the page a window shows for the text typed into it.
+    fn find_bar<'a>(&'a self, browser: &'a BrowserWindow) -> Element<'a, Message> {
+        let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+        let text = browser.find.as_deref().unwrap_or_default();
+        let mut bar = widget::row()
+            .push(
+                widget::text_input(fl!("find-placeholder"), text)
+                    .id(browser.find_id.clone())
+                    .width(Length::Fixed(240.0))
+                    .on_input(Message::FindInput)
+                    .on_submit(|_| Message::FindNext),
+            )
+            .push(
+                widget::button::icon(icon::from_name("go-up-symbolic"))
+                    .on_press(Message::FindPrevious),
+            )
+            .push(
+                widget::button::icon(icon::from_name("go-down-symbolic"))
+                    .on_press(Message::FindNext),
+            );
+        if browser.find_failed {
+            bar = bar.push(widget::text::caption(fl!("find-not-found")));
+        }
+        bar.push(widget::horizontal_space())
+            .push(
+                widget::button::icon(icon::from_name("window-close-symbolic"))
+                    .on_press(Message::FindClose),
+            )
+            .align_y(Alignment::Center)
+            .spacing(space_xxs)
+            .padding([0, space_xxs])
+            .into()
+    }
+
+    /// The top bookmarks folder, with its folders dropping down their contents in the
+    /// `focused` window.
+    fn bookmarks_bar(&self, focused: bool) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxxs,
+            space_xxs,
+            ..
+        } = theme::active().cosmic().spacing;
+
+        let mut bar = widget::row().spacing(space_xxxs).padding([0, space_xxs]);
+        for node in &self.bookmarks.root().children {
+            match node {
+                Node::Bookmark(bookmark) => {
+                    bar = bar.push(
+                        widget::button::text(bookmark.title.clone())
+                            .leading_icon(self.page_icon(&bookmark.url, "text-html-symbolic"))
+                            .on_press(Message::OpenBookmark(bookmark.id)),
+                    );
+                }
+                Node::Folder(folder) => {
+                    let button = widget::button::text(folder.title.clone())
+                        .leading_icon(icon::from_name("folder-symbolic"))
+                        .on_press(Message::BookmarkMenu(Some(folder.id)));
+                    let mut menu =
+                        widget::popover(button).position(widget::popover::Position::Bottom);
+                    // the menu stays under the bar folder while browsing its subfolders
+                    let shown = self.bookmark_menu.filter(|_| focused).and_then(|id| {
+                        match self.bookmarks.get(id) {
+                            Some(Node::Folder(shown))
+                                if shown.id == folder.id || folder.find(id).is_some() =>
+                            {
+                                Some(shown)
+                            }
+                            _ => None,
+                        }
+                    });
+                    if let Some(shown) = shown {
+                        menu = menu
+                            .popup(self.bookmark_folder_menu(shown))
+                            .on_close(Message::BookmarkMenu(None));
+                    }
+                    bar = bar.push(menu);
+                }
+            }
+        }
+        widget::scrollable::horizontal(bar).into()
+    }
+
+    /// The contents of a bookmarks bar folder.
+    fn bookmark_folder_menu<'a>(&'a self, folder: &'a bookmarks::Folder) -> Element<'a, Message> {
+        let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+        let mut list = widget::column();
+        for node in &folder.children {
+            let (handle, message) = match node {
+                Node::Bookmark(bookmark) => (
+                    self.page_icon(&bookmark.url, "text-html-symbolic"),
+                    Message::OpenBookmark(bookmark.id),
+                ),
+                Node::Folder(folder) => (
+                    icon::from_name("folder-symbolic").handle(),
+                    Message::BookmarkMenu(Some(folder.id)),
+                ),
+            };
+            list = list.push(
+                widget::button::custom(
+                    widget::row()
+                        .push(icon::icon(handle).size(16))
+                        .push(widget::text::body(node.title()))
+                        .align_y(Alignment::Center)
+                        .spacing(space_xxs),
+                )
+                .class(theme::Button::MenuItem)
+                .on_press(message)
+                .width(Length::Fill),
+            );
+        }
+
+        widget::container(list)
+            .padding(space_xxs)
+            .width(Length::Fixed(280.0))
+            .class(theme::Container::Dropdown)
+            .into()
+    }
+
+    /// Every bookmark and folder, to rearrange, edit, import and export.
+    pub fn bookmark_manager(&self) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxs,
+            space_s,
+            space_m,
+            ..
+        } = theme::active().cosmic().spacing;
+
+        let selected = self.bookmark_edit.as_ref().map(|edit| edit.id);
+        let mut tree = widget::column().spacing(space_xxs);
+        for (node, depth) in self.bookmarks.nodes() {
+            let id = node.id();
+            let handle = match node {
+                Node::Bookmark(bookmark) => self.page_icon(&bookmark.url, "text-html-symbolic"),
+                Node::Folder(_) => icon::from_name("folder-symbolic").handle(),
+            };
+            let mut details = widget::column().push(widget::text::body(node.title()));
+            if let Node::Bookmark(bookmark) = node {
+                details = details.push(widget::text::caption(bookmark.url.clone()));
+            }
+            tree = tree.push(
+                widget::row()
+                    .push(
+                        widget::horizontal_space()
+                            .width(Length::Fixed(f32::from(space_m) * depth as f32)),
+                    )
+                    .push(
+                        widget::button::custom(
+                            widget::row()
+                                .push(icon::icon(handle).size(16))
+                                .push(details)
+                                .align_y(Alignment::Center)
+                                .spacing(space_xxs),
+                        )
+                        .class(theme::Button::MenuItem)
+                        .selected(selected == Some(id))
+                        .on_press(Message::BookmarkSelect(id))
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("go-up-symbolic"))
+                            .on_press(Message::BookmarkMove(id, -1)),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("go-down-symbolic"))
+                            .on_press(Message::BookmarkMove(id, 1)),
+                    )
+                    .push(
+                        widget::button::icon(icon::from_name("edit-delete-symbolic"))
+                            .on_press(Message::BookmarkRemove(id)),
+                    )
+                    .align_y(Alignment::Center),
+            );
+        }
+
+        let mut content = widget::column()
+            .push(widget::button::standard(fl!("new-folder")).on_press(Message::BookmarkNewFolder))
+            .push(widget::scrollable(tree).height(Length::Fill))
+            .spacing(space_s);
+
+        if let Some(edit) = &self.bookmark_edit {
+            let mut form = widget::column().push(
+                widget::text_input(fl!("bookmark-title"), &edit.title)
+                    .on_input(Message::BookmarkTitle)
+                    .on_submit(|_| Message::BookmarkSave),
+            );
+            if let Some(Node::Bookmark(_)) = self.bookmarks.get(edit.id) {
+                form = form.push(
+                    widget::text_input(fl!("bookmark-tags"), &edit.tags)
+                        .on_input(Message::BookmarkTags)
+                        .on_submit(|_| Message::BookmarkSave),
+                );
+            }
+            form = form.push(
+                widget::row()
+                    .push(widget::dropdown(
+                        &edit.folders,
+                        edit.folder,
+                        Message::BookmarkFolder,
+                    ))
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::suggested(fl!("bookmark-save"))
+                            .on_press(Message::BookmarkSave),
+                    )
+                    .align_y(Alignment::Center),
+            );
+            content = content.push(form.spacing(space_xxs));
+        }
+
+        let transfer = widget::row()
+            .push(
+                widget::text_input(fl!("bookmarks-path"), &self.bookmarks_path)
+                    .on_input(Message::BookmarksPath),
+            )
+            .push(
+                widget::button::standard(fl!("bookmarks-import"))
+                    .on_press(Message::BookmarksImport),
+            )
+            .push(
+                widget::button::standard(fl!("bookmarks-export"))
+                    .on_press(Message::BookmarksExport),
+            )
+            .align_y(Alignment::Center)
+            .spacing(space_xxs);
+
+        content.push(transfer).into()
+    }
+
+    /// The dropdown of suggestions under the address bar.
+    fn suggestion_list<'a>(
+        &'a self,
+        suggestions: &'a omnibox::Suggestions,
+    ) -> Element<'a, Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = theme::active().cosmic().spacing;
+
+        let mut list = widget::column().spacing(space_xxs);
+        for (index, suggestion) in suggestions.items().iter().enumerate() {
+            let url = suggestion.url.as_str();
+            let (handle, detail) = match &suggestion.kind {
+                omnibox::SuggestionKind::Visit => {
+                    (self.page_icon(url, "text-html-symbolic"), url.to_string())
+                }
+                omnibox::SuggestionKind::Search(engine) => (
+                    icon::from_name("system-search-symbolic").handle(),
+                    fl!("search-with", engine = engine.as_str()),
+                ),
+                omnibox::SuggestionKind::History => (
+                    self.page_icon(url, "document-open-recent-symbolic"),
+                    url.to_string(),
+                ),
+                omnibox::SuggestionKind::Bookmark => {
+                    (self.page_icon(url, "starred-symbolic"), url.to_string())
+                }
+                omnibox::SuggestionKind::Tab(_) => (
+                    self.page_icon(url, "view-paged-symbolic"),
+                    fl!("switch-to-tab"),
+                ),
+            };
+
+            let mut row = widget::row()
+                .push(icon::icon(handle).size(16))
+                .push(
+                    widget::column()
+                        .push(widget::text::body(suggestion.title.clone()))
+                        .push(widget::text::caption(detail))
+                        .width(Length::Fill),
+                )
+                .align_y(Alignment::Center)
+                .spacing(space_s);
+            if suggestion.kind == omnibox::SuggestionKind::History {
+                row = row.push(
+                    widget::button::icon(icon::from_name("edit-delete-symbolic"))
+                        .on_press(Message::SuggestionRemoved(index)),
+                );
+            }
+
+            list = list.push(
+                widget::button::custom(row)
+                    .class(theme::Button::MenuItem)
+                    .selected(suggestions.selected() == Some(index))
+                    .on_press(Message::SuggestionChosen(index))
+                    .width(Length::Fill),
+            );
+        }
+
+        widget::container(list)
+            .padding(space_xxs)
+            .width(Length::Fixed(560.0))
+            .class(theme::Container::Dropdown)
+            .into()
+    }
+
+    /// Rebuilds the suggestions for `text` from history, bookmarks and open tabs.
+    fn suggest(&mut self, text: &str, complete: bool) {
+        let browser = self.focused();
+        let tabs: Vec<omnibox::OpenTab> = browser
+            .tabs()
+            .into_iter()
+            .map(|(tab, view)| omnibox::OpenTab {
+                view,
+                title: browser.tab_title(tab).to_string(),
+                url: self.webview.get_url(view),
+            })
+            .collect();
+        let sources = omnibox::Sources {
+            history: &self.history,
+            bookmarks: &self.bookmarks,
+            tabs: &tabs,
+            engines: self.config.search_engines(),
+        };
+        let suggestions = omnibox::Suggestions::new(text, complete, sources, SystemTime::now());
+        self.focused_mut().suggestions = suggestions;
+    }
+
+    /// Loads the suggested page in the current tab, or switches to the tab showing it.
+    fn open_suggestion(&mut self, suggestion: omnibox::Suggestion) -> Task<Message> {
+        self.focused_mut().suggestions = omnibox::Suggestions::default();
+        match suggestion.kind {
+            omnibox::SuggestionKind::Tab(view) => match self.focused().tab_for_view(view) {
+                Some(tab) => self.show_tab(tab),
+                None => Task::none(),
+            },
+            omnibox::SuggestionKind::Search(_) => self.go_to(suggestion.url, Transition::Generated),
+            _ => self.go_to(suggestion.url, Transition::Typed),
+        }
+    }
+
+    /// Loads what `text` typed into the address bar or start page stands for.
+    fn open_input(&mut self, text: &str) -> Task<Message> {
+        match omnibox::Input::parse(text, self.config.search_engines()) {
+            Some(omnibox::Input::Url(url)) => self.go_to(url, Transition::Typed),
+            Some(omnibox::Input::Search { url, .. }) => self.go_to(url, Transition::Generated),
+            None => Task::none(),
+        }
+    }
+
+    /// Loads `url` in the current tab.
+    fn go_to(&mut self, url: Url, transition: Transition) -> Task<Message> {
+        let Some(view) = self.webview.current_view() else {
+            return Task::none();
+        };
+        self.focused_mut().url = Some(url.to_string());
+        self.pending_transitions.insert(view, transition);
+        self.webview.update(web::Action::GoToUrl(url))
+    }
+
+    /// Fills in the history or bookmarks page `view` shows with what the browser knows.
+    fn fill_page(&mut self, view: web::ViewId) -> Task<Message> {
+        let Ok(url) = Url::parse(&self.webview.get_url(view)) else {
+            return Task::none();
+        };
+        let script = match Page::parse(&url) {
+            Some(Page::History) => {
+                let query = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "q")
+                    .map(|(_, value)| value.into_owned())
+                    .unwrap_or_default();
+                let visits: Vec<_> = self.history.search(&query).collect();
+                let icons: BTreeMap<String, String> = visits
+                    .iter()
+                    .filter_map(|visit| {
+                        let origin = favicon::origin(&visit.url)?;
+                        let favicon = self.favicons.get(&visit.url)?;
+                        Some((origin, favicon.data_url()))
+                    })
+                    .collect();
+                pages::show_visits(&query, &visits, &icons)
+            }
+            Some(Page::Bookmarks) => {
+                let mut icons = BTreeMap::new();
+                let mut folders = vec![self.bookmarks.root()];
+                while let Some(folder) = folders.pop() {
+                    for node in &folder.children {
+                        match node {
+                            Node::Bookmark(bookmark) => {
+                                let origin = favicon::origin(&bookmark.url);
+                                let favicon = self.favicons.get(&bookmark.url);
+                                if let (Some(origin), Some(favicon)) = (origin, favicon) {
+                                    icons.insert(origin, favicon.data_url());
+                                }
+                            }
+                            Node::Folder(folder) => folders.push(folder),
+                        }
+                    }
+                }
+                pages::show_bookmarks(self.bookmarks.root(), &icons)
+            }
+            _ => return Task::none(),
+        };
+        match self.webview.evaluate_script(view, &script) {
+            Ok(_) => Task::none(),
+            Err(err) => self.toast(format!("failed to show {url}: {err}")),
+        }
+    }
+
+    /// Opens `page_type` in a new tab, which becomes active once the view is created.
+    fn open_tab(&mut self, page_type: web::PageType) -> Task<Message> {
+        self.open_tab_in(self.window, page_type)
+    }
+
+    /// Opens `page_type` in a new tab of browser window `window`.
+    fn open_tab_in(
+        &mut self,
+        window: Option<window::Id>,
+        page_type: web::PageType,
+    ) -> Task<Message> {
+        self.opening.push_back(window);
+        self.webview
+            .update(web::Action::CreateView(page_type))
+            .map(cosmic::Action::from)
+    }
+
+    /// Carries out a request an internal page made through `window.astrolabe`.
+    fn bridge_request(&mut self, request: bridge::Request) -> Task<Message> {
+        match request {
+            bridge::Request::OpenTab(url) => match Url::parse(&url) {
+                Ok(url) => self.open_tab(web::PageType::Url(url.to_string())),
+                Err(err) => self.toast(format!("internal page asked to open {url:?}: {err}")),
+            },
+            // the start page searches like the address bar does
+            bridge::Request::Search(text) => self.open_input(&text),
+            bridge::Request::SearchHistory(text) => {
+                let mut url = Page::History.url();
+                url.query_pairs_mut().append_pair("q", text.trim());
+                self.go_to(url, Transition::Typed)
+            }
+            bridge::Request::DeleteHistory(id) => {
+                let removed = self.history.remove_visit(id);
+                self.report_history_error(removed)
+            }
+            bridge::Request::OpenPanel(name) => {
+                let context_page = match name.as_str() {
+                    "bookmarks" => ContextPage::Bookmarks,
+                    "search-engines" => ContextPage::SearchEngines,
+                    "settings" => ContextPage::Settings,
+                    "shortcuts" => ContextPage::Shortcuts,
+                    _ => return Task::none(),
+                };
+                self.context_page = context_page;
+                self.core.window.show_context = true;
+                Task::none()
+            }
+        }
+    }
+
+    /// Keeps `engines` with `default` searching by default, and writes them to the config.
+    fn save_search_engines(
+        &mut self,
+        engines: Vec<SearchEngine>,
+        default: String,
+    ) -> Task<Message> {
+        let saved = match &self.config_handler {
+            Some(handler) => self
+                .config
+                .set_search_engines(handler, engines)
+                .and_then(|_| self.config.set_default_search_engine(handler, default))
+                .map(drop),
+            None => {
+                self.config.search_engines = engines;
+                self.config.default_search_engine = default;
+                Ok(())
+            }
+        };
+        self.bridge.set_settings(&self.config);
+        match saved {
+            Ok(_) => Task::none(),
+            Err(err) => self.toast(format!("failed to save search engines: {err}")),
+        }
+    }
+
+    /// What the browser opens with, following the startup setting.
+    fn startup(&mut self) -> Task<Message> {
+        let mut tasks = Vec::new();
+        let session = match self.config.startup {
+            Startup::LastSession => self.data_file("session.json"),
+            _ => None,
+        };
+        let session = match session.map(|path| Session::open(&path)) {
+            Some(Ok(session)) => Some(session),
+            Some(Err(err)) => {
+                tasks.push(self.toast(format!("failed to load session: {err}")));
+                None
+            }
+            None => None,
+        };
+        if let Some(mut session) = session {
+            session.windows.retain(|window| !window.tabs.is_empty());
+            if !session.windows.is_empty() {
+                tasks.push(self.restore(session));
+                return Task::batch(tasks);
+            }
+        }
+
+        tasks.push(match Url::parse(self.config.homepage.trim()) {
+            Ok(url) if self.config.startup == Startup::Homepage => {
+                self.open_tab(web::PageType::Url(url.to_string()))
+            }
+            _ => self.open_tab(new_tab_page()),
+        });
+        Task::batch(tasks)
+    }
+
+    /// Reopens the windows and tabs of `session`, only loading the active tabs right away.
+    /// The first window's tabs go to the main window.
+    fn restore(&mut self, session: Session) -> Task<Message> {
+        let mut tasks = Vec::new();
+        for (index, saved) in session.windows.into_iter().enumerate() {
+            let window = if index == 0 {
+                tasks.push(self.place_main_window(saved.geometry));
+                None
+            } else {
+                let (window, open) = self.open_window(saved.geometry, false);
+                tasks.push(open);
+                Some(window)
+            };
+            for (index, tab) in saved.tabs.into_iter().enumerate() {
+                let title = tab
+                    .current()
+                    .map(|entry| entry.title.clone())
+                    .unwrap_or_default();
+                self.restoring.push_back(RestoringTab {
+                    tab,
+                    active: index == saved.active,
+                    position: None,
+                });
+                let page = web::PageType::Html(placeholder_page(&title));
+                tasks.push(self.open_tab_in(window, page));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Loads the page restored tab `view` was showing and scrolls back to where it was.
+    fn load_restored(&mut self, view: web::ViewId) -> Task<Message> {
+        let Some(entry) = self.tab_sessions.get(&view).and_then(session::Tab::current) else {
+            return Task::none();
+        };
+        let scroll = entry.scroll;
+        match Url::parse(&entry.url) {
+            Ok(url) => {
+                self.pending_scrolls.insert(view, scroll);
+                self.go_to(url, Transition::Reload)
+            }
+            Err(_) => Task::none(),
+        }
+    }
+
+    /// Where `name` is kept, `None` when nothing is kept on disk.
+    fn data_file(&self, name: &str) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(|dir| dir.join(name))
+    }
+
+    /// Writes the open tabs to disk, leaving out private windows.
+    fn save_session(&mut self) -> Task<Message> {
+        let Some(path) = self.data_file("session.json") else {
+            return Task::none();
+        };
+        match self.session().save(&path) {
+            Ok(()) => Task::none(),
+            Err(err) => self.toast(format!("failed to save session: {err}")),
+        }
+    }
+
+    /// The browser windows with their open tabs, the main window first, except private ones.
+    fn session(&mut self) -> Session {
+        let windows: Vec<Option<window::Id>> = self
+            .windows
+            .iter()
+            .filter(|(_, browser)| !browser.private)
+            .map(|(&window, _)| window)
+            .collect();
+        let mut session = Session::default();
+        for window in windows {
+            let saved = self.window_session(window);
+            // windows of tabs without an address have nothing to restore
+            if !saved.tabs.is_empty() {
+                session.windows.push(saved);
+            }
+        }
+        session
+    }
+
+    /// The open tabs of browser window `window` with an address, noting how far their pages
+    /// are scrolled.
+    fn window_session(&mut self, window: Option<window::Id>) -> session::Window {
+        let Some(browser) = self.windows.get(&window) else {
+            return session::Window::default();
+        };
+        let mut session = session::Window {
+            geometry: browser.geometry,
+            ..Default::default()
+        };
+        let tabs: Vec<_> = browser
+            .tabs()
+            .into_iter()
+            .map(|(tab, view)| (view, browser.nav.is_active(tab), browser.is_pinned(tab)))
+            .collect();
+        for (view, active, pinned) in tabs {
+            // pages that are loaded may have been scrolled since
+            if !self.unloaded.contains(&view) {
+                let scroll = self
+                    .webview
+                    .evaluate_script(view, SCROLL_POSITION_SCRIPT)
+                    .ok()
+                    .and_then(|json| serde_json::from_str::<(f64, f64)>(&json).ok());
+                let entry = self
+                    .tab_sessions
+                    .get_mut(&view)
+                    .and_then(session::Tab::current_mut);
+                if let (Some(scroll), Some(entry)) = (scroll, entry) {
+                    entry.scroll = scroll;
+                }
+            }
+            let Some(tab_session) = self.tab_sessions.get(&view) else {
+                continue;
+            };
+            if active {
+                session.active = session.tabs.len();
+            }
+            session.tabs.push(session::Tab {
+                pinned,
+                ..tab_session.clone()
+            });
+        }
+        session
+    }
+
+    /// Adds a tab for the newly created `view` to the focused window.
+    fn add_tab(&mut self, view: web::ViewId) -> Task<Message> {
+        let restored = self.restoring.pop_front();
+        let title = match restored
+            .as_ref()
+            .and_then(|restored| restored.tab.current())
+        {
+            Some(entry) if !entry.title.is_empty() => entry.title.clone(),
+            _ => self.webview.get_title(view),
+        };
+        let icon = match restored
+            .as_ref()
+            .and_then(|restored| restored.tab.current())
+        {
+            Some(entry) => icon::icon(self.page_icon(&entry.url, "text-html-symbolic")),
+            None => self.tab_icon(view),
+        };
+        let title = if title.is_empty() {
+            String::from("New Tab")
+        } else {
+            title
+        };
+        let browser = self.focused_mut();
+        let tab = browser.insert_tab(view, title, icon);
+        let Some(restored) = restored else {
+            return self.show_tab(tab);
+        };
+        if restored.tab.pinned {
+            browser.set_pinned(tab, true);
+        }
+        // pinned or not, the tab must stay on its side of the pinned ones
+        let last = browser.nav.iter().count() - 1;
+        browser.move_tab(tab, restored.position.unwrap_or(last));
+        self.tab_sessions.insert(view, restored.tab);
+        self.session_driven.insert(view);
+        self.unloaded.insert(view);
+        if restored.active {
+            return self.show_tab(tab);
+        }
+        Task::none()
+    }
+
+    /// Follows `event` of the page `view` shows, in whichever window its tab is.
+    fn view_event(&mut self, view: web::ViewId, event: web::ViewEvent) -> Task<Message> {
+        let Some((window, tab)) = self.find_tab(view) else {
+            return Task::none();
+        };
+        let private = self.is_private(window);
+        let mut tasks = Vec::new();
+        match event {
+            web::ViewEvent::TitleChanged(title) => {
+                if !private {
+                    let url = self.webview.get_url(view);
+                    let saved = self.history.set_title(&url, &title);
+                    tasks.push(self.report_history_error(saved));
+                }
+                if let Some(entry) = self
+                    .tab_sessions
+                    .get_mut(&view)
+                    .and_then(session::Tab::current_mut)
+                {
+                    entry.title = title.clone();
+                }
+                if let Some(browser) = self.windows.get_mut(&window) {
+                    browser.set_tab_title(tab, title);
+                    if browser.nav.is_active(tab) {
+                        tasks.push(self.update_title(window));
+                    }
+                }
+            }
+            web::ViewEvent::UrlChanged(url) => {
+                self.advance_load(view, 0.3);
+                let transition = self
+                    .pending_transitions
+                    .remove(&view)
+                    .unwrap_or(Transition::Link);
+                if !private {
+                    let recorded = self.history.record(&url, SystemTime::now(), transition);
+                    tasks.push(self.report_history_error(recorded));
+                }
+                let address = self.webview.get_url(view);
+                if has_address(&address) {
+                    self.tab_sessions
+                        .entry(view)
+                        .or_default()
+                        .navigate(&address, transition);
+                }
+                if let Some(browser) = self.windows.get_mut(&window) {
+                    if browser.view() == Some(view) {
+                        browser.url = Some(address);
+                    }
+                }
+            }
+            web::ViewEvent::BeginLoading { .. } => {
+                self.load_progress.insert(view, 0.1);
+                if let Some(browser) = self.windows.get_mut(&window) {
+                    let icon = icon::from_name("process-working-symbolic").icon();
+                    browser.nav.icon_set(tab, icon);
+                }
+            }
+            web::ViewEvent::DomReady => {
+                self.advance_load(view, 0.7);
+                tasks.push(self.fill_page(view));
+            }
+            web::ViewEvent::FinishLoading | web::ViewEvent::FailLoading { .. } => {
+                self.load_progress.remove(&view);
+                // loads that never committed a url don't carry over to the next one
+                self.pending_transitions.remove(&view);
+                let icon = self.tab_icon(view);
+                if let Some(browser) = self.windows.get_mut(&window) {
+                    browser.nav.icon_set(tab, icon);
+                }
+                if let Some((x, y)) = self.pending_scrolls.remove(&view) {
+                    let script = format!("window.scrollTo({x}, {y})");
+                    if let Err(err) = self.webview.evaluate_script(view, &script) {
+                        tasks.push(self.toast(format!("failed to restore scroll position: {err}")));
+                    }
+                }
+                if matches!(event, web::ViewEvent::FinishLoading) && !private {
+                    tasks.push(self.fetch_favicon(view));
+                }
+            }
+            web::ViewEvent::BridgeCall { name, args } => {
+                // pages only call in while used, in the window they are in
+                if let Some(request) = bridge::Request::parse(&name, &args) {
+                    tasks.push(self.focus_window(window));
+                    tasks.push(self.bridge_request(request));
+                }
+            }
+            _ => (),
+        }
+        Task::batch(tasks)
+    }
+
+    /// Whether the tab showing `view` can go `offset` pages back (negative) or forward.
+    fn can_go(&self, view: web::ViewId, offset: isize) -> bool {
+        if self.session_driven.contains(&view) {
+            self.tab_sessions
+                .get(&view)
+                .is_some_and(|tab| tab.entry_at(offset).is_some())
+        } else if offset < 0 {
+            self.webview.view_can_go_back(view)
+        } else {
+            self.webview.view_can_go_forward(view)
+        }
+    }
+
+    /// Selects the next match of the find bar text in the current page, or the previous one
+    /// going `backwards`. A `fresh` search starts over from the current match, so it grows
+    /// with the text typed.
+    fn find_in_page(&mut self, backwards: bool, fresh: bool) -> Task<Message> {
+        let text = self.focused().find.clone();
+        let (Some(text), Some(view)) = (text, self.webview.current_view()) else {
+            return Task::none();
+        };
+        if text.is_empty() {
+            self.focused_mut().find_failed = false;
+            return Task::none();
+        }
+        let text = serde_json::to_string(&text).expect("strings serialize");
+        let mut script = format!("window.find({text}, false, {backwards}, true)");
+        if fresh {
+            script.insert_str(0, FIND_FROM_SELECTION_START);
+        }
+        match self.webview.evaluate_script(view, &script) {
+            Ok(found) => {
+                self.focused_mut().find_failed = found != "true";
+                Task::none()
+            }
+            Err(err) => self.toast(format!("failed to search the page: {err}")),
+        }
+    }
+
+    /// Carries out `action` from the menu of a tab.
+    fn tab_action(&mut self, action: TabAction) -> Task<Message> {
+        match action {
+            TabAction::Close(tab) => self.update(Message::CloseTab(tab)),
+            TabAction::CloseOthers(tab) => {
+                let browser = self.focused();
+                let others: Vec<nav_bar::Id> = browser
+                    .nav
+                    .iter()
+                    .filter(|&other| other != tab && !browser.is_pinned(other))
+                    .collect();
+                let mut tasks = vec![self.show_tab(tab)];
+                for other in others {
+                    tasks.push(self.update(Message::CloseTab(other)));
+                }
+                Task::batch(tasks)
+            }
+            TabAction::CloseBelow(tab) => {
+                let browser = self.focused();
+                let below: Vec<nav_bar::Id> = browser
+                    .nav
+                    .iter()
+                    .skip_while(|&other| other != tab)
+                    .skip(1)
+                    .filter(|&other| !browser.is_pinned(other))
+                    .collect();
+                let tasks: Vec<_> = below
+                    .into_iter()
+                    .map(|other| self.update(Message::CloseTab(other)))
+                    .collect();
+                Task::batch(tasks)
+            }
+            TabAction::Duplicate(tab) => {
+                let browser = self.focused();
+                let Some(&view) = browser.nav.data::<web::ViewId>(tab) else {
+                    return Task::none();
+                };
+                let position = browser
+                    .nav
+                    .position(tab)
+                    .map(|position| usize::from(position) + 1);
+                // tabs without an address show the start page
+                let Some(duplicate) = self.tab_sessions.get(&view) else {
+                    return self.update(Message::NewTab);
+                };
+                let duplicate = session::Tab {
+                    pinned: browser.is_pinned(tab),
+                    ..duplicate.clone()
+                };
+                let title = duplicate
+                    .current()
+                    .map(|entry| entry.title.clone())
+                    .unwrap_or_default();
+                self.restoring.push_back(RestoringTab {
+                    tab: duplicate,
+                    active: true,
+                    position,
+                });
+                self.open_tab(web::PageType::Html(placeholder_page(&title)))
+            }
+            TabAction::MoveToNewWindow(tab) => {
+                let Some((moving, detach)) = self.detach_tab(self.window, tab) else {
+                    return Task::none();
+                };
+                let private = self.focused().private;
+                let (window, open) = self.open_window(None, private);
+                let focus = self.focus_window(Some(window));
+                Task::batch([detach, open, focus, self.attach_tab(moving, 0)])
+            }
+            TabAction::Pin(tab) => {
+                self.focused_mut().set_pinned(tab, true);
+                Task::none()
+            }
+            TabAction::Unpin(tab) => {
+                self.focused_mut().set_pinned(tab, false);
+                Task::none()
+            }
+            TabAction::Reload(tab) => {
+                let Some(&view) = self.focused().nav.data::<web::ViewId>(tab) else {
+                    return Task::none();
+                };
+                // unloaded tabs load their page once shown anyway
+                if self.unloaded.contains(&view) {
+                    return Task::none();
+                }
+                self.pending_transitions.insert(view, Transition::Reload);
+                self.webview.update(web::Action::RefreshView(view))
+            }
+        }
+    }
+
+    /// The icon of the site `view` shows.
+    fn tab_icon(&self, view: web::ViewId) -> icon::Icon {
+        icon::icon(self.page_icon(&self.webview.get_url(view), "text-html-symbolic"))
+    }
+
+    /// The icon of the site `url` is on, or the icon named `fallback` when it has none yet.
+    fn page_icon(&self, url: &str, fallback: &str) -> icon::Handle {
+        match self.favicons.get(url) {
+            Some(favicon) => favicon.handle().clone(),
+            None => icon::from_name(fallback).handle(),
+        }
+    }
+
+    /// Looks for the icon of the site `view` shows, unless it is known or looked for.
+    fn fetch_favicon(&mut self, view: web::ViewId) -> Task<Message> {
+        let url = self.webview.get_url(view);
+        let (Some(origin), Ok(page)) = (favicon::origin(&url), Url::parse(&url)) else {
+            return Task::none();
+        };
+        if self.favicons.contains(&origin) || !self.favicon_requests.insert(origin.clone()) {
+            return Task::none();
+        }
+        // without links the site root is still worth a try
+        let links = self
+            .webview
+            .evaluate_script(view, favicon::DISCOVERY_SCRIPT)
+            .unwrap_or_default();
+        let candidates = favicon::candidates(&page, &links);
+        Task::perform(
+            async move { favicon::download(&candidates).await },
+            move |icon| cosmic::Action::App(Message::FaviconFetched(origin.clone(), icon)),
+        )
+    }
+
+    /// Applies `change` to the config and writes it.
+    fn update_config(&mut self, change: impl FnOnce(&mut Config)) -> Task<Message> {
+        let mut config = self.config.clone();
+        change(&mut config);
+        let written = match &self.config_handler {
+            Some(handler) => config.write_entry(handler),
+            None => Ok(()),
+        };
+        self.key_binds = shortcuts::key_binds(&config.shortcuts);
+        self.bridge.set_settings(&config);
+        self.config = config;
+        match written {
+            Ok(()) => Task::none(),
+            Err(err) => self.toast(format!("failed to save settings: {err}")),
+        }
+    }
+
+    /// Picks `id` in the bookmark manager for editing.
+    fn select_bookmark(&mut self, id: u64) {
+        let Some(node) = self.bookmarks.get(id) else {
+            self.bookmark_edit = None;
+            return;
+        };
+        let tags = match node {
+            Node::Bookmark(bookmark) => bookmark.tags.join(", "),
+            Node::Folder(_) => String::new(),
+        };
+        // a folder can't be moved into itself
+        let moved = match node {
+            Node::Folder(folder) => Some(folder),
+            Node::Bookmark(_) => None,
+        };
+        let mut folders = Vec::new();
+        let mut folder_ids = Vec::new();
+        let mut folder = None;
+        for (candidate, depth) in self.bookmarks.folders() {
+            if moved
+                .is_some_and(|moved| moved.id == candidate.id || moved.find(candidate.id).is_some())
+            {
+                continue;
+            }
+            if candidate.children.iter().any(|child| child.id() == id) {
+                folder = Some(folders.len());
+            }
+            let title = if candidate.id == bookmarks::ROOT {
+                fl!("bookmarks-bar")
+            } else {
+                candidate.title.clone()
+            };
+            folders.push(format!("{}{title}", "    ".repeat(depth)));
+            folder_ids.push(candidate.id);
+        }
+        self.bookmark_edit = Some(BookmarkEdit {
+            id,
+            title: node.title().to_string(),
+            tags,
+            folders,
+            folder_ids,
+            folder,
+        });
+    }
+
+    /// Reloads the bookmark manager selection after the bookmarks changed under it,
+    /// keeping what was typed.
+    fn refresh_bookmark_edit(&mut self) {
+        let Some(edit) = self.bookmark_edit.take() else {
+            return;
+        };
+        self.select_bookmark(edit.id);
+        if let Some(refreshed) = &mut self.bookmark_edit {
+            refreshed.title = edit.title;
+            refreshed.tags = edit.tags;
+        }
+    }
+
+    /// Follows a change to the bookmarks, reporting it if it could not be saved.
+    fn bookmarks_changed<T>(&mut self, result: std::io::Result<T>) -> Task<Message> {
+        self.refresh_bookmark_edit();
+        self.bridge.set_bookmarks(&self.bookmarks);
+        match result {
+            Ok(_) => Task::none(),
+            Err(err) => self.toast(format!("failed to save bookmarks: {err}")),
+        }
+    }
+
+    /// Shows why the history could not be saved, if it couldn't.
+    fn report_history_error<T>(&mut self, result: std::io::Result<T>) -> Task<Message> {
+        match result {
+            Ok(_) => Task::none(),
+            Err(err) => self.toast(format!("failed to save history: {err}")),
+        }
+    }
+
+    /// Shows `message` in a notification.
+    fn toast(&mut self, message: String) -> Task<Message> {
+        self.toasts
+            .push(widget::toaster::Toast::new(message))
+            .map(cosmic::Action::App)
+    }
+
+    /// Moves the progress of a load `view` has in flight up to `progress`.
+    fn advance_load(&mut self, view: web::ViewId, progress: f32) {
+        if let Some(current) = self.load_progress.get_mut(&view) {
+            *current = current.max(progress);
+        }
+    }
+
+    /// The browser window last focused, whose active tab the web view shows.
+    fn focused(&self) -> &BrowserWindow {
+        &self.windows[&self.window]
+    }
+
+    fn focused_mut(&mut self) -> &mut BrowserWindow {
+        self.windows
+            .get_mut(&self.window)
+            .expect("the focused window is open")
+    }
+
+    /// The browser window with a tab showing `view`, and that tab.
+    fn find_tab(&self, view: web::ViewId) -> Option<(Option<window::Id>, nav_bar::Id)> {
+        self.windows
+            .iter()
+            .find_map(|(&window, browser)| Some((window, browser.tab_for_view(view)?)))
+    }
+
+    /// Has the web view show `view` of the focused window, at the size of its page area.
+    fn change_view(&self, view: web::ViewId) -> web::Action {
+        match self.focused().view_size {
+            Some(size) => web::Action::ShowView(view, size),
+            None => web::Action::ChangeView(view),
+        }
+    }
+
+    /// Activates `tab` of the focused window and shows its view.
+    fn show_tab(&mut self, tab: nav_bar::Id) -> Task<Message> {
+        let browser = self.focused_mut();
+        browser.nav.activate(tab);
+        let view = browser.nav.data::<web::ViewId>(tab).copied();
+        let mut tasks = Vec::new();
+        if let Some(view) = view {
+            let change = self.change_view(view);
+            tasks.push(self.webview.update(change));
+            let url = self.webview.get_url(view);
+            self.focused_mut().url = Some(url);
+            if self.unloaded.remove(&view) {
+                tasks.push(self.load_restored(view));
+            }
+        }
+        tasks.push(self.update_title(self.window));
+        Task::batch(tasks)
+    }
+
+    /// Activates `tab` of browser window `window`, which shows it once focused.
+    fn activate_tab(&mut self, window: Option<window::Id>, tab: nav_bar::Id) -> Task<Message> {
+        if window == self.window {
+            return self.show_tab(tab);
+        }
+        let Some(browser) = self.windows.get_mut(&window) else {
+            return Task::none();
+        };
+        browser.nav.activate(tab);
+        if let Some(view) = browser.view() {
+            let url = self.webview.get_url(view);
+            if let Some(browser) = self.windows.get_mut(&window) {
+                browser.url = Some(url);
+            }
+        }
+        self.update_title(window)
+    }
+
+    /// The browser window `id` is, `None` standing for the main one, unless it is another.
+    fn browser_window(&self, id: window::Id) -> Option<Option<window::Id>> {
+        if self.core.main_window_id() == Some(id) {
+            Some(None)
+        } else if self.windows.contains_key(&Some(id)) {
+            Some(Some(id))
+        } else {
+            None
+        }
+    }
+
+    /// Whether browser window `window` keeps its tabs out of the history and the session.
+    fn is_private(&self, window: Option<window::Id>) -> bool {
+        self.windows
+            .get(&window)
+            .is_some_and(|browser| browser.private)
+    }
+
+    /// The browser window with a tab showing `view`.
+    fn window_of_view(&self, view: web::ViewId) -> Option<Option<window::Id>> {
+        self.find_tab(view).map(|(window, _)| window)
+    }
+
+    /// Has browser window `window` focused, showing the view of its active tab.
+    fn focus_window(&mut self, window: Option<window::Id>) -> Task<Message> {
+        if window == self.window || !self.windows.contains_key(&window) {
+            return Task::none();
+        }
+        self.window = window;
+        let Some(view) = self.focused().view() else {
+            return Task::none();
+        };
+        let change = self.change_view(view);
+        let mut tasks = vec![self.webview.update(change)];
+        // tabs activated while their window had no focus load their page now
+        if self.unloaded.remove(&view) {
+            tasks.push(self.load_restored(view));
+        }
+        Task::batch(tasks)
+    }
+
+    /// Opens a browser window with a new tab, a private one when `private`.
+    fn new_window(&mut self, private: bool) -> Task<Message> {
+        let (window, open) = self.open_window(None, private);
+        Task::batch([open, self.open_tab_in(Some(window), new_tab_page())])
+    }
+
+    /// Opens a browser window without tabs where `geometry` says, or as large as the focused
+    /// one, a private one when `private`.
+    fn open_window(
+        &mut self,
+        geometry: Option<session::Geometry>,
+        private: bool,
+    ) -> (window::Id, Task<Message>) {
+        let geometry = geometry.or_else(|| {
+            let focused = self.focused().geometry?;
+            Some(session::Geometry {
+                position: None,
+                size: focused.size,
+            })
+        });
+        let mut settings = window::Settings {
+            size: Size::new(1024.0, 768.0),
+            min_size: Some(Size::new(360.0, 180.0)),
+            ..Default::default()
+        };
+        if let Some(geometry) = geometry {
+            settings.size = Size::new(geometry.size.0, geometry.size.1);
+            if let Some((x, y)) = geometry.position {
+                settings.position = window::Position::Specific(Point::new(x, y));
+            }
+        }
+        let (id, open) = window::open(settings);
+        let browser = BrowserWindow {
+            private,
+            ..BrowserWindow::new()
+        };
+        self.windows.insert(Some(id), browser);
+        let title = self.update_title(Some(id));
+        (id, Task::batch([open.discard(), title]))
+    }
+
+    /// Moves and sizes the main window the way `geometry` says.
+    fn place_main_window(&self, geometry: Option<session::Geometry>) -> Task<Message> {
+        let (Some(id), Some(geometry)) = (self.core.main_window_id(), geometry) else {
+            return Task::none();
+        };
+        let mut tasks = vec![window::resize(
+            id,
+            Size::new(geometry.size.0, geometry.size.1),
+        )];
+        if let Some((x, y)) = geometry.position {
+            tasks.push(window::move_to(id, Point::new(x, y)));
+        }
+        Task::batch(tasks)
+    }
+
+    /// Physical pixels per logical one of browser window `window`, 1 until it says otherwise.
+    fn scale_factor(&self, window: Option<window::Id>) -> f64 {
+        self.windows
+            .get(&window)
+            .map_or(1.0, |browser| browser.scale)
+    }
+
+    /// Scales every tab of browser window `window`, which moved to a screen of `scale`.
+    fn rescale_window(&mut self, window: Option<window::Id>, scale: f32) -> Task<Message> {
+        let scale = f64::from(scale);
+        let Some(browser) = self.windows.get_mut(&window) else {
+            return Task::none();
+        };
+        if browser.scale == scale {
+            return Task::none();
+        }
+        browser.scale = scale;
+        let tabs = browser.tabs();
+        Task::batch(
+            tabs.into_iter()
+                .map(|(_, view)| self.webview.update(web::Action::RescaleView(view, scale))),
+        )
+    }
+
+    /// Forgets browser window `window` once closed, keeping its tabs to reopen.
+    fn close_window(&mut self, window: Option<window::Id>) -> Task<Message> {
+        let Some(browser) = self.windows.get(&window) else {
+            return Task::none();
+        };
+        let tabs = browser.tabs();
+        let mut tasks: Vec<_> = tabs
+            .into_iter()
+            .map(|(tab, view)| self.forget_tab(window, tab, view))
+            .collect();
+        self.windows.remove(&window);
+        // the main window is always there to take over
+        if self.window == window {
+            self.window = None;
+            if let Some(view) = self.focused().view() {
+                let change = self.change_view(view);
+                tasks.push(self.webview.update(change));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Closes the focused window with its last `tab`, showing `view`. The browser quits once
+    /// the main window is the last one, until then another window's tabs move into it.
+    fn close_last_tab(&mut self, tab: nav_bar::Id, view: web::ViewId) -> Task<Message> {
+        if let Some(window) = self.window {
+            return window::close(window);
+        }
+        let Some(&other) = self.windows.keys().find(|window| window.is_some()) else {
+            let saved = self.save_session();
+            return Task::batch([saved, cosmic::iced::exit()]);
+        };
+        let mut tasks = vec![self.forget_tab(None, tab, view)];
+        let Some(promoted) = self.windows.remove(&other) else {
+            return Task::batch(tasks);
+        };
+        // the main window takes the place of the other one, which closes without its tabs
+        tasks.push(self.place_main_window(promoted.geometry));
+        self.windows.insert(None, promoted);
+        if let Some(id) = other {
+            tasks.push(window::close(id));
+        }
+        if let Some(view) = self.focused().view() {
+            let change = self.change_view(view);
+            tasks.push(self.webview.update(change));
+            if self.unloaded.remove(&view) {
+                tasks.push(self.load_restored(view));
+            }
+        }
+        tasks.push(self.update_title(None));
+        Task::batch(tasks)
+    }
+
+    /// Removes `tab` of browser window `window` and closes `view` it shows, keeping the tab
+    /// to reopen.
+    fn forget_tab(
+        &mut self,
+        window: Option<window::Id>,
+        tab: nav_bar::Id,
+        view: web::ViewId,
+    ) -> Task<Message> {
+        let Some(browser) = self.windows.get_mut(&window) else {
+            return Task::none();
+        };
+        let position = browser.nav.position(tab).map_or(0, usize::from);
+        let pinned = browser.is_pinned(tab);
+        let private = browser.private;
+        browser.nav.remove(tab);
+
+        self.load_progress.remove(&view);
+        self.pending_transitions.remove(&view);
+        // closed private tabs are gone for good
+        if let Some(mut tab) = self.tab_sessions.remove(&view).filter(|_| !private) {
+            tab.pinned = pinned;
+            self.closed_tabs.push(session::ClosedTab { tab, position });
+        }
+        self.session_driven.remove(&view);
+        self.unloaded.remove(&view);
+        self.pending_scrolls.remove(&view);
+        let mut tasks = vec![self.webview.update(web::Action::CloseView(view))];
+
+        // the inspector goes away with the tab it inspects
+        if let Some(inspector) = self.inspector_window {
+            if !self.webview.has_inspector() {
+                self.inspector_window = None;
+                tasks.push(window::close(inspector));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Takes `tab` out of browser window `window` for another one, keeping its view open. The
+    /// tab next to it takes over if it was active, and windows besides the main one close
+    /// with their last tab.
+    fn detach_tab(
+        &mut self,
+        window: Option<window::Id>,
+        tab: nav_bar::Id,
+    ) -> Option<(MovingTab, Task<Message>)> {
+        let browser = self.windows.get(&window)?;
+        let &view = browser.nav.data::<web::ViewId>(tab)?;
+        let moving = MovingTab {
+            view,
+            title: browser.tab_title(tab).to_string(),
+            pinned: browser.is_pinned(tab),
+        };
+        let task = match (browser.successor(tab), window) {
+            (Some(next), _) if browser.nav.is_active(tab) => self.activate_tab(window, next),
+            (Some(_), _) => Task::none(),
+            (None, Some(id)) => window::close(id),
+            // the main window keeps its last tab
+            (None, None) => return None,
+        };
+        self.windows.get_mut(&window)?.nav.remove(tab);
+        Some((moving, task))
+    }
+
+    /// Adds a tab another window gave up to the focused one at `position`, and shows it.
+    fn attach_tab(&mut self, moving: MovingTab, position: usize) -> Task<Message> {
+        let icon = if self.load_progress.contains_key(&moving.view) {
+            icon::from_name("process-working-symbolic").icon()
+        } else {
+            self.tab_icon(moving.view)
+        };
+        let browser = self.focused_mut();
+        let tab = browser.insert_tab(moving.view, moving.title, icon);
+        if moving.pinned {
+            browser.set_pinned(tab, true);
+        }
+        browser.move_tab(tab, position);
+        let scale = browser.scale;
+        let rescale = self
+            .webview
+            .update(web::Action::RescaleView(moving.view, scale));
+        Task::batch([rescale, self.show_tab(tab)])
+    }
+
+    /// Updates the title of browser window `window` to the page its active tab shows.
+    pub fn update_title(&mut self, window: Option<window::Id>) -> Task<Message> {
+        let mut window_title = if self.is_private(window) {
+            fl!("private-title")
+        } else {
+            fl!("app-title")
+        };
+
+        let page = self
+            .windows
+            .get(&window)
+            .map_or("", BrowserWindow::active_title);
+        if !page.is_empty() {
+            window_title.push_str(" — ");
             window_title.push_str(page);
         }
 
-        if let Some(id) = self.core.main_window_id() {
+        if let Some(id) = window.or(self.core.main_window_id()) {
             self.set_window_title(window_title, id)
         } else {
             Task::none()
@@ -503,17 +3118,256 @@ impl AppModel {
     }
 }
 
+/// Flags the application is started with. The defaults keep nothing on disk.
+#[derive(Clone, Debug, Default)]
+pub struct Flags {
+    /// Where history, bookmarks, favicons and the session are kept, nowhere when `None`.
+    pub data_dir: Option<PathBuf>,
+    /// Read and write the settings with cosmic-config, otherwise the defaults are used.
+    pub config: bool,
+}
+
+/// A tab on its way to another window, with its view kept open.
+struct MovingTab {
+    view: web::ViewId,
+    title: String,
+    pinned: bool,
+}
+
+/// A tab of the last session or a closed one, waiting for its view to be created.
+struct RestoringTab {
+    tab: session::Tab,
+    /// Show it once created, loading its page right away.
+    active: bool,
+    /// Where to put it in the nav bar, the end if unset.
+    position: Option<usize>,
+}
+
+/// The bookmark or folder picked in the bookmark manager, with its unsaved changes.
+struct BookmarkEdit {
+    id: u64,
+    title: String,
+    /// Tags separated by commas, empty for folders.
+    tags: String,
+    /// Folders it can be moved to, indented by depth.
+    folders: Vec<String>,
+    folder_ids: Vec<u64>,
+    /// Position of its folder in `folders`.
+    folder: Option<usize>,
+}
+
+/// Whether `url` is an address to bookmark or come back to, generated pages without one
+/// aren't.
+fn has_address(url: &str) -> bool {
+    !url.is_empty() && url != "about:blank"
+}
+
+/// What new tabs open.
+fn new_tab_page() -> web::PageType {
+    web::PageType::Url(Page::NewTab.url().into())
+}
+
+/// Stands in for a restored background tab until it is first shown.
+fn placeholder_page(title: &str) -> String {
+    let title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!("<!DOCTYPE html><html><head><title>{title}</title></head><body></body></html>")
+}
+
+/// Has the messages of `element`, drawn in browser window `window`, act on that window.
+fn in_window(window: Option<window::Id>, element: Element<Message>) -> Element<Message> {
+    element.map(move |message| Message::InWindow(window, Box::new(message)))
+}
+
+/// Height of the page load progress bar above the page.
+const PROGRESS_HEIGHT: f32 = 3.0;
+
+/// Keys that drive the suggestions under the address bar.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SuggestionKey {
+    Down,
+    Up,
+    /// Accepts the inline completion.
+    Complete,
+    Dismiss,
+    /// Forgets the selected history entry.
+    Delete,
+}
+
+fn suggestion_key(event: Event, _status: event::Status, _window: window::Id) -> Option<Message> {
+    let Event::Keyboard(keyboard::Event::KeyPressed {
+        key: Key::Named(key),
+        modifiers,
+        ..
+    }) = event
+    else {
+        return None;
+    };
+    let key = match key {
+        Named::ArrowDown => SuggestionKey::Down,
+        Named::ArrowUp => SuggestionKey::Up,
+        Named::Tab => SuggestionKey::Complete,
+        Named::Escape => SuggestionKey::Dismiss,
+        Named::Delete if modifiers.shift() => SuggestionKey::Delete,
+        _ => return None,
+    };
+    Some(Message::SuggestionKey(key))
+}
+
+/// Key presses no focused widget took, which may be shortcuts.
+fn shortcut_key(event: Event, status: event::Status, window: window::Id) -> Option<Message> {
+    match (event, status) {
+        (
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }),
+            event::Status::Ignored,
+        ) => Some(Message::Key(window, modifiers, key)),
+        _ => None,
+    }
+}
+
 /// The context page to display in the context drawer.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum ContextPage {
     #[default]
     About,
+    Console,
+    SearchEngines,
+    Bookmarks,
+    Settings,
+    Shortcuts,
 }
 
+/// Actions of the tab menu on the tab they are for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TabAction {
+    Close(nav_bar::Id),
+    CloseBelow(nav_bar::Id),
+    CloseOthers(nav_bar::Id),
+    Duplicate(nav_bar::Id),
+    MoveToNewWindow(nav_bar::Id),
+    Pin(nav_bar::Id),
+    Reload(nav_bar::Id),
+    Unpin(nav_bar::Id),
+}
+
+impl menu::action::MenuAction for TabAction {
+    type Message = cosmic::Action<Message>;
+
+    fn message(&self) -> Self::Message {
+        // the tab menu is the main window's nav bar's
+        let action = Message::TabAction(*self);
+        cosmic::Action::App(Message::InWindow(None, Box::new(action)))
+    }
+}
+
+/// Mime type of a tab dragged between tab bars.
+const TAB_MIME: &str = "application/x-astrolabe-tab";
+
+/// A tab dragged between tab bars of one window or two, by the view it shows.
+#[derive(Clone, Debug)]
+pub struct TabDrag(web::ViewId);
+
+impl AllowedMimeTypes for TabDrag {
+    fn allowed() -> Cow<'static, [String]> {
+        Cow::Owned(vec![String::from(TAB_MIME)])
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for TabDrag {
+    type Error = ();
+
+    fn try_from((data, _mime): (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        let view = std::str::from_utf8(&data).map_err(drop)?;
+        view.parse().map(TabDrag).map_err(drop)
+    }
+}
+
+/// Startup choices in the order the settings offer them.
+const STARTUP_OPTIONS: [Startup; 3] = [Startup::StartPage, Startup::LastSession, Startup::Homepage];
+
+/// Evaluates to how far a page is scrolled, as `[x, y]`.
+const SCROLL_POSITION_SCRIPT: &str = "[window.scrollX, window.scrollY]";
+
+/// Moves the selection back to where it starts, so `window.find` after it matches the
+/// current match again when it still fits.
+const FIND_FROM_SELECTION_START: &str =
+    "if (window.getSelection().rangeCount) window.getSelection().collapseToStart(); ";
+
+/// How often the session is saved while browsing, besides on exit.
+const SESSION_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Console levels in the order the filter offers them.
+const CONSOLE_LEVELS: [web::ConsoleLevel; 5] = [
+    web::ConsoleLevel::Debug,
+    web::ConsoleLevel::Log,
+    web::ConsoleLevel::Info,
+    web::ConsoleLevel::Warning,
+    web::ConsoleLevel::Error,
+];
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    Back,
+    BookmarkPage,
+    Bookmarks,
+    BookmarksBar,
+    CloseTab,
+    Console,
+    Find,
+    FindNext,
+    FindPrevious,
+    FocusAddressBar,
+    Forward,
+    History,
+    Inspect,
+    LastTab,
+    NewPrivateWindow,
     NewTab,
+    NewWindow,
+    NextTab,
+    PreviousTab,
+    Reload,
+    ReopenClosedTab(usize),
+    SearchEngines,
+    /// Activates the tab at that position, counted from 0.
+    SelectTab(usize),
+    Settings,
+    Shortcuts,
+}
+
+/// What the shortcuts editor calls `action`.
+fn action_label(action: MenuAction) -> String {
+    match action {
+        MenuAction::About => fl!("about"),
+        MenuAction::Back => fl!("back"),
+        MenuAction::BookmarkPage => fl!("bookmark-page"),
+        MenuAction::Bookmarks => fl!("manage-bookmarks"),
+        MenuAction::BookmarksBar => fl!("bookmarks-bar"),
+        MenuAction::CloseTab => fl!("close-tab"),
+        MenuAction::Console => fl!("console"),
+        MenuAction::Find => fl!("find"),
+        MenuAction::FindNext => fl!("find-next"),
+        MenuAction::FindPrevious => fl!("find-previous"),
+        MenuAction::FocusAddressBar => fl!("focus-address-bar"),
+        MenuAction::Forward => fl!("forward"),
+        MenuAction::History => fl!("history"),
+        MenuAction::Inspect => fl!("inspect"),
+        MenuAction::LastTab => fl!("last-tab"),
+        MenuAction::NewPrivateWindow => fl!("new-private-window"),
+        MenuAction::NewTab => fl!("new-tab"),
+        MenuAction::NewWindow => fl!("new-window"),
+        MenuAction::NextTab => fl!("next-tab"),
+        MenuAction::PreviousTab => fl!("previous-tab"),
+        MenuAction::Reload => fl!("reload"),
+        MenuAction::ReopenClosedTab(_) => fl!("reopen-closed-tab"),
+        MenuAction::SearchEngines => fl!("search-engines"),
+        MenuAction::SelectTab(index) => fl!("select-tab", number = index + 1),
+        MenuAction::Settings => fl!("settings"),
+        MenuAction::Shortcuts => fl!("shortcuts"),
+    }
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -522,7 +3376,1186 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::Back => Message::WebView(web::Action::GoBack),
+            MenuAction::BookmarkPage => Message::ToggleBookmark,
+            MenuAction::Bookmarks => Message::ToggleContextPage(ContextPage::Bookmarks),
+            MenuAction::BookmarksBar => Message::ToggleBookmarksBar,
+            MenuAction::CloseTab => Message::CloseActiveTab,
+            MenuAction::Console => Message::ToggleContextPage(ContextPage::Console),
+            MenuAction::Find => Message::Find,
+            MenuAction::FindNext => Message::FindNext,
+            MenuAction::FindPrevious => Message::FindPrevious,
+            MenuAction::FocusAddressBar => Message::FocusAddressBar,
+            MenuAction::Forward => Message::WebView(web::Action::GoForward),
+            MenuAction::History => Message::OpenHistory,
+            MenuAction::Inspect => Message::Inspect,
+            MenuAction::LastTab => Message::SelectLastTab,
+            MenuAction::NewPrivateWindow => Message::NewPrivateWindow,
             MenuAction::NewTab => Message::NewTab,
+            MenuAction::NewWindow => Message::NewWindow,
+            MenuAction::NextTab => Message::CycleTab(1),
+            MenuAction::PreviousTab => Message::CycleTab(-1),
+            MenuAction::Reload => Message::WebView(web::Action::Refresh),
+            MenuAction::ReopenClosedTab(index) => Message::ReopenClosedTab(*index),
+            MenuAction::SearchEngines => Message::ToggleContextPage(ContextPage::SearchEngines),
+            MenuAction::SelectTab(index) => Message::SelectTab(*index),
+            MenuAction::Settings => Message::ToggleContextPage(ContextPage::Settings),
+            MenuAction::Shortcuts => Message::ToggleContextPage(ContextPage::Shortcuts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::Engine as _;
+
+    fn app() -> AppModel {
+        // the default flags keep tests off the user's config, history, bookmarks and session
+        let mut app = AppModel::init(Core::default(), Flags::default()).0;
+        created(&mut app);
+        app
+    }
+
+    /// Replays the message the runtime would deliver once the newest view exists.
+    fn created(app: &mut AppModel) -> web::ViewId {
+        let view = *app.webview.engine().view_ids().last().expect("no view");
+        let _ = app.update(Message::WebViewCreated(view));
+        view
+    }
+
+    fn new_tab(app: &mut AppModel) -> web::ViewId {
+        let _ = app.update(Message::NewTab);
+        created(app)
+    }
+
+    fn close_tab(app: &mut AppModel, view: web::ViewId) {
+        let tab = app
+            .focused()
+            .tab_for_view(view)
+            .expect("no tab for that view");
+        let _ = app.update(Message::CloseTab(tab));
+    }
+
+    fn tab_views(app: &AppModel) -> Vec<web::ViewId> {
+        window_views(app, app.window)
+    }
+
+    fn active_view(app: &AppModel) -> Option<web::ViewId> {
+        app.focused().view()
+    }
+
+    /// The text of the focused window's tab showing `view`.
+    fn tab_text(app: &AppModel, view: web::ViewId) -> Option<&str> {
+        let browser = app.focused();
+        browser.nav.text(browser.tab_for_view(view)?)
+    }
+
+    #[test]
+    fn init_opens_one_tab() {
+        let app = app();
+
+        assert_eq!(tab_views(&app), app.webview.engine().view_ids());
+        assert_eq!(tab_views(&app).len(), 1);
+        assert_eq!(app.webview.current_view(), active_view(&app));
+    }
+
+    #[test]
+    fn history_and_session_are_kept_in_the_data_dir() {
+        let dir = std::env::temp_dir().join(format!("astrolabe-data-{}", std::process::id()));
+        let flags = Flags {
+            data_dir: Some(dir.clone()),
+            ..Flags::default()
+        };
+        let mut app = AppModel::init(Core::default(), flags).0;
+        let view = created(&mut app);
+
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::SaveSession);
+
+        assert!(dir.join("history.jsonl").exists());
+        assert!(dir.join("session.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn new_tab_activates_new_view() {
+        let mut app = app();
+
+        let first = new_tab(&mut app);
+        let second = new_tab(&mut app);
+
+        assert_eq!(tab_views(&app).len(), 3);
+        assert_eq!(&tab_views(&app)[1..], [first, second]);
+        assert_eq!(active_view(&app), Some(second));
+        assert_eq!(app.webview.current_view(), Some(second));
+    }
+
+    #[test]
+    fn close_middle_tab_keeps_other_views() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let middle = new_tab(&mut app);
+        let last = new_tab(&mut app);
+
+        close_tab(&mut app, middle);
+
+        assert_eq!(tab_views(&app), vec![start, last]);
+        assert_eq!(app.webview.engine().view_ids(), vec![start, last]);
+        assert_eq!(app.webview.current_view(), Some(last));
+    }
+
+    #[test]
+    fn close_active_last_tab_activates_previous() {
+        let mut app = app();
+        let previous = new_tab(&mut app);
+        let last = new_tab(&mut app);
+
+        close_tab(&mut app, last);
+
+        assert_eq!(active_view(&app), Some(previous));
+        assert_eq!(app.webview.current_view(), Some(previous));
+    }
+
+    #[test]
+    fn close_active_first_tab_activates_next() {
+        let mut app = app();
+        let first = tab_views(&app)[0];
+        let next = new_tab(&mut app);
+        let _ = app.update(Message::GotoTab(first));
+
+        close_tab(&mut app, first);
+
+        assert_eq!(active_view(&app), Some(next));
+        assert_eq!(app.webview.current_view(), Some(next));
+    }
+
+    #[test]
+    fn close_background_tab_keeps_active_tab() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        new_tab(&mut app);
+        let active = new_tab(&mut app);
+
+        close_tab(&mut app, start);
+
+        assert_eq!(active_view(&app), Some(active));
+        assert_eq!(app.webview.current_view(), Some(active));
+    }
+
+    #[test]
+    fn background_title_updates_its_own_tab() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        new_tab(&mut app);
+        let active = app.focused().nav.active();
+
+        let _ = app.update(Message::ViewEvent(
+            start,
+            web::ViewEvent::TitleChanged(String::from("Docs")),
+        ));
+
+        assert_eq!(tab_text(&app, start), Some("Docs"));
+        assert_eq!(app.focused().nav.active(), active);
+        assert_ne!(app.focused().nav.text(active), Some("Docs"));
+    }
+
+    #[test]
+    fn bridge_open_tab_opens_url_in_new_tab() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+
+        let _ = app.update(Message::ViewEvent(
+            start,
+            web::ViewEvent::BridgeCall {
+                name: String::from("openTab"),
+                args: vec![String::from("\"https://example.com/\"")],
+            },
+        ));
+        let view = created(&mut app);
+
+        assert_eq!(tab_views(&app), vec![start, view]);
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://example.com/"))
+        );
+    }
+
+    #[test]
+    fn address_bar_submit_navigates_current_tab() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+
+        let _ = app.update(Message::UrlSubmitted(String::from("example.com")));
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://example.com/"))
+        );
+
+        let _ = app.update(Message::UrlSubmitted(String::from("rust lang")));
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://www.google.com/search?q=rust+lang"))
+        );
+    }
+
+    #[test]
+    fn search_engines_are_picked_by_keyword_or_default() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+
+        let _ = app.update(Message::UrlSubmitted(String::from("w rust")));
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from(
+                "https://en.wikipedia.org/wiki/Special:Search?search=rust"
+            ))
+        );
+
+        let _ = app.update(Message::SearchEngineDefault(1));
+        let _ = app.update(Message::UrlSubmitted(String::from("rust")));
+        assert_eq!(app.config.default_search_engine, "DuckDuckGo");
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://duckduckgo.com/?q=rust"))
+        );
+    }
+
+    #[test]
+    fn removing_default_engine_picks_another() {
+        let mut app = app();
+
+        let _ = app.update(Message::SearchEngineRemove(0));
+        let _ = app.update(Message::SearchEngineRemove(0));
+        let _ = app.update(Message::SearchEngineRemove(0));
+
+        assert_eq!(app.config.search_engines.len(), 1);
+        assert_eq!(app.config.default_search_engine, "Wikipedia");
+    }
+
+    #[test]
+    fn opensearch_import_adds_engine() {
+        let mut app = app();
+        let path = std::env::temp_dir().join("astrolabe-opensearch-import.xml");
+        std::fs::write(
+            &path,
+            r#"<OpenSearchDescription>
+  <ShortName>Crates</ShortName>
+  <Url type="text/html" template="https://crates.io/search?q={searchTerms}"/>
+</OpenSearchDescription>"#,
+        )
+        .unwrap();
+
+        let _ = app.update(Message::OpenSearchPath(path.display().to_string()));
+        let _ = app.update(Message::OpenSearchImport);
+        let _ = std::fs::remove_file(&path);
+
+        let engine = app.config.search_engines.last().unwrap();
+        assert_eq!(engine.name, "Crates");
+        assert_eq!(engine.url, "https://crates.io/search?q=%s");
+        assert!(app.opensearch_path.is_empty());
+    }
+
+    /// Replays the events of `view` loading `url` with `title`.
+    fn visit(app: &mut AppModel, view: web::ViewId, url: &str, title: &str) {
+        let _ = app.update(Message::UrlSubmitted(String::from(url)));
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::UrlChanged(String::from(url)),
+        ));
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::TitleChanged(String::from(title)),
+        ));
+    }
+
+    #[test]
+    fn load_progress_follows_loading_events() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        let url = String::from("https://example.com/");
+
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::BeginLoading { url: url.clone() },
+        ));
+        assert_eq!(app.load_progress.get(&view), Some(&0.1));
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::DomReady));
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::UrlChanged(url)));
+        assert_eq!(app.load_progress.get(&view), Some(&0.7));
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::FinishLoading));
+        assert_eq!(app.load_progress.get(&view), None);
+    }
+
+    #[test]
+    fn stop_cancels_load_of_current_tab() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        app.webview
+            .engine_mut()
+            .begin_loading(view, "https://example.com/");
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::BeginLoading {
+                url: String::from("https://example.com/"),
+            },
+        ));
+        assert!(app.webview.view_is_loading(view));
+
+        let _ = app.update(Message::Stop);
+
+        assert!(!app.webview.view_is_loading(view));
+        assert!(app.load_progress.is_empty());
+    }
+
+    #[test]
+    fn suggestion_switches_to_open_tab() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::GotoTab(start));
+
+        let _ = app.update(Message::UrlChanged(String::from("domain")));
+        let tab = app
+            .focused()
+            .suggestions
+            .items()
+            .iter()
+            .position(|item| item.kind == omnibox::SuggestionKind::Tab(view))
+            .expect("open tab is suggested");
+        let _ = app.update(Message::SuggestionChosen(tab));
+
+        assert_eq!(active_view(&app), Some(view));
+        assert!(app.focused().suggestions.is_empty());
+    }
+
+    #[test]
+    fn suggested_history_entry_can_be_deleted() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        visit(&mut app, view, "https://example.org/", "Other");
+
+        let _ = app.update(Message::UrlChanged(String::from("domain")));
+        let _ = app.update(Message::SuggestionKey(SuggestionKey::Down));
+        let _ = app.update(Message::SuggestionKey(SuggestionKey::Down));
+        let _ = app.update(Message::SuggestionKey(SuggestionKey::Delete));
+
+        assert!(app.history.get("https://example.com/").is_none());
+        assert_eq!(app.focused().suggestions.items().len(), 1);
+    }
+
+    #[test]
+    fn typed_host_is_completed_in_the_address_bar() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "http://intranet.example/wiki", "Wiki");
+        let _ = app.update(Message::UrlSubmitted(String::from("example.org")));
+
+        let _ = app.update(Message::UrlChanged(String::from("intra")));
+        assert_eq!(app.focused().url.as_deref(), Some("intranet.example"));
+        // deleting the selected rest of the host leaves what was typed
+        let _ = app.update(Message::UrlChanged(String::from("intra")));
+        assert_eq!(app.focused().url.as_deref(), Some("intra"));
+
+        let _ = app.update(Message::UrlChanged(String::from("intran")));
+        let _ = app.update(Message::UrlSubmitted(String::from("intranet.example")));
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("http://intranet.example/"))
+        );
+    }
+
+    #[test]
+    fn favicons_are_looked_for_once_per_origin() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let other = new_tab(&mut app);
+        visit(&mut app, other, "https://example.com/more", "More");
+        let origin = String::from("https://example.com");
+
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::FinishLoading));
+        let _ = app.update(Message::ViewEvent(other, web::ViewEvent::FinishLoading));
+        let discoveries = app
+            .webview
+            .engine()
+            .scripts()
+            .iter()
+            .filter(|(_, script)| script == favicon::DISCOVERY_SCRIPT)
+            .count();
+        assert_eq!(discoveries, 1);
+        assert!(app.favicon_requests.contains(&origin));
+
+        let icon = favicon::Icon::Svg(b"<svg/>".to_vec());
+        let _ = app.update(Message::FaviconFetched(origin.clone(), Some(icon)));
+        assert!(app.favicon_requests.is_empty());
+        assert!(app.favicons.get("https://example.com/more").is_some());
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::FinishLoading));
+        assert!(app.favicon_requests.is_empty());
+    }
+
+    #[test]
+    fn visits_are_recorded_with_their_transition() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::UrlChanged(String::from("https://example.com/more")),
+        ));
+        let _ = app.update(Message::WebView(web::Action::GoBack));
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::UrlChanged(String::from("https://example.com/")),
+        ));
+
+        let transitions: Vec<Transition> = app
+            .history
+            .visits()
+            .iter()
+            .map(|visit| visit.transition)
+            .collect();
+        assert_eq!(
+            transitions,
+            [Transition::Typed, Transition::Link, Transition::BackForward]
+        );
+    }
+
+    #[test]
+    fn private_windows_record_nothing() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        visit(&mut app, start, "https://example.org/", "Example");
+        let _ = app.update(Message::NewPrivateWindow);
+        let view = created(&mut app);
+        let window = app.window.expect("private window not focused");
+
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::FinishLoading));
+        let visited: Vec<&str> = app
+            .history
+            .visits()
+            .iter()
+            .map(|visit| visit.url.as_str())
+            .collect();
+        assert_eq!(visited, ["https://example.org/"]);
+        assert!(app.favicon_requests.is_empty());
+        let session = app.session();
+        assert_eq!(session.windows.len(), 1);
+        assert_eq!(session.windows[0].tabs.len(), 1);
+
+        let _ = app.update(Message::CloseActiveTab);
+        let _ = app.update(Message::WindowClosed(window));
+        assert!(app.closed_tabs.is_empty());
+    }
+
+    /// What a read call like `astrolabe.settings()` answers `view` with.
+    fn bridge_read(app: &mut AppModel, view: web::ViewId, name: &str) -> Option<serde_json::Value> {
+        let json = app.webview.engine_mut().call_bridge(view, name, &[])?;
+        serde_json::from_str(&json).ok()
+    }
+
+    #[test]
+    fn bridge_reads_settings_and_bookmarks() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::ToggleBookmark);
+        let _ = app.update(Message::ToggleBookmarksBar);
+
+        let settings = bridge_read(&mut app, start, "settings").unwrap();
+        assert_eq!(
+            settings["defaultSearchEngine"],
+            app.config.default_search_engine.as_str()
+        );
+        assert_eq!(settings["showBookmarksBar"], app.config.show_bookmarks_bar);
+        let bookmarks = bridge_read(&mut app, start, "bookmarks").unwrap();
+        assert_eq!(bookmarks["children"][0]["url"], "https://example.com/");
+
+        // pages from the web can't read them
+        assert_eq!(bridge_read(&mut app, view, "settings"), None);
+    }
+
+    fn bridge_call(app: &mut AppModel, view: web::ViewId, name: &str, arg: &str) {
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::BridgeCall {
+                name: String::from(name),
+                args: vec![String::from(arg)],
+            },
+        ));
+    }
+
+    /// Replays the page in `view` being ready, returns the script that filled it in.
+    fn fill_script(app: &mut AppModel, view: web::ViewId) -> String {
+        let _ = app.update(Message::ViewEvent(view, web::ViewEvent::DomReady));
+        match app.webview.engine().scripts().last() {
+            Some((filled, script)) if *filled == view => script.clone(),
+            other => panic!("expected a script filling in the page, got {other:?}"),
+        }
+    }
+
+    /// The html the engine was served for the page `view` shows.
+    fn served_html(app: &AppModel, view: web::ViewId) -> String {
+        let page = app
+            .webview
+            .engine()
+            .mock_view(view)
+            .and_then(|view| view.html.clone());
+        page.expect("expected a served page")
+    }
+
+    #[test]
+    fn history_page_lists_searches_and_deletes_visits() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        visit(&mut app, view, "https://example.org/", "Other");
+
+        let _ = app.update(Message::OpenHistory);
+        assert_eq!(app.focused().url.as_deref(), Some("astrolabe://history"));
+        assert_eq!(app.webview.get_url(view), "astrolabe://history");
+        assert!(served_html(&app, view).contains("<title>History</title>"));
+        let script = fill_script(&mut app, view);
+        assert!(script.contains("https://example.com/"));
+        assert!(script.contains("https://example.org/"));
+
+        bridge_call(&mut app, view, "searchHistory", "\"org\"");
+        assert_eq!(
+            app.focused().url.as_deref(),
+            Some("astrolabe://history?q=org")
+        );
+        let script = fill_script(&mut app, view);
+        assert!(script.starts_with("showVisits(\"org\""));
+        assert!(!script.contains("https://example.com/"));
+        assert!(script.contains("https://example.org/"));
+
+        let id = app.history.visits()[0].id;
+        bridge_call(&mut app, view, "deleteHistory", &id.to_string());
+        assert!(app.history.get("https://example.com/").is_none());
+        assert_eq!(app.history.visits().len(), 1);
+    }
+
+    #[test]
+    fn internal_pages_keep_their_address_through_back_and_forward() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        let changed = |url: &str| web::ViewEvent::UrlChanged(String::from(url));
+        assert_eq!(app.focused().url.as_deref(), Some("astrolabe://newtab"));
+        assert!(served_html(&app, view).contains("<title>New Tab</title>"));
+
+        let _ = app.update(Message::UrlSubmitted(String::from("astrolabe://settings")));
+        let _ = app.update(Message::ViewEvent(view, changed("astrolabe://settings")));
+        assert_eq!(app.webview.get_url(view), "astrolabe://settings");
+        assert!(served_html(&app, view).contains("<title>Settings</title>"));
+
+        let _ = app.update(Message::WebView(web::Action::GoBack));
+        let _ = app.update(Message::ViewEvent(view, changed("astrolabe://newtab")));
+        assert_eq!(app.webview.get_url(view), "astrolabe://newtab");
+        assert_eq!(app.focused().url.as_deref(), Some("astrolabe://newtab"));
+        assert!(served_html(&app, view).contains("<title>New Tab</title>"));
+        assert!(app.can_go(view, 1));
+
+        let _ = app.update(Message::WebView(web::Action::GoForward));
+        assert_eq!(app.webview.get_url(view), "astrolabe://settings");
+        bridge_call(&mut app, view, "openPanel", "\"shortcuts\"");
+        assert_eq!(app.context_page, ContextPage::Shortcuts);
+        assert!(app.core.window.show_context);
+    }
+
+    #[test]
+    fn bookmark_toggle_stars_current_page() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+
+        let _ = app.update(Message::ToggleBookmark);
+        let bookmark = app.bookmarks.find_url("https://example.com/").unwrap();
+        assert_eq!(bookmark.title, "Example Domain");
+        let id = bookmark.id;
+
+        let _ = app.update(Message::UrlSubmitted(String::from("example.org")));
+        let _ = app.update(Message::OpenBookmark(id));
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://example.com/"))
+        );
+
+        let _ = app.update(Message::ToggleBookmark);
+        assert!(app.bookmarks.bookmarks().is_empty());
+    }
+
+    #[test]
+    fn bookmark_manager_edits_and_refiles() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::ToggleBookmark);
+        let id = app.bookmarks.find_url("https://example.com/").unwrap().id;
+        let _ = app.update(Message::BookmarkNewFolder);
+        let folder = app.bookmark_edit.as_ref().unwrap().id;
+
+        let _ = app.update(Message::BookmarkSelect(id));
+        let _ = app.update(Message::BookmarkTitle(String::from("Example")));
+        let _ = app.update(Message::BookmarkTags(String::from("test, , docs")));
+        let _ = app.update(Message::BookmarkSave);
+        let edit = app.bookmark_edit.as_ref().unwrap();
+        let index = edit.folder_ids.iter().position(|&id| id == folder).unwrap();
+        let _ = app.update(Message::BookmarkFolder(index));
+
+        let bookmark = app.bookmarks.find_url("https://example.com/").unwrap();
+        assert_eq!(bookmark.title, "Example");
+        assert_eq!(bookmark.tags, ["test", "docs"]);
+        let Some(Node::Folder(folder)) = app.bookmarks.get(folder) else {
+            panic!("folder is gone");
+        };
+        assert_eq!(folder.children[0].id(), id);
+        assert_eq!(app.bookmark_edit.as_ref().unwrap().folder, Some(index));
+    }
+
+    #[test]
+    fn bookmarks_export_and_import_through_file() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::ToggleBookmark);
+        let path = std::env::temp_dir().join("astrolabe-bookmarks-export.html");
+
+        let _ = app.update(Message::BookmarksPath(path.display().to_string()));
+        let _ = app.update(Message::BookmarksExport);
+        app.bookmarks = Bookmarks::default();
+        let _ = app.update(Message::BookmarksPath(path.display().to_string()));
+        let _ = app.update(Message::BookmarksImport);
+        let _ = std::fs::remove_file(&path);
+
+        let bookmark = app.bookmarks.find_url("https://example.com/").unwrap();
+        assert_eq!(bookmark.title, "Example Domain");
+        assert!(app.bookmarks_path.is_empty());
+    }
+
+    #[test]
+    fn tab_session_follows_back_and_forward() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        visit(&mut app, view, "https://example.org/", "Other");
+        let _ = app.update(Message::WebView(web::Action::GoBack));
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::UrlChanged(String::from("https://example.com/")),
+        ));
+
+        let tab = &app.tab_sessions[&view];
+        assert_eq!(tab.entries.len(), 2);
+        assert_eq!(tab.index, 0);
+        assert_eq!(tab.current().unwrap().title, "Example Domain");
+    }
+
+    #[test]
+    fn session_keeps_tabs_with_addresses_and_scroll() {
+        let mut app = app();
+        let first = new_tab(&mut app);
+        visit(&mut app, first, "https://example.com/", "Example Domain");
+        let second = new_tab(&mut app);
+        visit(&mut app, second, "https://example.org/", "Other");
+        app.webview
+            .engine_mut()
+            .set_script_result(SCROLL_POSITION_SCRIPT, Ok("[0,120]"));
+
+        let session = app.session();
+
+        // the start page has nothing to go back to
+        let window = &session.windows[0];
+        assert_eq!(window.tabs.len(), 2);
+        assert_eq!(window.active, 1);
+        let entry = window.tabs[1].current().unwrap();
+        assert_eq!(entry.url, "https://example.org/");
+        assert_eq!(entry.scroll, (0.0, 120.0));
+    }
+
+    #[test]
+    fn restore_loads_active_tab_and_defers_others() {
+        let mut app = app();
+        let mut first = session::Tab::default();
+        first.navigate("https://example.com/", Transition::Typed);
+        first.navigate("https://example.com/more", Transition::Link);
+        let mut second = session::Tab::default();
+        second.navigate("https://example.org/", Transition::Typed);
+        second.current_mut().unwrap().title = String::from("Other");
+
+        let _ = app.restore(Session {
+            windows: vec![session::Window {
+                tabs: vec![first, second],
+                active: 0,
+                geometry: None,
+            }],
+        });
+        let views = app.webview.engine().view_ids()[1..].to_vec();
+        for &view in &views {
+            let _ = app.update(Message::WebViewCreated(view));
+        }
+        let (first, second) = (views[0], views[1]);
+
+        assert_eq!(active_view(&app), Some(first));
+        assert_eq!(
+            app.webview.engine().get_url(first),
+            Ok(String::from("https://example.com/more"))
+        );
+        assert_eq!(tab_text(&app, second), Some("Other"));
+        assert!(app.unloaded.contains(&second));
+
+        // the restored tab goes back to the page it showed before the restart
+        assert!(app.can_go(first, -1));
+        let _ = app.update(Message::WebView(web::Action::GoBack));
+        assert_eq!(
+            app.webview.engine().get_url(first),
+            Ok(String::from("https://example.com/"))
+        );
+
+        let _ = app.update(Message::GotoTab(second));
+        assert_eq!(
+            app.webview.engine().get_url(second),
+            Ok(String::from("https://example.org/"))
+        );
+        assert!(app.unloaded.is_empty());
+    }
+
+    #[test]
+    fn closed_tab_reopens_at_its_position() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let middle = new_tab(&mut app);
+        visit(&mut app, middle, "https://example.com/", "Example Domain");
+        visit(&mut app, middle, "https://example.org/", "Other");
+        let last = new_tab(&mut app);
+
+        close_tab(&mut app, middle);
+        let _ = app.update(Message::ReopenClosedTab(0));
+        let view = created(&mut app);
+
+        assert_eq!(tab_views(&app), vec![start, view, last]);
+        assert_eq!(active_view(&app), Some(view));
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://example.org/"))
+        );
+        assert_eq!(tab_text(&app, view), Some("Other"));
+        assert!(app.can_go(view, -1));
+        assert!(app.closed_tabs.is_empty());
+    }
+
+    fn tab_action(app: &mut AppModel, view: web::ViewId, action: fn(nav_bar::Id) -> TabAction) {
+        let tab = app
+            .focused()
+            .tab_for_view(view)
+            .expect("no tab for that view");
+        let _ = app.update(Message::TabAction(action(tab)));
+    }
+
+    #[test]
+    fn pinned_tabs_stay_compact_on_top_and_in_the_session() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let docs = new_tab(&mut app);
+        visit(&mut app, docs, "https://example.com/docs", "Docs");
+        let blog = new_tab(&mut app);
+        visit(&mut app, blog, "https://example.com/blog", "Blog");
+
+        tab_action(&mut app, blog, TabAction::Pin);
+        let pinned = app.focused().tab_for_view(blog).unwrap();
+        assert_eq!(tab_views(&app), vec![blog, start, docs]);
+        assert_eq!(app.focused().nav.text(pinned), Some(""));
+        assert_eq!(app.focused().tab_title(pinned), "Blog");
+
+        // dropped tabs don't mix with the pinned ones
+        let _ = app.update(Message::TabDropped(pinned, Some(TabDrag(docs))));
+        assert_eq!(tab_views(&app), vec![blog, docs, start]);
+        let _ = app.update(Message::TabDropped(
+            app.focused().tab_for_view(start).unwrap(),
+            Some(TabDrag(blog)),
+        ));
+        assert_eq!(tab_views(&app), vec![blog, docs, start]);
+
+        let session = app.session();
+        assert_eq!(
+            session.windows[0]
+                .tabs
+                .iter()
+                .map(|tab| tab.pinned)
+                .collect::<Vec<_>>(),
+            [true, false]
+        );
+        tab_action(&mut app, blog, TabAction::Unpin);
+        assert_eq!(tab_views(&app), vec![blog, docs, start]);
+        assert_eq!(app.focused().nav.text(pinned), Some("Blog"));
+
+        let _ = app.restore(session);
+        let views = app.webview.engine().view_ids();
+        let restored = views[views.len() - 2..].to_vec();
+        for &view in &restored {
+            let _ = app.update(Message::WebViewCreated(view));
         }
+        assert_eq!(tab_views(&app)[0], restored[0]);
+        assert!(app
+            .focused()
+            .is_pinned(app.focused().tab_for_view(restored[0]).unwrap()));
+        assert!(!app
+            .focused()
+            .is_pinned(app.focused().tab_for_view(restored[1]).unwrap()));
+    }
+
+    #[test]
+    fn tab_menu_closes_others_and_below_but_not_pinned_tabs() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let views: Vec<_> = (0..4).map(|_| new_tab(&mut app)).collect();
+        tab_action(&mut app, views[3], TabAction::Pin);
+
+        tab_action(&mut app, views[1], TabAction::CloseBelow);
+        assert_eq!(tab_views(&app), vec![views[3], start, views[0], views[1]]);
+
+        tab_action(&mut app, views[0], TabAction::CloseOthers);
+        assert_eq!(tab_views(&app), vec![views[3], views[0]]);
+        assert_eq!(active_view(&app), Some(views[0]));
+    }
+
+    #[test]
+    fn duplicate_tab_opens_next_to_it_with_its_history() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+        visit(&mut app, view, "https://example.org/", "Other");
+        let last = new_tab(&mut app);
+
+        tab_action(&mut app, view, TabAction::Duplicate);
+        let duplicate = created(&mut app);
+
+        assert_eq!(&tab_views(&app)[1..], [view, duplicate, last]);
+        assert_eq!(active_view(&app), Some(duplicate));
+        assert_eq!(
+            app.webview.engine().get_url(duplicate),
+            Ok(String::from("https://example.org/"))
+        );
+        assert!(app.can_go(duplicate, -1));
+    }
+
+    /// The tabs of browser window `window`, focused or not.
+    fn window_views(app: &AppModel, window: Option<window::Id>) -> Vec<web::ViewId> {
+        let browser = app.windows.get(&window).expect("no such window");
+        browser.tabs().into_iter().map(|(_, view)| view).collect()
+    }
+
+    #[test]
+    fn new_window_gets_its_own_tabs() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+
+        let _ = app.update(Message::NewWindow);
+        let view = created(&mut app);
+        let window = app.window.expect("new window not focused");
+        assert_eq!(tab_views(&app), vec![view]);
+        assert_eq!(app.webview.current_view(), Some(view));
+
+        let _ = app.update(Message::InWindow(None, Box::new(Message::NewTab)));
+        let other = created(&mut app);
+        assert_eq!(app.window, None);
+        assert_eq!(tab_views(&app), vec![start, other]);
+        assert_eq!(window_views(&app, Some(window)), vec![view]);
+
+        // the window closes with its last tab, taking it along
+        let tab = app.windows[&Some(window)].nav.iter().next().unwrap();
+        let _ = app.update(Message::InWindow(
+            Some(window),
+            Box::new(Message::CloseTab(tab)),
+        ));
+        let _ = app.update(Message::WindowClosed(window));
+        assert!(!app.windows.contains_key(&Some(window)));
+        assert!(!app.webview.engine().view_ids().contains(&view));
+        assert_eq!(app.window, None);
+        assert_eq!(app.webview.current_view(), Some(other));
+    }
+
+    #[test]
+    fn main_window_takes_over_another_when_closing_its_last_tab() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let _ = app.update(Message::NewWindow);
+        let view = created(&mut app);
+        let window = app.window.expect("new window not focused");
+        visit(&mut app, view, "https://example.org/", "Other");
+
+        let tab = app.windows[&None].tab_for_view(start).unwrap();
+        let _ = app.update(Message::InWindow(None, Box::new(Message::CloseTab(tab))));
+        let _ = app.update(Message::WindowClosed(window));
+        assert_eq!(app.windows.len(), 1);
+        assert_eq!(app.window, None);
+        assert_eq!(window_views(&app, None), vec![view]);
+        assert_eq!(app.webview.engine().view_ids(), vec![view]);
+        assert_eq!(app.webview.current_view(), Some(view));
+        assert_eq!(app.focused().active_title(), "Other");
+    }
+
+    #[test]
+    fn tabs_move_between_windows_keeping_their_page() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let view = new_tab(&mut app);
+        visit(&mut app, view, "https://example.com/", "Example Domain");
+
+        tab_action(&mut app, view, TabAction::MoveToNewWindow);
+        let window = app.window.expect("new window not focused");
+        assert_eq!(tab_views(&app), vec![view]);
+        assert_eq!(app.webview.current_view(), Some(view));
+        assert_eq!(app.focused().active_title(), "Example Domain");
+        assert_eq!(window_views(&app, None), vec![start]);
+
+        // the main window keeps its last tab
+        let _ = app.update(Message::InWindow(None, Box::new(Message::NewTab)));
+        let last = created(&mut app);
+        close_tab(&mut app, start);
+        tab_action(&mut app, last, TabAction::MoveToNewWindow);
+        assert_eq!(app.window, None);
+        assert_eq!(tab_views(&app), vec![last]);
+
+        let target = app.focused().nav.iter().next().unwrap();
+        let _ = app.update(Message::TabDropped(target, Some(TabDrag(view))));
+        assert_eq!(tab_views(&app), vec![view, last]);
+        assert_eq!(active_view(&app), Some(view));
+        assert!(window_views(&app, Some(window)).is_empty());
+        assert_eq!(
+            app.webview.engine().get_url(view),
+            Ok(String::from("https://example.com/"))
+        );
+    }
+
+    #[test]
+    fn session_keeps_every_window_where_it_was() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        visit(&mut app, start, "https://example.com/", "Example Domain");
+        let _ = app.update(Message::NewWindow);
+        let view = created(&mut app);
+        visit(&mut app, view, "https://example.org/", "Other");
+        let window = app.window.unwrap();
+        let _ = app.update(Message::WindowEvent(
+            window,
+            window::Event::Resized(Size::new(800.0, 600.0)),
+        ));
+
+        let session = app.session();
+        assert_eq!(app.window, Some(window));
+        assert_eq!(session.windows.len(), 2);
+        let url = |index: usize| {
+            session.windows[index].tabs[0]
+                .current()
+                .unwrap()
+                .url
+                .clone()
+        };
+        assert_eq!(url(0), "https://example.com/");
+        assert_eq!(url(1), "https://example.org/");
+        assert_eq!(
+            session.windows[1].geometry,
+            Some(session::Geometry {
+                position: None,
+                size: (800.0, 600.0),
+            })
+        );
+
+        let mut restored = self::app();
+        let _ = restored.restore(session);
+        let views = restored.webview.engine().view_ids()[1..].to_vec();
+        for &view in &views {
+            let _ = restored.update(Message::WebViewCreated(view));
+        }
+        assert_eq!(restored.windows.len(), 2);
+        assert_eq!(restored.window_of_view(views[0]), Some(None));
+        assert!(restored.window_of_view(views[1]).unwrap().is_some());
+    }
+
+    #[test]
+    fn tabs_are_scaled_like_their_window() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let _ = app.update(Message::NewWindow);
+        let view = created(&mut app);
+        let window = app.window.unwrap();
+        let scale = |app: &AppModel, view| app.webview.engine().mock_view(view).unwrap().scale;
+
+        let _ = app.update(Message::WindowEvent(window, window::Event::Rescaled(2.0)));
+        let other = new_tab(&mut app);
+        assert_eq!(scale(&app, view), 2.0);
+        assert_eq!(scale(&app, other), 2.0);
+        assert_eq!(scale(&app, start), 1.0);
+
+        let _ = app.update(Message::InWindow(None, Box::new(Message::NewTab)));
+        created(&mut app);
+        let target = app.focused().nav.iter().next().unwrap();
+        let _ = app.update(Message::TabDropped(target, Some(TabDrag(other))));
+        assert_eq!(window_views(&app, Some(window)), vec![view]);
+        assert_eq!(scale(&app, other), 1.0);
+    }
+
+    #[test]
+    fn windows_without_focus_follow_their_pages_and_keep_their_size() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let to = |window, message| Message::InWindow(window, Box::new(message));
+        let resize =
+            |width, height| Message::WebView(web::Action::Resize(Size::new(width, height)));
+        let _ = app.update(to(None, resize(800, 600)));
+        let _ = app.update(Message::NewWindow);
+        let view = created(&mut app);
+        let window = app.window;
+        let _ = app.update(to(window, resize(400, 300)));
+
+        let _ = app.update(to(None, Message::Update));
+        let _ = app.update(Message::ViewEvent(
+            view,
+            web::ViewEvent::TitleChanged(String::from("Elsewhere")),
+        ));
+        assert_eq!(app.window, None);
+        assert_eq!(app.webview.current_view(), Some(start));
+        let other = &app.windows[&window];
+        assert_eq!(other.active_title(), "Elsewhere");
+        let size = |view| app.webview.engine().mock_view(view).unwrap().size;
+        assert_eq!(size(start), Size::new(800, 600));
+        assert_eq!(size(view), Size::new(400, 300));
+    }
+
+    fn press(app: &mut AppModel, modifiers: Modifiers, key: Key) {
+        let _ = app.update(Message::Key(window::Id::unique(), modifiers, key));
+    }
+
+    #[test]
+    fn tab_shortcuts_switch_and_close_tabs() {
+        let mut app = app();
+        let first = tab_views(&app)[0];
+        let second = new_tab(&mut app);
+        let third = new_tab(&mut app);
+
+        press(&mut app, Modifiers::CTRL, Key::Character("1".into()));
+        assert_eq!(active_view(&app), Some(first));
+        press(
+            &mut app,
+            Modifiers::CTRL | Modifiers::SHIFT,
+            Key::Named(Named::Tab),
+        );
+        assert_eq!(active_view(&app), Some(third));
+        press(&mut app, Modifiers::CTRL, Key::Character("2".into()));
+        assert_eq!(active_view(&app), Some(second));
+
+        press(&mut app, Modifiers::CTRL, Key::Character("w".into()));
+        assert_eq!(tab_views(&app), vec![first, third]);
+        assert_eq!(app.webview.current_view(), Some(first));
+        press(&mut app, Modifiers::CTRL, Key::Character("9".into()));
+        assert_eq!(active_view(&app), Some(third));
+    }
+
+    #[test]
+    fn shortcut_editor_rebinds_actions() {
+        let mut app = app();
+        let new_tab = shortcuts::actions()
+            .iter()
+            .position(|&action| action == MenuAction::NewTab)
+            .unwrap();
+        let opened = app.webview.engine().view_ids().len();
+
+        let _ = app.update(Message::ShortcutInput(new_tab, String::from("ctrl+n")));
+        let _ = app.update(Message::ShortcutSave(new_tab));
+        assert_eq!(app.config.shortcuts["new-tab"], "Ctrl+N");
+        press(&mut app, Modifiers::CTRL, Key::Character("t".into()));
+        assert_eq!(app.webview.engine().view_ids().len(), opened);
+        press(&mut app, Modifiers::CTRL, Key::Character("n".into()));
+        assert_eq!(app.webview.engine().view_ids().len(), opened + 1);
+
+        // pages must keep getting plain keys
+        let _ = app.update(Message::ShortcutInput(new_tab, String::from("N")));
+        let _ = app.update(Message::ShortcutSave(new_tab));
+        assert_eq!(app.config.shortcuts["new-tab"], "Ctrl+N");
+
+        let _ = app.update(Message::ShortcutReset(new_tab));
+        assert!(app.config.shortcuts.is_empty());
+        press(&mut app, Modifiers::CTRL, Key::Character("t".into()));
+        assert_eq!(app.webview.engine().view_ids().len(), opened + 2);
+    }
+
+    #[test]
+    fn find_bar_searches_the_current_page() {
+        let mut app = app();
+        let view = tab_views(&app)[0];
+
+        press(&mut app, Modifiers::CTRL, Key::Character("f".into()));
+        assert_eq!(app.focused().find.as_deref(), Some(""));
+        let _ = app.update(Message::FindInput(String::from("astrolabe")));
+        let script = &app.webview.engine().scripts().last().unwrap().1;
+        assert!(script.ends_with(r#"window.find("astrolabe", false, false, true)"#));
+        assert!(app.focused().find_failed);
+
+        app.webview
+            .engine_mut()
+            .set_script_result(r#"window.find("astrolabe", false, true, true)"#, Ok("true"));
+        press(&mut app, Modifiers::SHIFT, Key::Named(Named::F3));
+        assert_eq!(app.webview.engine().scripts().last().unwrap().0, view);
+        assert!(!app.focused().find_failed);
+
+        let _ = app.update(Message::FindClose);
+        assert!(app.focused().find.is_none());
+    }
+
+    #[test]
+    fn console_submit_records_result() {
+        let mut app = app();
+
+        let _ = app.update(Message::ConsoleInput(String::from("1 + 1")));
+        let _ = app.update(Message::ConsoleSubmit);
+        let _ = app.update(Message::ScriptResult(Ok(String::from("2"))));
+
+        assert!(app.console_input.is_empty());
+        assert_eq!(
+            app.console_history,
+            vec![(String::from("1 + 1"), Some(Ok(String::from("2"))))]
+        );
+        assert_eq!(
+            app.webview
+                .engine()
+                .scripts()
+                .last()
+                .map(|(_, source)| source.as_str()),
+            Some("1 + 1")
+        );
+    }
+
+    #[test]
+    fn close_tab_then_new_tab_gets_fresh_view() {
+        let mut app = app();
+        let start = tab_views(&app)[0];
+        let closed = new_tab(&mut app);
+        close_tab(&mut app, closed);
+
+        let view = new_tab(&mut app);
+
+        assert_ne!(view, closed);
+        assert_eq!(tab_views(&app), vec![start, view]);
+        assert_eq!(app.webview.current_view(), Some(view));
+    }
+
+    #[test]
+    fn inspector_window_follows_inspected_tab() {
+        let mut app = app();
+        let view = new_tab(&mut app);
+
+        let _ = app.update(Message::Inspect);
+        assert!(app.inspector_window.is_some());
+        assert!(app.webview.has_inspector());
+        assert_eq!(app.webview.engine().view_ids().len(), 3);
+
+        close_tab(&mut app, view);
+        assert!(app.inspector_window.is_none());
+        assert!(!app.webview.has_inspector());
+        assert_eq!(app.webview.engine().view_ids().len(), 1);
     }
 }
@@ -0,0 +1,701 @@
+// SPDX-License-Identifier: MIT
+
+//! Saved pages, arranged in nested folders, kept on disk as JSON.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::storage;
+
+/// Id of the top folder, whose items make up the bookmarks bar.
+pub const ROOT: u64 = 0;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: u64,
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub added: SystemTime,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: u64,
+    pub title: String,
+    /// Bookmarks and folders in the order the user arranged them.
+    pub children: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Node {
+    Bookmark(Bookmark),
+    Folder(Folder),
+}
+
+impl Node {
+    pub fn id(&self) -> u64 {
+        match self {
+            Node::Bookmark(bookmark) => bookmark.id,
+            Node::Folder(folder) => folder.id,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            Node::Bookmark(bookmark) => &bookmark.title,
+            Node::Folder(folder) => &folder.title,
+        }
+    }
+}
+
+impl Folder {
+    fn new(id: u64, title: &str) -> Self {
+        Self {
+            id,
+            title: title.to_string(),
+            children: Vec::new(),
+        }
+    }
+
+    /// The bookmark or folder `id` anywhere inside this folder.
+    pub fn find(&self, id: u64) -> Option<&Node> {
+        self.children.iter().find_map(|node| match node {
+            _ if node.id() == id => Some(node),
+            Node::Folder(folder) => folder.find(id),
+            Node::Bookmark(_) => None,
+        })
+    }
+
+    fn folder_mut(&mut self, id: u64) -> Option<&mut Folder> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|node| match node {
+            Node::Folder(folder) => folder.folder_mut(id),
+            Node::Bookmark(_) => None,
+        })
+    }
+
+    /// The folder holding `id` and its position there.
+    fn locate(&self, id: u64) -> Option<(u64, usize)> {
+        self.children
+            .iter()
+            .enumerate()
+            .find_map(|(index, node)| match node {
+                _ if node.id() == id => Some((self.id, index)),
+                Node::Folder(folder) => folder.locate(id),
+                Node::Bookmark(_) => None,
+            })
+    }
+
+    fn take(&mut self, id: u64) -> Option<Node> {
+        if let Some(index) = self.children.iter().position(|node| node.id() == id) {
+            return Some(self.children.remove(index));
+        }
+        self.children.iter_mut().find_map(|node| match node {
+            Node::Folder(folder) => folder.take(id),
+            Node::Bookmark(_) => None,
+        })
+    }
+
+    fn max_id(&self) -> u64 {
+        self.children
+            .iter()
+            .map(|node| match node {
+                Node::Bookmark(bookmark) => bookmark.id,
+                Node::Folder(folder) => folder.max_id(),
+            })
+            .fold(self.id, u64::max)
+    }
+
+    fn collect_bookmarks<'a>(&'a self, bookmarks: &mut Vec<&'a Bookmark>) {
+        for node in &self.children {
+            match node {
+                Node::Bookmark(bookmark) => bookmarks.push(bookmark),
+                Node::Folder(folder) => folder.collect_bookmarks(bookmarks),
+            }
+        }
+    }
+
+    fn collect_nodes<'a>(&'a self, depth: usize, nodes: &mut Vec<(&'a Node, usize)>) {
+        for node in &self.children {
+            nodes.push((node, depth));
+            if let Node::Folder(folder) = node {
+                folder.collect_nodes(depth + 1, nodes);
+            }
+        }
+    }
+
+    fn collect_folders<'a>(&'a self, depth: usize, folders: &mut Vec<(&'a Folder, usize)>) {
+        folders.push((self, depth));
+        for node in &self.children {
+            if let Node::Folder(folder) = node {
+                folder.collect_folders(depth + 1, folders);
+            }
+        }
+    }
+}
+
+/// Every bookmark, saved to a file unless created with [`Bookmarks::default`].
+#[derive(Clone, Debug)]
+pub struct Bookmarks {
+    root: Folder,
+    next_id: u64,
+    path: Option<PathBuf>,
+}
+
+impl Default for Bookmarks {
+    fn default() -> Self {
+        Self {
+            root: Folder::new(ROOT, ""),
+            next_id: ROOT + 1,
+            path: None,
+        }
+    }
+}
+
+impl Bookmarks {
+    /// Loads the bookmarks saved at `path`, starting empty if there are none yet.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let root: Folder = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Folder::new(ROOT, ""),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            next_id: root.max_id() + 1,
+            root,
+            path: Some(path),
+        })
+    }
+
+    /// The top folder, shown as the bookmarks bar.
+    pub fn root(&self) -> &Folder {
+        &self.root
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Node> {
+        self.root.find(id)
+    }
+
+    /// Every bookmark, depth first in folder order.
+    pub fn bookmarks(&self) -> Vec<&Bookmark> {
+        let mut bookmarks = Vec::new();
+        self.root.collect_bookmarks(&mut bookmarks);
+        bookmarks
+    }
+
+    /// Every bookmark and folder below the root with how deep it is nested, depth first.
+    pub fn nodes(&self) -> Vec<(&Node, usize)> {
+        let mut nodes = Vec::new();
+        self.root.collect_nodes(0, &mut nodes);
+        nodes
+    }
+
+    /// Every folder with how deep it is nested, the root first.
+    pub fn folders(&self) -> Vec<(&Folder, usize)> {
+        let mut folders = Vec::new();
+        self.root.collect_folders(0, &mut folders);
+        folders
+    }
+
+    /// The first bookmark of `url`.
+    pub fn find_url(&self, url: &str) -> Option<&Bookmark> {
+        self.bookmarks()
+            .into_iter()
+            .find(|bookmark| bookmark.url == url)
+    }
+
+    /// Adds a bookmark at the end of folder `parent` and returns its id.
+    pub fn add(
+        &mut self,
+        parent: u64,
+        title: &str,
+        url: &str,
+        added: SystemTime,
+    ) -> io::Result<Option<u64>> {
+        let id = self.next_id;
+        let Some(folder) = self.root.folder_mut(parent) else {
+            return Ok(None);
+        };
+        folder.children.push(Node::Bookmark(Bookmark {
+            id,
+            title: title.to_string(),
+            url: url.to_string(),
+            tags: Vec::new(),
+            added,
+        }));
+        self.next_id += 1;
+        self.save().map(|()| Some(id))
+    }
+
+    /// Adds an empty folder at the end of folder `parent` and returns its id.
+    pub fn add_folder(&mut self, parent: u64, title: &str) -> io::Result<Option<u64>> {
+        let id = self.next_id;
+        let Some(folder) = self.root.folder_mut(parent) else {
+            return Ok(None);
+        };
+        folder.children.push(Node::Folder(Folder::new(id, title)));
+        self.next_id += 1;
+        self.save().map(|()| Some(id))
+    }
+
+    /// Renames `id`, and retags it if it is a bookmark.
+    pub fn update(&mut self, id: u64, title: &str, tags: Vec<String>) -> io::Result<bool> {
+        let Some((parent, index)) = self.root.locate(id) else {
+            return Ok(false);
+        };
+        let folder = self
+            .root
+            .folder_mut(parent)
+            .expect("parent was just located");
+        match &mut folder.children[index] {
+            Node::Bookmark(bookmark) => {
+                bookmark.title = title.to_string();
+                bookmark.tags = tags;
+            }
+            Node::Folder(folder) => folder.title = title.to_string(),
+        }
+        self.save().map(|()| true)
+    }
+
+    /// Removes `id`, with everything in it if it is a folder.
+    pub fn remove(&mut self, id: u64) -> io::Result<bool> {
+        if self.root.take(id).is_none() {
+            return Ok(false);
+        }
+        self.save().map(|()| true)
+    }
+
+    /// Moves `id` into folder `parent` at `index`, or its end if `index` is past it.
+    ///
+    /// Folders can't be moved into themselves.
+    pub fn move_to(&mut self, id: u64, parent: u64, index: usize) -> io::Result<bool> {
+        let is_inside = match self.get(id) {
+            Some(Node::Folder(folder)) => folder.id == parent || folder.find(parent).is_some(),
+            Some(Node::Bookmark(_)) => false,
+            None => return Ok(false),
+        };
+        if is_inside || self.root.folder_mut(parent).is_none() {
+            return Ok(false);
+        }
+        let node = self.root.take(id).expect("node was just found");
+        let folder = self.root.folder_mut(parent).expect("folder was just found");
+        let index = index.min(folder.children.len());
+        folder.children.insert(index, node);
+        self.save().map(|()| true)
+    }
+
+    /// Moves `id` `offset` places up or down within its folder.
+    pub fn move_by(&mut self, id: u64, offset: isize) -> io::Result<bool> {
+        let Some((parent, index)) = self.root.locate(id) else {
+            return Ok(false);
+        };
+        let index = index.saturating_add_signed(offset);
+        self.move_to(id, parent, index)
+    }
+
+    /// Adds the bookmarks of a Netscape bookmark file to the end of the root folder.
+    ///
+    /// Items of the other browser's bookmarks bar land directly in the root, the bookmarks
+    /// bar here. Returns how many bookmarks were imported.
+    pub fn import_html(&mut self, html: &str) -> io::Result<usize> {
+        let mut parser = NetscapeParser::new(self.next_id);
+        let imported = parser.parse(html);
+        self.next_id = parser.next_id;
+        let mut bookmarks = Vec::new();
+        imported.collect_bookmarks(&mut bookmarks);
+        let count = bookmarks.len();
+        self.root.children.extend(imported.children);
+        self.save().map(|()| count)
+    }
+
+    /// The bookmarks as a Netscape bookmark file other browsers can import.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+             <!-- This is an automatically generated file.\n     \
+             It will be read and overwritten.\n     \
+             DO NOT EDIT! -->\n\
+             <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+             <TITLE>Bookmarks</TITLE>\n\
+             <H1>Bookmarks</H1>\n\
+             <DL><p>\n    \
+             <DT><H3 PERSONAL_TOOLBAR_FOLDER=\"true\">Bookmarks Bar</H3>\n    \
+             <DL><p>\n",
+        );
+        write_children(&mut html, &self.root, 2);
+        html.push_str("    </DL><p>\n</DL><p>\n");
+        html
+    }
+
+    fn save(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => storage::write_atomically(path, &serde_json::to_string(&self.root)?),
+            None => Ok(()),
+        }
+    }
+}
+
+fn write_children(html: &mut String, folder: &Folder, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for node in &folder.children {
+        match node {
+            Node::Bookmark(bookmark) => {
+                let added = bookmark
+                    .added
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                html.push_str(&format!(
+                    "{indent}<DT><A HREF=\"{}\" ADD_DATE=\"{added}\"",
+                    escape(&bookmark.url)
+                ));
+                if !bookmark.tags.is_empty() {
+                    html.push_str(&format!(" TAGS=\"{}\"", escape(&bookmark.tags.join(","))));
+                }
+                html.push_str(&format!(">{}</A>\n", escape(&bookmark.title)));
+            }
+            Node::Folder(folder) => {
+                html.push_str(&format!(
+                    "{indent}<DT><H3>{}</H3>\n{indent}<DL><p>\n",
+                    escape(&folder.title)
+                ));
+                write_children(html, folder, depth + 1);
+                html.push_str(&format!("{indent}</DL><p>\n"));
+            }
+        }
+    }
+}
+
+/// Reads the loose HTML of Netscape bookmark files, which no two browsers write alike.
+struct NetscapeParser {
+    next_id: u64,
+}
+
+impl NetscapeParser {
+    fn new(next_id: u64) -> Self {
+        Self { next_id }
+    }
+
+    fn id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn parse(&mut self, html: &str) -> Folder {
+        // `None` stands for lists that don't belong to a folder, like the outermost one
+        let mut stack: Vec<Option<(Folder, bool)>> = vec![Some((Folder::new(ROOT, ""), false))];
+        let mut heading: Option<(String, bool)> = None;
+        let mut rest = html;
+
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+            let tag = &rest[..end];
+            rest = &rest[end + 1..];
+            let name = tag
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+
+            match name.as_str() {
+                "h3" => {
+                    let (title, after) = element_text(rest, "</h3>");
+                    rest = after;
+                    let toolbar =
+                        attribute(tag, "personal_toolbar_folder").as_deref() == Some("true");
+                    heading = Some((title, toolbar));
+                }
+                "dl" => {
+                    let folder = heading
+                        .take()
+                        .map(|(title, toolbar)| (Folder::new(self.id(), &title), toolbar));
+                    stack.push(folder);
+                }
+                "/dl" if stack.len() > 1 => {
+                    if let Some((folder, toolbar)) = stack.pop().flatten() {
+                        let parent = current_folder(&mut stack);
+                        if toolbar {
+                            parent.children.extend(folder.children);
+                        } else {
+                            parent.children.push(Node::Folder(folder));
+                        }
+                    }
+                }
+                "a" => {
+                    let (title, after) = element_text(rest, "</a>");
+                    rest = after;
+                    let Some(url) = attribute(tag, "href") else {
+                        continue;
+                    };
+                    let added = attribute(tag, "add_date")
+                        .and_then(|date| date.parse().ok())
+                        .and_then(|secs| {
+                            SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+                        })
+                        .unwrap_or_else(SystemTime::now);
+                    let tags = attribute(tag, "tags")
+                        .map(|tags| {
+                            tags.split(',')
+                                .map(str::trim)
+                                .filter(|tag| !tag.is_empty())
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let bookmark = Bookmark {
+                        id: self.id(),
+                        title: if title.is_empty() { url.clone() } else { title },
+                        url,
+                        tags,
+                        added,
+                    };
+                    current_folder(&mut stack)
+                        .children
+                        .push(Node::Bookmark(bookmark));
+                }
+                _ => (),
+            }
+        }
+
+        // close folders a truncated file left open
+        while stack.len() > 1 {
+            if let Some((folder, _)) = stack.pop().flatten() {
+                current_folder(&mut stack)
+                    .children
+                    .push(Node::Folder(folder));
+            }
+        }
+        stack
+            .pop()
+            .flatten()
+            .map(|(folder, _)| folder)
+            .expect("root is never popped")
+    }
+}
+
+fn current_folder(stack: &mut [Option<(Folder, bool)>]) -> &mut Folder {
+    stack
+        .iter_mut()
+        .rev()
+        .find_map(|entry| entry.as_mut().map(|(folder, _)| folder))
+        .expect("root is never popped")
+}
+
+/// The text up to the next tag, and what follows that tag if it is `closing`.
+fn element_text<'a>(html: &'a str, closing: &str) -> (String, &'a str) {
+    let end = html.find('<').unwrap_or(html.len());
+    let text = unescape(html[..end].trim());
+    let rest = &html[end..];
+    match rest.get(..closing.len()) {
+        Some(tag) if tag.eq_ignore_ascii_case(closing) => (text, &rest[closing.len()..]),
+        _ => (text, rest),
+    }
+}
+
+/// The value of attribute `name` in the inside of a tag, ignoring the name's case.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lowercase = tag.to_ascii_lowercase();
+    let start = lowercase.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = start + tag[start..].find('"')?;
+    Some(unescape(&tag[start..end]))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(folder: &Folder) -> Vec<&str> {
+        folder.children.iter().map(Node::title).collect()
+    }
+
+    fn bookmarks() -> Bookmarks {
+        let mut bookmarks = Bookmarks::default();
+        let now = SystemTime::UNIX_EPOCH;
+        bookmarks
+            .add(ROOT, "Rust", "https://rust-lang.org/", now)
+            .unwrap();
+        let docs = bookmarks.add_folder(ROOT, "Docs").unwrap().unwrap();
+        bookmarks
+            .add(docs, "docs.rs", "https://docs.rs/", now)
+            .unwrap();
+        bookmarks
+            .add(ROOT, "Example", "https://example.com/", now)
+            .unwrap();
+        bookmarks
+    }
+
+    #[test]
+    fn bookmarks_keep_folder_order() {
+        let bookmarks = bookmarks();
+
+        assert_eq!(titles(bookmarks.root()), ["Rust", "Docs", "Example"]);
+        let urls: Vec<&str> = bookmarks
+            .bookmarks()
+            .iter()
+            .map(|bookmark| bookmark.url.as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://rust-lang.org/",
+                "https://docs.rs/",
+                "https://example.com/"
+            ]
+        );
+        assert_eq!(
+            bookmarks.find_url("https://docs.rs/").unwrap().title,
+            "docs.rs"
+        );
+    }
+
+    #[test]
+    fn move_reorders_and_refiles() {
+        let mut bookmarks = bookmarks();
+        let docs = bookmarks.root().children[1].id();
+        let example = bookmarks.find_url("https://example.com/").unwrap().id;
+
+        assert!(bookmarks.move_by(example, -2).unwrap());
+        assert_eq!(titles(bookmarks.root()), ["Example", "Rust", "Docs"]);
+
+        assert!(bookmarks.move_to(example, docs, 0).unwrap());
+        let Some(Node::Folder(folder)) = bookmarks.get(docs) else {
+            panic!("docs is a folder");
+        };
+        assert_eq!(titles(folder), ["Example", "docs.rs"]);
+
+        // a folder can't end up inside itself
+        assert!(!bookmarks.move_to(docs, docs, 0).unwrap());
+    }
+
+    #[test]
+    fn removing_folder_removes_contents() {
+        let mut bookmarks = bookmarks();
+        let docs = bookmarks.root().children[1].id();
+
+        assert!(bookmarks.remove(docs).unwrap());
+        assert!(!bookmarks.remove(docs).unwrap());
+        assert!(bookmarks.find_url("https://docs.rs/").is_none());
+    }
+
+    #[test]
+    fn update_retags_bookmark() {
+        let mut bookmarks = bookmarks();
+        let rust = bookmarks.find_url("https://rust-lang.org/").unwrap().id;
+
+        bookmarks
+            .update(rust, "Rust Lang", vec![String::from("lang")])
+            .unwrap();
+
+        let bookmark = bookmarks.find_url("https://rust-lang.org/").unwrap();
+        assert_eq!(bookmark.title, "Rust Lang");
+        assert_eq!(bookmark.tags, ["lang"]);
+    }
+
+    #[test]
+    fn netscape_import_reads_folders_and_toolbar() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1" PERSONAL_TOOLBAR_FOLDER="true">Bookmarks Toolbar</H3>
+    <DL><p>
+        <DT><A HREF="https://example.com/?a=1&amp;b=2" ADD_DATE="60" TAGS="a, b">Example</A>
+    </DL><p>
+    <DT><H3>Reading &amp; Notes</H3>
+    <DL><p>
+        <dt><a href="https://docs.rs/">docs.rs</a>
+        <dt><a href="https://far.example/" add_date="18000000000000000000">Far</a>
+    </DL><p>
+</DL><p>"#;
+        let mut bookmarks = Bookmarks::default();
+        let before = SystemTime::now();
+
+        assert_eq!(bookmarks.import_html(html).unwrap(), 3);
+
+        assert_eq!(titles(bookmarks.root()), ["Example", "Reading & Notes"]);
+        let example = bookmarks.find_url("https://example.com/?a=1&b=2").unwrap();
+        assert_eq!(example.tags, ["a", "b"]);
+        assert_eq!(
+            example.added,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+        assert!(bookmarks.find_url("https://docs.rs/").is_some());
+        // dates past what the clock holds count as added now
+        let far = bookmarks.find_url("https://far.example/").unwrap();
+        assert!(far.added >= before && far.added <= SystemTime::now());
+    }
+
+    #[test]
+    fn netscape_export_round_trips() {
+        let mut bookmarks = bookmarks();
+        let rust = bookmarks.find_url("https://rust-lang.org/").unwrap().id;
+        bookmarks
+            .update(rust, "Rust <3", vec![String::from("lang")])
+            .unwrap();
+
+        let mut imported = Bookmarks::default();
+        imported.import_html(&bookmarks.to_html()).unwrap();
+
+        assert_eq!(titles(imported.root()), ["Rust <3", "Docs", "Example"]);
+        assert_eq!(
+            imported.find_url("https://rust-lang.org/").unwrap().tags,
+            ["lang"]
+        );
+        assert!(imported.find_url("https://docs.rs/").is_some());
+    }
+
+    #[test]
+    fn bookmarks_survive_reopening() {
+        let path = std::env::temp_dir()
+            .join(format!("astrolabe-bookmarks-{}", std::process::id()))
+            .join("bookmarks.json");
+        let _ = fs::remove_file(&path);
+
+        let mut bookmarks = Bookmarks::open(path.clone()).unwrap();
+        let docs = bookmarks.add_folder(ROOT, "Docs").unwrap().unwrap();
+        bookmarks
+            .add(docs, "docs.rs", "https://docs.rs/", SystemTime::UNIX_EPOCH)
+            .unwrap();
+
+        let mut reopened = Bookmarks::open(path.clone()).unwrap();
+        let added = reopened
+            .add(
+                ROOT,
+                "Example",
+                "https://example.com/",
+                SystemTime::UNIX_EPOCH,
+            )
+            .unwrap();
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(reopened.root().children[0], bookmarks.root().children[0]);
+        assert_eq!(added, Some(docs + 2));
+    }
+}
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT
+
+//! Native functions internal pages reach through `window.astrolabe`.
+
+use std::sync::{Arc, RwLock};
+
+use crate::bookmarks::Bookmarks;
+use crate::config::Config;
+use crate::web;
+
+/// Requests an internal page makes of the browser.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Request {
+    /// `astrolabe.openTab(url)`
+    OpenTab(String),
+    /// `astrolabe.search(query)`
+    Search(String),
+    /// `astrolabe.searchHistory(text)`
+    SearchHistory(String),
+    /// `astrolabe.deleteHistory(visitId)`
+    DeleteHistory(u64),
+    /// `astrolabe.openPanel(name)`
+    OpenPanel(String),
+}
+
+impl Request {
+    /// Decodes a [`web::ViewEvent::BridgeCall`], ignoring calls with malformed arguments.
+    pub fn parse(name: &str, args: &[String]) -> Option<Self> {
+        let string_arg = |index: usize| {
+            args.get(index)
+                .and_then(|arg| serde_json::from_str::<String>(arg).ok())
+        };
+
+        match name {
+            "openTab" => string_arg(0).map(Request::OpenTab),
+            "search" => string_arg(0).map(Request::Search),
+            "searchHistory" => string_arg(0).map(Request::SearchHistory),
+            "deleteHistory" => args
+                .first()
+                .and_then(|arg| serde_json::from_str::<u64>(arg).ok())
+                .map(Request::DeleteHistory),
+            "openPanel" => string_arg(0).map(Request::OpenPanel),
+            _ => None,
+        }
+    }
+}
+
+/// Copies of the app's state, as JSON, that `astrolabe.settings()` and
+/// `astrolabe.bookmarks()` answer with. Bridge calls can't wait for the app to answer.
+#[derive(Clone, Debug, Default)]
+pub struct Reads {
+    settings: Arc<RwLock<String>>,
+    bookmarks: Arc<RwLock<String>>,
+}
+
+impl Reads {
+    /// Answers `astrolabe.settings()` with `config` from now on.
+    pub fn set_settings(&self, config: &Config) {
+        let settings = serde_json::json!({
+            "searchEngines": config.search_engines,
+            "defaultSearchEngine": config.default_search_engine,
+            "showBookmarksBar": config.show_bookmarks_bar,
+            "startup": config.startup,
+            "homepage": config.homepage,
+            "shortcuts": config.shortcuts,
+        });
+        replace(&self.settings, settings.to_string());
+    }
+
+    /// Answers `astrolabe.bookmarks()` with the folder tree of `bookmarks` from now on.
+    pub fn set_bookmarks(&self, bookmarks: &Bookmarks) {
+        if let Ok(tree) = serde_json::to_string(bookmarks.root()) {
+            replace(&self.bookmarks, tree);
+        }
+    }
+}
+
+fn replace(copy: &RwLock<String>, json: String) {
+    if let Ok(mut copy) = copy.write() {
+        *copy = json;
+    }
+}
+
+/// Exposes the browser's functions to internal pages loaded in `webview`, returns where
+/// the read calls get their answers.
+pub fn register<Engine, Message>(webview: &mut web::WebView<Engine, Message>) -> Reads
+where
+    Engine: web::Engine + Default,
+    Message: Send + Clone + 'static,
+{
+    webview.register_bridge_function("version", |_| {
+        serde_json::Value::from(env!("CARGO_PKG_VERSION")).to_string()
+    });
+
+    let reads = Reads::default();
+    for (name, copy) in [
+        ("settings", reads.settings.clone()),
+        ("bookmarks", reads.bookmarks.clone()),
+    ] {
+        webview.register_bridge_function(name, move |_| match copy.read() {
+            Ok(json) if !json.is_empty() => json.clone(),
+            _ => String::from("null"),
+        });
+    }
+
+    // these answer right away, the work happens once the app sees the call as a `Request`
+    for name in [
+        "openTab",
+        "search",
+        "searchHistory",
+        "deleteHistory",
+        "openPanel",
+    ] {
+        webview.register_bridge_function(name, |_| String::from("null"));
+    }
+    reads
+}
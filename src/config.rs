@@ -1,9 +1,54 @@
 // SPDX-License-Identifier: MIT
 
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
+use crate::search::{self, SearchEngine, SearchEngines};
+
+#[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
 #[version = 1]
 pub struct Config {
     demo: String,
+    pub search_engines: Vec<SearchEngine>,
+    /// Name of the engine searching anything typed without a keyword.
+    pub default_search_engine: String,
+    /// Show the top bookmarks folder under the header.
+    pub show_bookmarks_bar: bool,
+    pub startup: Startup,
+    /// Address opened on startup with [`Startup::Homepage`].
+    pub homepage: String,
+    /// Chords the user changed by action name, see [`crate::shortcuts::key_binds`].
+    pub shortcuts: BTreeMap<String, String>,
+}
+
+/// What the browser shows when it starts.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Startup {
+    #[default]
+    StartPage,
+    /// The tabs open when the browser last closed.
+    LastSession,
+    Homepage,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let search_engines = search::default_engines();
+        Self {
+            demo: String::new(),
+            default_search_engine: search_engines[0].name.clone(),
+            search_engines,
+            show_bookmarks_bar: true,
+            startup: Startup::default(),
+            homepage: String::new(),
+            shortcuts: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn search_engines(&self) -> SearchEngines<'_> {
+        SearchEngines::new(&self.search_engines, &self.default_search_engine)
+    }
 }
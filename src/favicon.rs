@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: MIT
+
+//! Site icons: found through the links a page declares, downloaded once per origin and kept
+//! on disk.
+
+use base64::Engine as _;
+use cosmic::widget::icon;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+use crate::storage;
+
+/// Evaluates to the icons a page links, as `[[href, sizes, type], …]` in document order.
+pub const DISCOVERY_SCRIPT: &str = "Array.from(\
+    document.querySelectorAll('link[rel~=\"icon\" i]'), \
+    (link) => [link.href, link.getAttribute('sizes') || '', link.type])";
+
+/// Edge length raster icons are scaled down to, enough for the nav bar on HiDPI screens.
+const MAX_SIZE: u32 = 64;
+
+/// Largest icon file downloaded, bigger ones are not icons worth showing.
+const MAX_BYTES: u64 = 1024 * 1024;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A decoded icon, rasters normalized to PNG.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Icon {
+    Png(Vec<u8>),
+    Svg(Vec<u8>),
+}
+
+impl Icon {
+    /// Reads an ICO, PNG or SVG file, scaling rasters down to [`MAX_SIZE`].
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if is_svg(bytes) {
+            return Some(Icon::Svg(bytes.to_vec()));
+        }
+        let format = image::guess_format(bytes).ok()?;
+        if !matches!(format, image::ImageFormat::Ico | image::ImageFormat::Png) {
+            return None;
+        }
+        let mut image = image::load_from_memory_with_format(bytes, format).ok()?;
+        if image.width() > MAX_SIZE || image.height() > MAX_SIZE {
+            image = image.thumbnail(MAX_SIZE, MAX_SIZE);
+        }
+        let mut png = io::Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png).ok()?;
+        Some(Icon::Png(png.into_inner()))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Icon::Png(bytes) | Icon::Svg(bytes) => bytes,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Icon::Png(_) => "png",
+            Icon::Svg(_) => "svg",
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            Icon::Png(_) => "image/png",
+            Icon::Svg(_) => "image/svg+xml",
+        }
+    }
+}
+
+fn is_svg(bytes: &[u8]) -> bool {
+    let start = &bytes[..bytes.len().min(512)];
+    let start = String::from_utf8_lossy(start);
+    let start = start.trim_start();
+    start.starts_with("<svg") || (start.starts_with("<?xml") && start.contains("<svg"))
+}
+
+/// The origin icons of the page at `url` are kept under, `None` for pages without a site.
+pub fn origin(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.origin().ascii_serialization())
+}
+
+/// Where to look for the icon of `page`, best first, from the links [`DISCOVERY_SCRIPT`]
+/// found. Scalable icons come first, then the largest, and `/favicon.ico` last.
+pub fn candidates(page: &Url, links: &str) -> Vec<Url> {
+    let links: Vec<(String, String, String)> = serde_json::from_str(links).unwrap_or_default();
+    let mut ranked: Vec<(u32, Url)> = links
+        .into_iter()
+        .filter_map(|(href, sizes, kind)| {
+            let url = Url::parse(&href).ok()?;
+            matches!(url.scheme(), "http" | "https").then(|| (rank(&url, &sizes, &kind), url))
+        })
+        .collect();
+    // stable, so equally good links keep the page's order
+    ranked.sort_by_key(|(rank, _)| Reverse(*rank));
+
+    let mut candidates: Vec<Url> = ranked.into_iter().map(|(_, url)| url).collect();
+    if let Ok(fallback) = page.join("/favicon.ico") {
+        if matches!(fallback.scheme(), "http" | "https") && !candidates.contains(&fallback) {
+            candidates.push(fallback);
+        }
+    }
+    candidates
+}
+
+/// How well a link fits, higher is better.
+fn rank(url: &Url, sizes: &str, kind: &str) -> u32 {
+    if kind == "image/svg+xml" || url.path().ends_with(".svg") || sizes == "any" {
+        return u32::MAX;
+    }
+    // `16x16 32x32` lists every size an ICO holds
+    sizes
+        .split_whitespace()
+        .filter_map(|size| size.split_once(['x', 'X']))
+        .filter_map(|(width, _)| width.parse::<u32>().ok())
+        .max()
+        .map_or(1, |size| size.min(MAX_SIZE) + 1)
+}
+
+/// Downloads the first of `candidates` that is an icon.
+pub async fn download(candidates: &[Url]) -> Option<Icon> {
+    let client = reqwest::Client::builder().timeout(TIMEOUT).build().ok()?;
+    for url in candidates {
+        if let Some(icon) = fetch(&client, url).await {
+            return Some(icon);
+        }
+    }
+    None
+}
+
+async fn fetch(client: &reqwest::Client, url: &Url) -> Option<Icon> {
+    let mut response = client
+        .get(url.as_str())
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .ok()?;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.ok()? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > MAX_BYTES {
+            return None;
+        }
+    }
+    Icon::decode(&bytes)
+}
+
+/// An icon ready to show.
+pub struct Favicon {
+    icon: Icon,
+    handle: icon::Handle,
+}
+
+impl Favicon {
+    fn new(icon: Icon) -> Self {
+        // made once, a handle per frame would decode the icon every frame
+        let handle = match &icon {
+            Icon::Png(bytes) => icon::from_raster_bytes(bytes.clone()),
+            Icon::Svg(bytes) => icon::from_svg_bytes(bytes.clone()),
+        };
+        Self { icon, handle }
+    }
+
+    pub fn handle(&self) -> &icon::Handle {
+        &self.handle
+    }
+
+    /// The icon as a `data:` url, for pages the browser generates.
+    pub fn data_url(&self) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(self.icon.bytes());
+        format!("data:{};base64,{encoded}", self.icon.mime_type())
+    }
+}
+
+/// The icons of every origin seen, one file per origin in a directory.
+#[derive(Default)]
+pub struct Cache {
+    favicons: HashMap<String, Favicon>,
+    /// Directory of the icon files, none when they aren't kept.
+    dir: Option<PathBuf>,
+}
+
+impl Cache {
+    /// Loads the icons kept in `dir`, skipping files that aren't icons.
+    pub fn open(dir: PathBuf) -> io::Result<Self> {
+        let mut favicons = HashMap::new();
+        match fs::read_dir(&dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry?.path();
+                    let Some(key) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                        continue;
+                    };
+                    let icon = match path.extension().and_then(|extension| extension.to_str()) {
+                        Some("png") => Icon::Png(fs::read(&path)?),
+                        Some("svg") => Icon::Svg(fs::read(&path)?),
+                        _ => continue,
+                    };
+                    favicons.insert(key.to_string(), Favicon::new(icon));
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        Ok(Self {
+            favicons,
+            dir: Some(dir),
+        })
+    }
+
+    /// The icon of the site the page at `url` is on.
+    pub fn get(&self, url: &str) -> Option<&Favicon> {
+        self.favicons.get(&key(&origin(url)?))
+    }
+
+    pub fn contains(&self, origin: &str) -> bool {
+        self.favicons.contains_key(&key(origin))
+    }
+
+    /// Keeps `icon` for `origin` and writes it, replacing the icon it had.
+    pub fn insert(&mut self, origin: &str, icon: Icon) -> io::Result<()> {
+        let key = key(origin);
+        if let Some(dir) = &self.dir {
+            let path = dir.join(format!("{key}.{}", icon.extension()));
+            storage::write_atomically(&path, icon.bytes())?;
+            // an icon that changed format would otherwise come back next run
+            let other = match icon {
+                Icon::Png(_) => "svg",
+                Icon::Svg(_) => "png",
+            };
+            match fs::remove_file(path.with_extension(other)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        self.favicons.insert(key, Favicon::new(icon));
+        Ok(())
+    }
+}
+
+/// The file name `origin` is kept under, without the characters paths can't hold.
+fn key(origin: &str) -> String {
+    origin.replace("://", "_").replace([':', '/', '\\'], "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_1X1: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f,
+        0x15, 0xc4, 0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xf8,
+        0xcf, 0xc0, 0xf0, 0x1f, 0x00, 0x05, 0x00, 0x01, 0xff, 0x89, 0x99, 0x3d, 0x1d, 0x00, 0x00,
+        0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn candidates_prefer_scalable_then_large_icons() {
+        let page = Url::parse("https://example.com/docs/page.html").unwrap();
+        let links = r#"[
+            ["https://example.com/small.png", "16x16", "image/png"],
+            ["https://example.com/icon.ico", "16x16 48x48", ""],
+            ["data:image/png;base64,AAAA", "", ""],
+            ["https://cdn.example.com/logo.svg", "", ""]
+        ]"#;
+
+        let candidates: Vec<String> = candidates(&page, links)
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            candidates,
+            [
+                "https://cdn.example.com/logo.svg",
+                "https://example.com/icon.ico",
+                "https://example.com/small.png",
+                "https://example.com/favicon.ico",
+            ]
+        );
+        assert_eq!(
+            super::candidates(&page, "null"),
+            [Url::parse("https://example.com/favicon.ico").unwrap()]
+        );
+    }
+
+    #[test]
+    fn decodes_png_and_svg_only() {
+        assert!(matches!(Icon::decode(PNG_1X1), Some(Icon::Png(_))));
+        let svg = br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"/>"#;
+        assert_eq!(Icon::decode(svg), Some(Icon::Svg(svg.to_vec())));
+        assert_eq!(Icon::decode(b"<html>not found</html>"), None);
+    }
+
+    #[test]
+    fn icons_are_kept_per_origin() {
+        let dir = std::env::temp_dir().join(format!("astrolabe-favicons-{}", std::process::id()));
+        let mut cache = Cache::open(dir.clone()).unwrap();
+        let origin = origin("https://example.com:8443/page").unwrap();
+
+        cache
+            .insert(&origin, Icon::decode(PNG_1X1).unwrap())
+            .unwrap();
+        cache
+            .insert(&origin, Icon::Svg(b"<svg/>".to_vec()))
+            .unwrap();
+        let reopened = Cache::open(dir.clone()).unwrap();
+        let files = fs::read_dir(&dir).unwrap().count();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(files, 1);
+        assert!(reopened.contains("https://example.com:8443"));
+        assert!(reopened.get("https://example.com:8443/other").is_some());
+        assert!(reopened.get("https://example.com/").is_none());
+        assert_eq!(
+            reopened
+                .get("https://example.com:8443/")
+                .unwrap()
+                .data_url(),
+            "data:image/svg+xml;base64,PHN2Zy8+"
+        );
+        assert!(origin("astrolabe://history").is_none());
+    }
+}
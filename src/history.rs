@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: MIT
+
+//! Pages the user has visited, kept on disk as one JSON visit per line, with the titles
+//! pages ended up with appended as they change.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::storage;
+use crate::web::engine::is_app_url;
+
+/// How a visit came about.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    /// Followed from a page, or anything the browser did not start itself.
+    Link,
+    /// An address typed or picked in the address bar.
+    Typed,
+    /// A search results page for text typed in the address bar or start page.
+    Generated,
+    /// Opened from a bookmark.
+    Bookmark,
+    Reload,
+    BackForward,
+}
+
+/// One committed navigation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub id: u64,
+    pub url: String,
+    pub title: String,
+    pub time: SystemTime,
+    pub transition: Transition,
+}
+
+/// A visited url, merged across visits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub url: String,
+    pub title: String,
+    pub visits: u32,
+    pub last_visit: SystemTime,
+}
+
+/// A line of the history file.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Record {
+    Visit(Visit),
+    /// The title the latest visit to `url` ended up with.
+    Title {
+        url: String,
+        title: String,
+    },
+}
+
+/// Where the entry of a url and its latest visit are.
+#[derive(Clone, Copy, Debug)]
+struct Position {
+    entry: usize,
+    visit: usize,
+}
+
+/// Every visit, oldest first, saved to a file unless created with [`History::default`].
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    visits: Vec<Visit>,
+    entries: Vec<Entry>,
+    positions: HashMap<String, Position>,
+    next_id: u64,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Loads the history saved at `path`, starting empty if there is none yet.
+    ///
+    /// Lines that can't be read, e.g. from a crash mid-write, are skipped.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let mut history = Self {
+            path: Some(path),
+            ..Self::default()
+        };
+        for record in contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+        {
+            match record {
+                Record::Visit(visit) => history.push(visit),
+                Record::Title { url, title } => {
+                    history.retitle(&url, &title);
+                }
+            }
+        }
+        Ok(history)
+    }
+
+    /// Records a visit to `url`, internal pages are not remembered.
+    pub fn record(
+        &mut self,
+        url: &str,
+        time: SystemTime,
+        transition: Transition,
+    ) -> io::Result<()> {
+        if url.is_empty() || url == "about:blank" || is_app_url(url) {
+            return Ok(());
+        }
+        let visit = Visit {
+            id: self.next_id,
+            url: url.to_string(),
+            title: self
+                .get(url)
+                .map(|entry| entry.title.clone())
+                .unwrap_or_default(),
+            time,
+            transition,
+        };
+        self.push(visit);
+        self.append(self.visits.last().expect("visit was just pushed"))
+    }
+
+    /// Remembers the title `url` ended up with, appending it to the file.
+    pub fn set_title(&mut self, url: &str, title: &str) -> io::Result<()> {
+        if !self.retitle(url, title) {
+            return Ok(());
+        }
+        self.append(&Record::Title {
+            url: url.to_string(),
+            title: title.to_string(),
+        })
+    }
+
+    /// Forgets every visit to `url`, returns whether it was known.
+    pub fn remove(&mut self, url: &str) -> io::Result<bool> {
+        self.remove_where(|visit| visit.url == url)
+    }
+
+    /// Forgets the visit with `id`, returns whether it was known.
+    pub fn remove_visit(&mut self, id: u64) -> io::Result<bool> {
+        self.remove_where(|visit| visit.id == id)
+    }
+
+    /// Every entry, in the order their urls were first visited.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn get(&self, url: &str) -> Option<&Entry> {
+        let position = self.positions.get(url)?;
+        self.entries.get(position.entry)
+    }
+
+    /// Every visit, oldest first.
+    pub fn visits(&self) -> &[Visit] {
+        &self.visits
+    }
+
+    /// Visits made within `range`, newest first.
+    pub fn between(&self, range: Range<SystemTime>) -> impl Iterator<Item = &Visit> {
+        self.visits
+            .iter()
+            .rev()
+            .filter(move |visit| range.contains(&visit.time))
+    }
+
+    /// Visits whose url or title contains `text`, ignoring case, newest first.
+    pub fn search<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a Visit> {
+        let text = text.trim().to_lowercase();
+        self.visits.iter().rev().filter(move |visit| {
+            visit.url.to_lowercase().contains(&text) || visit.title.to_lowercase().contains(&text)
+        })
+    }
+
+    /// Adds `visit` as the latest, counting it towards the entry for its url.
+    fn push(&mut self, visit: Visit) {
+        self.next_id = self.next_id.max(visit.id + 1);
+        let entry = match self.positions.get(&visit.url) {
+            Some(position) => {
+                let entry = &mut self.entries[position.entry];
+                entry.visits += 1;
+                entry.last_visit = visit.time;
+                if !visit.title.is_empty() {
+                    entry.title = visit.title.clone();
+                }
+                position.entry
+            }
+            None => {
+                self.entries.push(Entry {
+                    url: visit.url.clone(),
+                    title: visit.title.clone(),
+                    visits: 1,
+                    last_visit: visit.time,
+                });
+                self.entries.len() - 1
+            }
+        };
+        let position = Position {
+            entry,
+            visit: self.visits.len(),
+        };
+        self.positions.insert(visit.url.clone(), position);
+        self.visits.push(visit);
+    }
+
+    /// Titles the latest visit to `url` and its entry, returns whether the title changed.
+    fn retitle(&mut self, url: &str, title: &str) -> bool {
+        let Some(&Position { entry, visit }) = self.positions.get(url) else {
+            return false;
+        };
+        if self.visits[visit].title == title {
+            return false;
+        }
+        self.visits[visit].title = title.to_string();
+        self.entries[entry].title = title.to_string();
+        true
+    }
+
+    fn rebuild_entries(&mut self) {
+        self.entries.clear();
+        self.positions.clear();
+        for visit in std::mem::take(&mut self.visits) {
+            self.push(visit);
+        }
+    }
+
+    fn remove_where(&mut self, f: impl Fn(&Visit) -> bool) -> io::Result<bool> {
+        let len = self.visits.len();
+        self.visits.retain(|visit| !f(visit));
+        if self.visits.len() == len {
+            return Ok(false);
+        }
+        self.rebuild_entries();
+        self.save().map(|()| true)
+    }
+
+    fn append(&self, record: &impl Serialize) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        // a line torn by a crash mid-write is ended first, or it would take the record along
+        if file.metadata()?.len() > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last != *b"\n" {
+                line.insert(0, '\n');
+            }
+        }
+        file.write_all(line.as_bytes())
+    }
+
+    /// Rewrites the whole file, folding the appended titles into the visits.
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut contents = String::new();
+        for visit in &self.visits {
+            contents.push_str(&serde_json::to_string(visit)?);
+            contents.push('\n');
+        }
+        storage::write_atomically(path, &contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn minutes(minutes: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn visits_are_merged_by_url() {
+        let mut history = History::default();
+        let start = SystemTime::UNIX_EPOCH;
+
+        history
+            .record("https://example.com/", start, Transition::Typed)
+            .unwrap();
+        history
+            .record("https://example.org/", start, Transition::Link)
+            .unwrap();
+        history
+            .record(
+                "https://example.com/",
+                start + Duration::from_secs(60),
+                Transition::Link,
+            )
+            .unwrap();
+        history
+            .set_title("https://example.com/", "Example")
+            .unwrap();
+
+        let entry = history.get("https://example.com/").unwrap();
+        assert_eq!(history.entries().len(), 2);
+        assert_eq!(history.visits().len(), 3);
+        assert_eq!(entry.url, "https://example.com/");
+        assert_eq!(entry.title, "Example");
+        assert_eq!(entry.visits, 2);
+        assert_eq!(entry.last_visit, start + Duration::from_secs(60));
+    }
+
+    #[test]
+    fn internal_pages_are_not_recorded() {
+        let mut history = History::default();
+
+        history
+            .record("about:blank", SystemTime::UNIX_EPOCH, Transition::Typed)
+            .unwrap();
+        history
+            .record(
+                "astrolabe://newtab",
+                SystemTime::UNIX_EPOCH,
+                Transition::Typed,
+            )
+            .unwrap();
+
+        assert!(history.entries().is_empty());
+        assert!(history.visits().is_empty());
+    }
+
+    #[test]
+    fn remove_forgets_url() {
+        let mut history = History::default();
+        history
+            .record(
+                "https://example.com/",
+                SystemTime::UNIX_EPOCH,
+                Transition::Typed,
+            )
+            .unwrap();
+
+        assert!(history.remove("https://example.com/").unwrap());
+        assert!(!history.remove("https://example.com/").unwrap());
+        assert!(history.get("https://example.com/").is_none());
+    }
+
+    #[test]
+    fn remove_visit_keeps_other_visits_of_url() {
+        let mut history = History::default();
+        history
+            .record("https://example.com/", minutes(1), Transition::Typed)
+            .unwrap();
+        history
+            .record("https://example.com/", minutes(2), Transition::Reload)
+            .unwrap();
+        let latest = history.visits()[1].id;
+
+        assert!(history.remove_visit(latest).unwrap());
+
+        let entry = history.get("https://example.com/").unwrap();
+        assert_eq!(entry.visits, 1);
+        assert_eq!(entry.last_visit, minutes(1));
+    }
+
+    #[test]
+    fn queries_by_range_and_text_are_newest_first() {
+        let mut history = History::default();
+        history
+            .record("https://example.com/", minutes(1), Transition::Typed)
+            .unwrap();
+        history
+            .record("https://docs.rs/", minutes(2), Transition::Link)
+            .unwrap();
+        history.set_title("https://docs.rs/", "Docs.rs").unwrap();
+        history
+            .record("https://example.org/", minutes(3), Transition::Link)
+            .unwrap();
+
+        let urls = |visits: Vec<&Visit>| -> Vec<String> {
+            visits.into_iter().map(|visit| visit.url.clone()).collect()
+        };
+        assert_eq!(
+            urls(history.between(minutes(2)..minutes(4)).collect()),
+            ["https://example.org/", "https://docs.rs/"]
+        );
+        assert_eq!(
+            urls(history.search("EXAMPLE").collect()),
+            ["https://example.org/", "https://example.com/"]
+        );
+        assert_eq!(
+            urls(history.search("docs.rs").collect()),
+            ["https://docs.rs/"]
+        );
+    }
+
+    #[test]
+    fn titles_are_appended_and_replayed() {
+        let path = std::env::temp_dir()
+            .join(format!("astrolabe-history-titles-{}", std::process::id()))
+            .join("history.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut history = History::open(path.clone()).unwrap();
+        history
+            .record("https://example.com/", minutes(1), Transition::Typed)
+            .unwrap();
+        history
+            .set_title("https://example.com/", "Loading")
+            .unwrap();
+        history
+            .set_title("https://example.com/", "Example")
+            .unwrap();
+        history
+            .set_title("https://example.com/", "Example")
+            .unwrap();
+
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        let reopened = History::open(path.clone()).unwrap();
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(lines, 3);
+        assert_eq!(reopened.visits(), history.visits());
+        assert_eq!(
+            reopened.get("https://example.com/").unwrap().title,
+            "Example"
+        );
+    }
+
+    #[test]
+    fn history_survives_reopening() {
+        let path = std::env::temp_dir()
+            .join(format!("astrolabe-history-{}", std::process::id()))
+            .join("history.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut history = History::open(path.clone()).unwrap();
+        history
+            .record("https://example.com/", minutes(1), Transition::Typed)
+            .unwrap();
+        history
+            .record("https://example.org/", minutes(2), Transition::Link)
+            .unwrap();
+        history
+            .set_title("https://example.com/", "Example")
+            .unwrap();
+        history.remove("https://example.org/").unwrap();
+        history
+            .record("https://docs.rs/", minutes(3), Transition::Generated)
+            .unwrap();
+
+        let reopened = History::open(path.clone()).unwrap();
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(reopened.visits(), history.visits());
+        assert_eq!(reopened.entries(), history.entries());
+        assert_eq!(reopened.visits()[0].title, "Example");
+        assert_eq!(reopened.visits()[1].transition, Transition::Generated);
+    }
+
+    #[test]
+    fn records_after_a_torn_line_are_kept() {
+        let path = std::env::temp_dir()
+            .join(format!("astrolabe-history-torn-{}", std::process::id()))
+            .join("history.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut history = History::open(path.clone()).unwrap();
+        history
+            .record("https://example.com/", minutes(1), Transition::Typed)
+            .unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, r#"{{"id":1,"url":"https://exa"#).unwrap();
+        let mut history = History::open(path.clone()).unwrap();
+        history
+            .record("https://example.org/", minutes(2), Transition::Link)
+            .unwrap();
+        history
+            .set_title("https://example.org/", "Example")
+            .unwrap();
+
+        let reopened = History::open(path.clone()).unwrap();
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(reopened.visits(), history.visits());
+        assert_eq!(reopened.visits().len(), 2);
+        assert_eq!(
+            reopened.get("https://example.org/").unwrap().title,
+            "Example"
+        );
+    }
+}
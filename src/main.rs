@@ -1,10 +1,20 @@
 // SPDX-License-Identifier: MIT
 
 mod app;
+mod bookmarks;
+mod bridge;
 mod config;
+mod favicon;
+mod history;
 mod i18n;
-mod startpage;
+mod omnibox;
+mod pages;
+mod search;
+mod session;
+mod shortcuts;
+mod storage;
 mod web;
+mod window;
 
 fn main() -> cosmic::iced::Result {
     // Get the system's preferred languages.
@@ -20,6 +30,11 @@ fn main() -> cosmic::iced::Result {
             .min_height(180.0),
     );
 
-    // Starts the application's event loop with `()` as the application's flags.
-    cosmic::app::run::<app::AppModel>(settings, ())
+    let flags = app::Flags {
+        data_dir: storage::data_dir(),
+        config: true,
+    };
+
+    // Starts the application's event loop with the flags.
+    cosmic::app::run::<app::AppModel>(settings, flags)
 }
@@ -0,0 +1,777 @@
+// SPDX-License-Identifier: MIT
+
+//! Turns what was typed into the address bar into a page to load, and suggests pages
+//! while typing.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::time::SystemTime;
+use url::Url;
+
+use crate::bookmarks::Bookmarks;
+use crate::history::{self, History};
+use crate::search::SearchEngines;
+use crate::web::ViewId;
+
+/// Schemes that are addresses even without `//`, like `about:blank`.
+const OPAQUE_SCHEMES: [&str; 4] = ["about", "astrolabe", "data", "mailto"];
+
+/// What the typed text was taken to mean.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Input {
+    Url(Url),
+    Search {
+        /// Name of the engine searching.
+        engine: String,
+        query: String,
+        url: Url,
+    },
+}
+
+impl Input {
+    /// Reads `text` as a keyword search like `w rust`, then as an address if it looks like
+    /// one, otherwise as a search with the default engine.
+    ///
+    /// Returns `None` for blank text, or a search no engine can run.
+    pub fn parse(text: &str, engines: SearchEngines) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        let (engine, query) = match engines.keyword_search(text) {
+            Some(search) => search,
+            None => {
+                if let Some(url) = normalize_url(text) {
+                    return Some(Input::Url(url));
+                }
+                (engines.default_engine()?, text)
+            }
+        };
+        Some(Input::Search {
+            engine: engine.name.clone(),
+            query: query.to_string(),
+            url: engine.search_url(query)?,
+        })
+    }
+
+    /// The page to load for this input.
+    pub fn url(&self) -> &Url {
+        match self {
+            Input::Url(url) | Input::Search { url, .. } => url,
+        }
+    }
+}
+
+fn normalize_url(text: &str) -> Option<Url> {
+    if text.starts_with('/') {
+        return Url::from_file_path(Path::new(text)).ok();
+    }
+
+    if text.contains("://") {
+        return Url::parse(text).ok();
+    }
+
+    if text.contains(char::is_whitespace) {
+        return None;
+    }
+
+    if let Some((scheme, _)) = text.split_once(':') {
+        if OPAQUE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+            return Url::parse(text).ok();
+        }
+    }
+
+    let authority = text.split(['/', '?', '#']).next().unwrap_or_default();
+    let scheme = match Host::parse(authority)? {
+        // local servers rarely have certificates
+        Host::Local => "http",
+        Host::Domain => "https",
+    };
+    Url::parse(&format!("{scheme}://{text}")).ok()
+}
+
+/// The kinds of host an address without a scheme can start with.
+enum Host {
+    Local,
+    Domain,
+}
+
+impl Host {
+    /// Classifies `authority` (`host` or `host:port`), `None` when it is not a host.
+    fn parse(authority: &str) -> Option<Self> {
+        let host = match authority.rsplit_once(':') {
+            Some((host, port)) if !host.ends_with(':') => {
+                if !port.is_empty() && !port.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return None;
+                }
+                host
+            }
+            _ => authority,
+        };
+
+        if host.eq_ignore_ascii_case("localhost") || host.parse::<Ipv4Addr>().is_ok() {
+            return Some(Host::Local);
+        }
+        if let Some(ip) = host.strip_prefix('[').and_then(|ip| ip.strip_suffix(']')) {
+            return ip.parse::<Ipv6Addr>().is_ok().then_some(Host::Local);
+        }
+        is_domain(host).then_some(Host::Domain)
+    }
+}
+
+/// Whether `host` looks like `example.com`, with a top level domain made of letters.
+fn is_domain(host: &str) -> bool {
+    let labels: Vec<&str> = host.split('.').collect();
+    let Some((tld, _)) = labels.split_last() else {
+        return false;
+    };
+    labels.len() > 1
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+        && (tld.chars().all(char::is_alphabetic) && tld.chars().count() >= 2
+            || tld.starts_with("xn--"))
+}
+
+/// Most suggestions shown under the address bar, besides the typed text itself.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// What a bookmark never visited counts for, as much as a visit in the last few days.
+const BOOKMARK_FRECENCY: f64 = 100.0;
+
+/// Where a suggestion came from, decides what choosing it does.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SuggestionKind {
+    /// The typed text read as an address.
+    Visit,
+    /// The typed text read as a search with the named engine.
+    Search(String),
+    History,
+    Bookmark,
+    /// Switches to the tab already showing the page.
+    Tab(ViewId),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    pub title: String,
+    pub url: Url,
+}
+
+/// An open tab that may be suggested instead of loading its page again.
+#[derive(Clone, Debug)]
+pub struct OpenTab {
+    pub view: ViewId,
+    pub title: String,
+    pub url: String,
+}
+
+/// The typed text finished to the host of a page visited before.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Completion {
+    /// The typed text followed by the rest of the host, e.g. `github.com` for `git`.
+    pub text: String,
+    /// The site's front page, with the scheme it was visited with.
+    pub url: Url,
+}
+
+/// Everything suggestions are drawn from.
+#[derive(Clone, Copy, Debug)]
+pub struct Sources<'a> {
+    pub history: &'a History,
+    pub bookmarks: &'a Bookmarks,
+    pub tabs: &'a [OpenTab],
+    pub engines: SearchEngines<'a>,
+}
+
+/// Suggestions for the text in the address bar, best first.
+///
+/// The first suggestion is always what submitting the text as typed would do.
+#[derive(Clone, Debug, Default)]
+pub struct Suggestions {
+    /// The text the suggestions are for, as typed.
+    text: String,
+    items: Vec<Suggestion>,
+    selected: Option<usize>,
+    completion: Option<Completion>,
+}
+
+impl Suggestions {
+    /// Suggestions for `text`, finishing the host being typed when `complete` is set, which
+    /// it isn't while deleting.
+    pub fn new(text: &str, complete: bool, sources: Sources, now: SystemTime) -> Self {
+        let Some(input) = Input::parse(text, sources.engines) else {
+            return Self::default();
+        };
+        let terms: Vec<String> = text.split_whitespace().map(str::to_lowercase).collect();
+
+        let mut ranked: Vec<(f64, Suggestion)> = Vec::new();
+        for entry in sources.history.entries() {
+            let Ok(url) = Url::parse(&entry.url) else {
+                continue;
+            };
+            if let Some(quality) = match_quality(&terms, &url, &entry.title) {
+                let kind = match sources.tabs.iter().find(|tab| tab.url == entry.url) {
+                    Some(tab) => SuggestionKind::Tab(tab.view),
+                    None if sources.bookmarks.find_url(&entry.url).is_some() => {
+                        SuggestionKind::Bookmark
+                    }
+                    None => SuggestionKind::History,
+                };
+                let suggestion = Suggestion {
+                    kind,
+                    title: entry.title.clone(),
+                    url,
+                };
+                ranked.push((quality * frecency(entry, now), suggestion));
+            }
+        }
+        for bookmark in sources.bookmarks.bookmarks() {
+            if sources.history.get(&bookmark.url).is_some() {
+                continue;
+            }
+            let Ok(url) = Url::parse(&bookmark.url) else {
+                continue;
+            };
+            let words = format!("{} {}", bookmark.title, bookmark.tags.join(" "));
+            if let Some(quality) = match_quality(&terms, &url, &words) {
+                let suggestion = Suggestion {
+                    kind: SuggestionKind::Bookmark,
+                    title: bookmark.title.clone(),
+                    url,
+                };
+                ranked.push((quality * BOOKMARK_FRECENCY, suggestion));
+            }
+        }
+        // tabs that were never recorded, like ones still loading, rank after visited pages
+        for tab in sources.tabs {
+            if sources.history.get(&tab.url).is_some() {
+                continue;
+            }
+            let Ok(url) = Url::parse(&tab.url) else {
+                continue;
+            };
+            if let Some(quality) = match_quality(&terms, &url, &tab.title) {
+                let suggestion = Suggestion {
+                    kind: SuggestionKind::Tab(tab.view),
+                    title: tab.title.clone(),
+                    url,
+                };
+                ranked.push((quality, suggestion));
+            }
+        }
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        let completion = ranked
+            .iter()
+            .filter(|_| complete)
+            .find_map(|(_, suggestion)| complete_host(text, &suggestion.url));
+        let first = match (&completion, input) {
+            (Some(completion), _) => Suggestion {
+                kind: SuggestionKind::Visit,
+                title: completion.text.clone(),
+                url: completion.url.clone(),
+            },
+            (None, Input::Url(url)) => Suggestion {
+                kind: SuggestionKind::Visit,
+                title: url.to_string(),
+                url,
+            },
+            (None, Input::Search { engine, query, url }) => Suggestion {
+                kind: SuggestionKind::Search(engine),
+                title: query,
+                url,
+            },
+        };
+
+        let mut items = vec![first];
+        for (_, suggestion) in ranked {
+            if items.len() > MAX_SUGGESTIONS {
+                break;
+            }
+            // switching to a tab is worth offering even when its page was suggested already
+            let is_tab = matches!(suggestion.kind, SuggestionKind::Tab(_));
+            if is_tab || items.iter().all(|item| item.url != suggestion.url) {
+                items.push(suggestion);
+            }
+        }
+
+        Self {
+            text: text.to_string(),
+            items,
+            selected: None,
+            completion,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn items(&self) -> &[Suggestion] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The suggestion picked with the arrow keys, if any.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// What submitting does: the selected suggestion, or the typed text.
+    pub fn chosen(&self) -> Option<&Suggestion> {
+        self.items.get(self.selected.unwrap_or_default())
+    }
+
+    /// The host the typed text was finished to, shown in the address bar until typed over.
+    pub fn completion(&self) -> Option<&Completion> {
+        self.completion.as_ref()
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = Some(match self.selected {
+                Some(index) => (index + 1) % self.items.len(),
+                None => 0,
+            });
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = Some(match self.selected {
+                Some(0) | None => self.items.len() - 1,
+                Some(index) => index - 1,
+            });
+        }
+    }
+
+    /// Drops the suggestion at `index` once its history entry was deleted.
+    pub fn remove(&mut self, index: usize) -> Option<Suggestion> {
+        // the first suggestion is the typed text, it can't be removed
+        if index == 0 || index >= self.items.len() {
+            return None;
+        }
+        let removed = self.items.remove(index);
+        self.selected = self
+            .selected
+            .map(|selected| selected.min(self.items.len() - 1));
+        Some(removed)
+    }
+}
+
+/// How much a visit is worth, by how long ago it was. Recent visits count for more.
+fn frecency(entry: &history::Entry, now: SystemTime) -> f64 {
+    let days = now
+        .duration_since(entry.last_visit)
+        .unwrap_or_default()
+        .as_secs()
+        / (60 * 60 * 24);
+    let weight = match days {
+        0..=3 => 100.0,
+        4..=14 => 70.0,
+        15..=31 => 50.0,
+        32..=90 => 30.0,
+        _ => 10.0,
+    };
+    f64::from(entry.visits) * weight
+}
+
+/// Whether every term is in the url or title, weighted up when the host starts with the text.
+fn match_quality(terms: &[String], url: &Url, title: &str) -> Option<f64> {
+    let haystack = format!("{} {}", url.as_str(), title).to_lowercase();
+    if !terms.iter().all(|term| haystack.contains(term.as_str())) {
+        return None;
+    }
+    let host = url.host_str().map(strip_www).unwrap_or_default();
+    match terms {
+        [term] if host.starts_with(term.as_str()) => Some(2.0),
+        _ => Some(1.0),
+    }
+}
+
+/// `text` finished to `url`'s host when it is the start of it.
+fn complete_host(text: &str, url: &Url) -> Option<Completion> {
+    if text.contains(['/', ':']) || text.contains(char::is_whitespace) {
+        return None;
+    }
+    let host = strip_www(url.host_str()?);
+    let typed = strip_www(text).to_lowercase();
+    if typed.is_empty() || !host.starts_with(&typed) || !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(Completion {
+        text: format!("{text}{}", &host[typed.len()..]),
+        url: Url::parse(&url.origin().ascii_serialization()).ok()?,
+    })
+}
+
+fn strip_www(host: &str) -> &str {
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search;
+
+    fn parse(text: &str) -> Option<Input> {
+        let engines = search::default_engines();
+        Input::parse(text, SearchEngines::new(&engines, "Google"))
+    }
+
+    fn url(text: &str) -> Option<String> {
+        match parse(text)? {
+            Input::Url(url) => Some(url.to_string()),
+            Input::Search { .. } => None,
+        }
+    }
+
+    fn is_search(text: &str) -> bool {
+        matches!(parse(text), Some(Input::Search { .. }))
+    }
+
+    #[test]
+    fn blank_text_is_nothing() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("   "), None);
+    }
+
+    #[test]
+    fn urls_with_a_scheme_are_kept() {
+        assert_eq!(
+            url("https://example.com/a?b=c"),
+            Some("https://example.com/a?b=c".into())
+        );
+        assert_eq!(
+            url("http://example.com"),
+            Some("http://example.com/".into())
+        );
+        assert_eq!(
+            url("  https://example.com  "),
+            Some("https://example.com/".into())
+        );
+        assert_eq!(url("about:blank"), Some("about:blank".into()));
+        assert_eq!(
+            url("astrolabe://history"),
+            Some("astrolabe://history".into())
+        );
+    }
+
+    #[test]
+    fn bare_domains_get_https() {
+        assert_eq!(url("example.com"), Some("https://example.com/".into()));
+        assert_eq!(
+            url("www.rust-lang.org/learn"),
+            Some("https://www.rust-lang.org/learn".into())
+        );
+        assert_eq!(
+            url("example.com:8443/x#top"),
+            Some("https://example.com:8443/x#top".into())
+        );
+        assert_eq!(url("docs.rs?q=url"), Some("https://docs.rs/?q=url".into()));
+    }
+
+    #[test]
+    fn local_hosts_get_http() {
+        assert_eq!(url("localhost"), Some("http://localhost/".into()));
+        assert_eq!(
+            url("localhost:8080/app"),
+            Some("http://localhost:8080/app".into())
+        );
+        assert_eq!(url("127.0.0.1:3000"), Some("http://127.0.0.1:3000/".into()));
+        assert_eq!(url("192.168.1.1"), Some("http://192.168.1.1/".into()));
+        assert_eq!(url("[::1]:8000"), Some("http://[::1]:8000/".into()));
+    }
+
+    #[test]
+    fn file_paths_become_file_urls() {
+        assert_eq!(
+            url("file:///tmp/page.html"),
+            Some("file:///tmp/page.html".into())
+        );
+        assert_eq!(url("/tmp/page.html"), Some("file:///tmp/page.html".into()));
+    }
+
+    #[test]
+    fn everything_else_is_a_search() {
+        assert!(is_search("rust"));
+        assert!(is_search("rust borrow checker"));
+        assert!(is_search("what is example.com"));
+        assert!(is_search("1.5"));
+        assert!(is_search("example.c0m"));
+        assert!(is_search("localhost:abc"));
+        assert!(is_search("c++"));
+    }
+
+    #[test]
+    fn searches_are_encoded_into_the_search_url() {
+        let engines = search::default_engines();
+        assert_eq!(
+            Input::parse("rust & c++", SearchEngines::new(&engines, "Google"))
+                .map(|input| input.url().to_string()),
+            Some(String::from(
+                "https://www.google.com/search?q=rust+%26+c%2B%2B"
+            ))
+        );
+    }
+
+    #[test]
+    fn keywords_search_with_their_engine() {
+        assert_eq!(
+            parse("w rust"),
+            Some(Input::Search {
+                engine: String::from("Wikipedia"),
+                query: String::from("rust"),
+                url: Url::parse("https://en.wikipedia.org/wiki/Special:Search?search=rust")
+                    .unwrap(),
+            })
+        );
+        // a lone keyword is searched for like any other word
+        assert!(matches!(parse("w"), Some(Input::Search { engine, .. }) if engine == "Google"));
+    }
+
+    #[test]
+    fn default_engine_searches_everything_else() {
+        let engines = search::default_engines();
+        let input = Input::parse("rust", SearchEngines::new(&engines, "DuckDuckGo"));
+
+        assert_eq!(
+            input.map(|input| input.url().to_string()),
+            Some(String::from("https://duckduckgo.com/?q=rust"))
+        );
+        assert_eq!(
+            Input::parse("rust", SearchEngines::new(&[], "Google")),
+            None
+        );
+        assert!(Input::parse("example.com", SearchEngines::new(&[], "Google")).is_some());
+    }
+
+    fn history(visits: &[(&str, &str, u32, u64)]) -> History {
+        let mut history = History::default();
+        for &(url, title, count, days_ago) in visits {
+            for _ in 0..count {
+                history
+                    .record(url, days(100 - days_ago), history::Transition::Link)
+                    .unwrap();
+            }
+            history.set_title(url, title).unwrap();
+        }
+        history
+    }
+
+    fn days(days: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(days * 60 * 60 * 24)
+    }
+
+    fn suggest(text: &str, history: &History, tabs: &[OpenTab]) -> Suggestions {
+        suggest_from(text, true, history, &Bookmarks::default(), tabs)
+    }
+
+    fn suggest_from(
+        text: &str,
+        complete: bool,
+        history: &History,
+        bookmarks: &Bookmarks,
+        tabs: &[OpenTab],
+    ) -> Suggestions {
+        let engines = search::default_engines();
+        let engines = SearchEngines::new(&engines, "Google");
+        let sources = Sources {
+            history,
+            bookmarks,
+            tabs,
+            engines,
+        };
+        Suggestions::new(text, complete, sources, days(100))
+    }
+
+    fn urls(suggestions: &Suggestions) -> Vec<&str> {
+        suggestions
+            .items()
+            .iter()
+            .map(|item| item.url.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn typed_text_comes_first() {
+        let history = history(&[("https://rust-lang.org/", "Rust", 1, 0)]);
+
+        let suggestions = suggest("rust lang", &history, &[]);
+
+        assert_eq!(
+            suggestions.items()[0].kind,
+            SuggestionKind::Search("Google".into())
+        );
+        assert_eq!(suggestions.items()[0].title, "rust lang");
+        assert_eq!(urls(&suggestions)[1], "https://rust-lang.org/");
+        assert!(suggest("  ", &history, &[]).is_empty());
+    }
+
+    #[test]
+    fn frequent_recent_visits_rank_higher() {
+        let history = history(&[
+            ("https://example.com/old", "Old docs", 5, 60),
+            ("https://example.com/new", "New docs", 2, 1),
+            ("https://example.com/once", "Once docs", 1, 1),
+        ]);
+
+        let suggestions = suggest("docs", &history, &[]);
+
+        assert_eq!(
+            urls(&suggestions)[1..],
+            [
+                "https://example.com/new",
+                "https://example.com/old",
+                "https://example.com/once"
+            ]
+        );
+    }
+
+    #[test]
+    fn every_term_must_match() {
+        let history = history(&[
+            ("https://docs.rs/url", "url - Rust", 1, 0),
+            ("https://docs.rs/serde", "serde - Rust", 1, 0),
+        ]);
+
+        let suggestions = suggest("rust url", &history, &[]);
+
+        assert_eq!(urls(&suggestions)[1..], ["https://docs.rs/url"]);
+    }
+
+    #[test]
+    fn open_tabs_are_switched_to() {
+        let history = history(&[("https://example.com/", "Example", 1, 0)]);
+        let (first, second) = (ViewId::from_raw(3), ViewId::from_raw(4));
+        let tabs = [
+            OpenTab {
+                view: first,
+                title: String::from("Example"),
+                url: String::from("https://example.com/"),
+            },
+            OpenTab {
+                view: second,
+                title: String::from("Example loading"),
+                url: String::from("https://example.org/"),
+            },
+        ];
+
+        let suggestions = suggest("example", &history, &tabs);
+
+        let kinds: Vec<_> = suggestions.items()[1..]
+            .iter()
+            .map(|item| &item.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [&SuggestionKind::Tab(first), &SuggestionKind::Tab(second)]
+        );
+    }
+
+    #[test]
+    fn top_domain_is_completed_inline() {
+        let history = history(&[
+            ("https://www.github.com/rust-lang", "Rust", 3, 0),
+            ("https://gitlab.com/", "GitLab", 1, 0),
+        ]);
+
+        let suggestions = suggest("Git", &history, &[]);
+
+        let completion = suggestions.completion().unwrap();
+        assert_eq!(completion.text, "Github.com");
+        assert_eq!(completion.url.as_str(), "https://www.github.com/");
+        assert_eq!(suggestions.chosen().unwrap().url, completion.url);
+        assert_eq!(suggest("git ", &history, &[]).completion(), None);
+        assert_eq!(suggest("github.com/", &history, &[]).completion(), None);
+    }
+
+    #[test]
+    fn completion_keeps_the_scheme_and_skips_deleting() {
+        let history = history(&[("http://intranet.example/wiki", "Wiki", 1, 0)]);
+
+        let suggestions = suggest("intra", &history, &[]);
+        assert_eq!(
+            suggestions
+                .completion()
+                .map(|completion| completion.url.as_str()),
+            Some("http://intranet.example/")
+        );
+
+        let bookmarks = Bookmarks::default();
+        let deleting = suggest_from("intra", false, &history, &bookmarks, &[]);
+        assert_eq!(deleting.completion(), None);
+        assert_eq!(
+            deleting.chosen().unwrap().kind,
+            SuggestionKind::Search("Google".into())
+        );
+    }
+
+    #[test]
+    fn bookmarks_are_suggested() {
+        let history = history(&[("https://docs.rs/", "Docs.rs", 2, 0)]);
+        let mut bookmarks = Bookmarks::default();
+        for (title, url) in [
+            ("Docs.rs", "https://docs.rs/"),
+            ("The Rust Book", "https://doc.rust-lang.org/book/"),
+            ("Serde", "https://serde.rs/"),
+        ] {
+            bookmarks
+                .add(crate::bookmarks::ROOT, title, url, days(0))
+                .unwrap();
+        }
+
+        let suggestions = suggest_from("doc", false, &history, &bookmarks, &[]);
+
+        let items: Vec<_> = suggestions.items()[1..]
+            .iter()
+            .map(|item| (item.url.as_str(), &item.kind))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("https://docs.rs/", &SuggestionKind::Bookmark),
+                ("https://doc.rust-lang.org/book/", &SuggestionKind::Bookmark),
+            ]
+        );
+    }
+
+    #[test]
+    fn selection_wraps_around() {
+        let history = history(&[("https://example.com/", "Example Domain", 1, 0)]);
+        let mut suggestions = suggest("domain", &history, &[]);
+
+        suggestions.select_previous();
+        assert_eq!(suggestions.selected(), Some(1));
+        suggestions.select_next();
+        assert_eq!(suggestions.selected(), Some(0));
+        suggestions.select_next();
+        assert_eq!(
+            suggestions.chosen().unwrap().url.as_str(),
+            "https://example.com/"
+        );
+    }
+
+    #[test]
+    fn typed_text_can_not_be_removed() {
+        let history = history(&[("https://example.com/", "Example Domain", 1, 0)]);
+        let mut suggestions = suggest("domain", &history, &[]);
+        suggestions.select_previous();
+
+        assert_eq!(suggestions.remove(0), None);
+        assert_eq!(
+            suggestions.remove(1).map(|item| item.url.to_string()),
+            Some(String::from("https://example.com/"))
+        );
+        assert_eq!(suggestions.items().len(), 1);
+        assert_eq!(suggestions.selected(), Some(0));
+    }
+}
@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: MIT
+
+//! The `astrolabe://` pages the browser serves itself, and the page shown for loads that
+//! failed. The engine asks for them through the scheme handler, [`serve`].
+
+use base64::Engine as _;
+use rust_embed::RustEmbed;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+use url::Url;
+
+use crate::app::{APP_ICON, REPOSITORY};
+use crate::bookmarks::{Folder, Node};
+use crate::favicon;
+use crate::history::Visit;
+
+pub const SCHEME: &str = "astrolabe";
+
+/// Files the generated pages share.
+#[derive(RustEmbed)]
+#[folder = "resources/pages/"]
+struct Assets;
+
+/// A page of the browser's own.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Page {
+    NewTab,
+    History,
+    Bookmarks,
+    Downloads,
+    Settings,
+    About,
+}
+
+impl Page {
+    pub const ALL: [Page; 6] = [
+        Page::NewTab,
+        Page::History,
+        Page::Bookmarks,
+        Page::Downloads,
+        Page::Settings,
+        Page::About,
+    ];
+
+    /// The page `url` addresses, if it is one of the browser's own.
+    pub fn parse(url: &Url) -> Option<Self> {
+        if url.scheme() != SCHEME {
+            return None;
+        }
+        let host = url.host_str()?;
+        Self::ALL.into_iter().find(|page| page.host() == host)
+    }
+
+    pub fn url(self) -> Url {
+        Url::parse(&format!("{SCHEME}://{}", self.host())).expect("page urls are valid")
+    }
+
+    fn host(self) -> &'static str {
+        match self {
+            Page::NewTab => "newtab",
+            Page::History => "history",
+            Page::Bookmarks => "bookmarks",
+            Page::Downloads => "downloads",
+            Page::Settings => "settings",
+            Page::About => "about",
+        }
+    }
+}
+
+/// The html the engine is served for `url`, `None` for addresses of no page.
+///
+/// Pages showing what the browser knows are served empty, [`show_visits`] and
+/// [`show_bookmarks`] fill them in once loaded.
+pub fn serve(url: &str) -> Option<String> {
+    let html = match Page::parse(&Url::parse(url).ok()?)? {
+        Page::NewTab => new_tab(),
+        Page::History => history(),
+        Page::Bookmarks => bookmarks(),
+        Page::Downloads => downloads(),
+        Page::Settings => settings(),
+        Page::About => about(
+            APP_ICON,
+            REPOSITORY,
+            env!("VERGEN_GIT_SHA"),
+            env!("VERGEN_GIT_COMMIT_DATE"),
+        ),
+    };
+    Some(html)
+}
+
+/// A search box that takes addresses too.
+fn new_tab() -> String {
+    page(
+        "New Tab",
+        r#"<div class="centered">
+<h1>Welcome!</h1>
+<form onsubmit="handleSearch(event)">
+<input type="text" id="search-input" placeholder="Search or enter address" required>
+<button type="submit"><svg viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg">
+<path d="M21.53 20.47l-5.66-5.66a8 8 0 10-1.06 1.06l5.66 5.66a.75.75 0 001.06-1.06z
+M4 10a6 6 0 1112 0A6 6 0 014 10z"/></svg></button>
+</form>
+</div>
+<script>
+    function handleSearch(event) {
+        event.preventDefault();
+        const query = document.getElementById('search-input').value.trim();
+        // the browser decides whether this is an address or a search
+        if (query && window.astrolabe) {
+            window.astrolabe.search(query);
+        }
+    }
+</script>
+"#,
+    )
+}
+
+/// Visits grouped by day under a search box, see [`show_visits`].
+fn history() -> String {
+    page(
+        "History",
+        r#"<h1>History</h1>
+<form onsubmit="handleSearch(event)">
+<input type="search" id="search-input" placeholder="Search history">
+</form>
+<div id="visits"></div>
+<script>
+    function handleSearch(event) {
+        event.preventDefault();
+        if (window.astrolabe) {
+            window.astrolabe.searchHistory(document.getElementById('search-input').value);
+        }
+    }
+
+    function dayLabel(date) {
+        const today = new Date();
+        const yesterday = new Date();
+        yesterday.setDate(today.getDate() - 1);
+        if (date.toDateString() === today.toDateString()) {
+            return 'Today';
+        }
+        if (date.toDateString() === yesterday.toDateString()) {
+            return 'Yesterday';
+        }
+        return date.toLocaleDateString(undefined, {
+            weekday: 'long', year: 'numeric', month: 'long', day: 'numeric'
+        });
+    }
+
+    function deleteVisit(row, id) {
+        if (window.astrolabe) {
+            window.astrolabe.deleteHistory(id);
+        }
+        const day = row.parentElement;
+        row.remove();
+        if (!day.querySelector('li')) {
+            day.previousElementSibling.remove();
+            day.remove();
+        }
+    }
+
+    function showVisits(query, visits, icons) {
+        document.getElementById('search-input').value = query;
+        const list = document.getElementById('visits');
+        list.innerHTML = '';
+        let day = null;
+        let label = null;
+        for (const visit of visits) {
+            const date = new Date(visit.time);
+            if (date.toDateString() !== label) {
+                label = date.toDateString();
+                const heading = document.createElement('h2');
+                heading.textContent = dayLabel(date);
+                day = document.createElement('ul');
+                list.append(heading, day);
+            }
+
+            const row = document.createElement('li');
+            const time = document.createElement('time');
+            time.textContent = date.toLocaleTimeString(undefined, {
+                hour: '2-digit', minute: '2-digit'
+            });
+            const link = document.createElement('a');
+            link.href = visit.url;
+            link.title = visit.url;
+            link.textContent = visit.title || visit.url;
+            const icon = document.createElement('img');
+            icon.alt = '';
+            if (icons[visit.origin]) {
+                icon.src = icons[visit.origin];
+            } else {
+                icon.style.visibility = 'hidden';
+            }
+            const remove = document.createElement('button');
+            remove.title = 'Remove from history';
+            remove.textContent = '✕';
+            remove.onclick = () => deleteVisit(row, visit.id);
+            row.append(time, icon, link, remove);
+            day.appendChild(row);
+        }
+        if (visits.length === 0) {
+            const empty = document.createElement('p');
+            empty.className = 'muted';
+            empty.textContent = 'No pages found.';
+            list.appendChild(empty);
+        }
+    }
+</script>
+"#,
+    )
+}
+
+/// Script filling in the history page with `visits`, newest first, found for the search
+/// `query`, each with the icon `icons` has for its origin as a `data:` url.
+pub fn show_visits(query: &str, visits: &[&Visit], icons: &BTreeMap<String, String>) -> String {
+    let visits: Vec<serde_json::Value> = visits
+        .iter()
+        .map(|visit| {
+            let time = visit
+                .time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            serde_json::json!({
+                "id": visit.id,
+                "url": visit.url,
+                "title": visit.title,
+                "time": time,
+                "origin": favicon::origin(&visit.url),
+            })
+        })
+        .collect();
+    format!(
+        "showVisits({}, {}, {})",
+        serde_json::Value::from(query),
+        serde_json::Value::from(visits),
+        serde_json::to_string(icons).unwrap_or_else(|_| String::from("{}")),
+    )
+}
+
+/// Bookmarks by folder, see [`show_bookmarks`].
+fn bookmarks() -> String {
+    page(
+        "Bookmarks",
+        r#"<h1>Bookmarks</h1>
+<button onclick="openPanel('bookmarks')">Manage bookmarks</button>
+<div id="bookmarks"></div>
+<script>
+    function showBookmarks(html) {
+        document.getElementById('bookmarks').innerHTML = html;
+    }
+</script>
+"#,
+    )
+}
+
+/// Script filling in the bookmarks page with every bookmark under `root`, each with the
+/// icon `icons` has for its origin as a `data:` url.
+pub fn show_bookmarks(root: &Folder, icons: &BTreeMap<String, String>) -> String {
+    let html = serde_json::Value::from(bookmark_list(root, icons));
+    format!("showBookmarks({html})")
+}
+
+fn bookmark_list(root: &Folder, icons: &BTreeMap<String, String>) -> String {
+    let mut html = String::new();
+    if root.children.is_empty() {
+        html.push_str("<p class=\"muted\">No bookmarks yet.</p>\n");
+    } else {
+        push_folder(&mut html, root, icons);
+    }
+    html
+}
+
+fn push_folder(html: &mut String, folder: &Folder, icons: &BTreeMap<String, String>) {
+    html.push_str("<ul>\n");
+    for node in &folder.children {
+        match node {
+            Node::Bookmark(bookmark) => {
+                let icon = favicon::origin(&bookmark.url)
+                    .and_then(|origin| icons.get(&origin))
+                    .map_or(
+                        String::from("<img alt=\"\" style=\"visibility: hidden\">"),
+                        |icon| format!("<img alt=\"\" src=\"{}\">", escape(icon)),
+                    );
+                let title = if bookmark.title.is_empty() {
+                    &bookmark.url
+                } else {
+                    &bookmark.title
+                };
+                // scripts behind a link would run with the page's access to the browser
+                let href = match Url::parse(&bookmark.url) {
+                    Ok(url) if url.scheme() != "javascript" => escape(url.as_str()),
+                    _ => String::new(),
+                };
+                html.push_str(&format!(
+                    "<li>{icon}<a href=\"{href}\" title=\"{}\">{}</a></li>\n",
+                    escape(&bookmark.url),
+                    escape(title),
+                ));
+            }
+            Node::Folder(folder) => {
+                html.push_str(&format!("<h2>{}</h2>\n", escape(&folder.title)));
+                push_folder(html, folder, icons);
+            }
+        }
+    }
+    html.push_str("</ul>\n");
+}
+
+/// Files downloaded, which the browser doesn't do yet.
+fn downloads() -> String {
+    page(
+        "Downloads",
+        "<h1>Downloads</h1>\n<p class=\"muted\">No downloads.</p>\n",
+    )
+}
+
+/// Opens the settings, which live in the side panel.
+fn settings() -> String {
+    page(
+        "Settings",
+        "<h1>Settings</h1>\n\
+         <p class=\"muted\">Settings open in the side panel.</p>\n\
+         <button onclick=\"openPanel('settings')\">General</button>\n\
+         <button onclick=\"openPanel('search-engines')\">Search engines</button>\n\
+         <button onclick=\"openPanel('shortcuts')\">Keyboard shortcuts</button>\n",
+    )
+}
+
+/// The browser's name, version and the commit it was built from, under its `icon`.
+fn about(icon: &[u8], repository: &str, commit: &str, date: &str) -> String {
+    let icon = base64::engine::general_purpose::STANDARD.encode(icon);
+    let short_commit: String = commit.chars().take(7).collect();
+    let repository = escape(repository);
+    let body = format!(
+        "<div class=\"about\">\n\
+         <img alt=\"\" src=\"data:image/svg+xml;base64,{icon}\">\n\
+         <h1>Astrolabe</h1>\n\
+         <p>Version {}</p>\n\
+         <p><a href=\"{repository}\">{repository}</a></p>\n\
+         <p class=\"muted\"><a href=\"{repository}/commits/{}\">Git commit {} on {}</a></p>\n\
+         </div>\n",
+        env!("CARGO_PKG_VERSION"),
+        escape(commit),
+        escape(&short_commit),
+        escape(date),
+    );
+    page("About Astrolabe", &body)
+}
+
+/// Says why `url` could not be loaded.
+pub fn error(url: &str, reason: &str) -> String {
+    let body = format!(
+        "<div class=\"centered\">\n\
+         <h1>Problem loading page</h1>\n\
+         <p class=\"muted\">{}</p>\n\
+         <p class=\"muted\">{}</p>\n\
+         </div>\n",
+        escape(url),
+        escape(reason),
+    );
+    page("Problem loading page", &body)
+}
+
+/// A page titled `title` showing `body`, styled like the browser's other pages.
+fn page(title: &str, body: &str) -> String {
+    let style = Assets::get("style.css")
+        .map(|file| String::from_utf8_lossy(&file.data).into_owned())
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{}</title>
+    <style>
+{style}
+    </style>
+    <script>
+        function openPanel(name) {{
+            if (window.astrolabe) {{
+                window.astrolabe.openPanel(name);
+            }}
+        }}
+    </script>
+</head>
+<body>
+{body}</body>
+</html>"#,
+        escape(title)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmarks::Bookmark;
+    use std::time::SystemTime;
+
+    #[test]
+    fn every_page_has_its_own_url() {
+        for page in Page::ALL {
+            assert_eq!(Page::parse(&page.url()), Some(page));
+            assert!(serve(page.url().as_str()).is_some());
+        }
+        assert_eq!(serve("astrolabe://nope"), None);
+        assert_eq!(Page::NewTab.url().as_str(), "astrolabe://newtab");
+        let search = Url::parse("astrolabe://history?q=rust").unwrap();
+        assert_eq!(Page::parse(&search), Some(Page::History));
+        assert_eq!(Page::parse(&Url::parse("astrolabe://nope").unwrap()), None);
+        assert_eq!(Page::parse(&Url::parse("https://history/").unwrap()), None);
+    }
+
+    #[test]
+    fn bookmarks_page_escapes_and_skips_script_links() {
+        let bookmark = |title: &str, url: &str| {
+            Node::Bookmark(Bookmark {
+                id: 1,
+                title: title.to_string(),
+                url: url.to_string(),
+                tags: Vec::new(),
+                added: SystemTime::UNIX_EPOCH,
+            })
+        };
+        let root = Folder {
+            id: 0,
+            title: String::new(),
+            children: vec![
+                bookmark("<b>Docs</b>", "https://example.com/?a=1&b=2"),
+                Node::Folder(Folder {
+                    id: 2,
+                    title: String::from("Tools"),
+                    children: vec![bookmark("Run", "javascript:alert(1)")],
+                }),
+            ],
+        };
+
+        let html = bookmark_list(&root, &BTreeMap::new());
+
+        assert!(html.contains("&lt;b&gt;Docs&lt;/b&gt;"));
+        assert!(html.contains("href=\"https://example.com/?a=1&amp;b=2\""));
+        assert!(html.contains("<h2>Tools</h2>"));
+        assert!(html.contains("href=\"\""));
+        assert!(!html.contains("href=\"javascript:"));
+    }
+}
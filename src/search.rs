@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: MIT
+
+//! Search engines the address bar and start page search with.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use url::{form_urlencoded, Url};
+
+/// Stands in for the query in engine url templates.
+const QUERY_PLACEHOLDER: &str = "%s";
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SearchEngine {
+    pub name: String,
+    /// Results page, with `%s` where the query goes.
+    pub url: String,
+    /// Endpoint answering with query completions, with `%s` where the query goes.
+    pub suggest_url: Option<String>,
+    /// Typed before a query to search with this engine instead of the default, e.g. `w rust`.
+    pub keyword: Option<String>,
+}
+
+impl SearchEngine {
+    pub fn new(name: &str, url: &str, suggest_url: Option<&str>, keyword: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            suggest_url: suggest_url.map(String::from),
+            keyword: keyword.map(String::from),
+        }
+    }
+
+    /// The results page for `query`, `None` when the template is not a url.
+    pub fn search_url(&self, query: &str) -> Option<Url> {
+        let query: String = form_urlencoded::byte_serialize(query.as_bytes()).collect();
+        Url::parse(&self.url.replace(QUERY_PLACEHOLDER, &query)).ok()
+    }
+
+    /// Reads an [OpenSearch description](https://github.com/dewitt/opensearch) document.
+    pub fn from_opensearch(xml: &str) -> Result<Self, OpenSearchError> {
+        let name = element_text(xml, "ShortName").ok_or(OpenSearchError::MissingName)?;
+        let url = url_template(xml, "text/html").ok_or(OpenSearchError::MissingUrl)?;
+        Ok(Self {
+            name,
+            url,
+            suggest_url: url_template(xml, "application/x-suggestions+json"),
+            keyword: None,
+        })
+    }
+
+    /// Reads the OpenSearch description saved at `path`.
+    pub fn import_opensearch(path: &Path) -> Result<Self, OpenSearchError> {
+        let xml =
+            std::fs::read_to_string(path).map_err(|err| OpenSearchError::Io(err.to_string()))?;
+        Self::from_opensearch(&xml)
+    }
+}
+
+/// Why an OpenSearch description could not be imported.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OpenSearchError {
+    MissingName,
+    /// There is no `text/html` url taking `{searchTerms}`.
+    MissingUrl,
+    Io(String),
+}
+
+impl fmt::Display for OpenSearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenSearchError::MissingName => write!(f, "search engine has no ShortName"),
+            OpenSearchError::MissingUrl => write!(f, "search engine has no results url"),
+            OpenSearchError::Io(reason) => write!(f, "failed to read search engine: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for OpenSearchError {}
+
+/// The engines a fresh install starts with, the first one searches by default.
+pub fn default_engines() -> Vec<SearchEngine> {
+    vec![
+        SearchEngine::new(
+            "Google",
+            "https://www.google.com/search?q=%s",
+            Some("https://suggestqueries.google.com/complete/search?client=firefox&q=%s"),
+            Some("g"),
+        ),
+        SearchEngine::new(
+            "DuckDuckGo",
+            "https://duckduckgo.com/?q=%s",
+            Some("https://duckduckgo.com/ac/?type=list&q=%s"),
+            Some("d"),
+        ),
+        SearchEngine::new(
+            "Wikipedia",
+            "https://en.wikipedia.org/wiki/Special:Search?search=%s",
+            Some("https://en.wikipedia.org/w/api.php?action=opensearch&search=%s"),
+            Some("w"),
+        ),
+    ]
+}
+
+/// The configured engines and the one searching by default.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchEngines<'a> {
+    engines: &'a [SearchEngine],
+    default: &'a str,
+}
+
+impl<'a> SearchEngines<'a> {
+    pub fn new(engines: &'a [SearchEngine], default: &'a str) -> Self {
+        Self { engines, default }
+    }
+
+    pub fn engines(&self) -> &'a [SearchEngine] {
+        self.engines
+    }
+
+    /// The engine named as default, or the first one if it is gone.
+    pub fn default_engine(&self) -> Option<&'a SearchEngine> {
+        self.engines
+            .iter()
+            .find(|engine| engine.name == self.default)
+            .or_else(|| self.engines.first())
+    }
+
+    pub fn by_keyword(&self, keyword: &str) -> Option<&'a SearchEngine> {
+        self.engines
+            .iter()
+            .find(|engine| engine.keyword.as_deref() == Some(keyword))
+    }
+
+    /// Splits `w rust` into the engine with keyword `w` and the query `rust`.
+    pub fn keyword_search<'t>(&self, text: &'t str) -> Option<(&'a SearchEngine, &'t str)> {
+        let (keyword, query) = text.trim_start().split_once(char::is_whitespace)?;
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        self.by_keyword(keyword).map(|engine| (engine, query))
+    }
+}
+
+/// Text of the first `<tag>` element.
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    let text = unescape(xml[start..end].trim());
+    (!text.is_empty()).then_some(text)
+}
+
+/// Template of the first `<Url>` of `mime` type, converted to a `%s` template.
+fn url_template(xml: &str, mime: &str) -> Option<String> {
+    xml.split("<Url")
+        .skip(1)
+        .filter_map(|element| element.split_once('>').map(|(attributes, _)| attributes))
+        .filter(|attributes| attribute(attributes, "type").as_deref() == Some(mime))
+        .find_map(|attributes| attribute(attributes, "template"))
+        .filter(|template| template.contains("{searchTerms}"))
+        .map(|template| {
+            let template = template.replace("{searchTerms}", QUERY_PLACEHOLDER);
+            strip_optional_parameters(&template)
+        })
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ['"', '\''].iter().find_map(|quote| {
+        let start = attributes.find(&format!("{name}={quote}"))? + name.len() + 2;
+        let end = start + attributes[start..].find(*quote)?;
+        Some(unescape(&attributes[start..end]))
+    })
+}
+
+/// Drops parameters like `page={startPage?}` that only apply to paged requests.
+fn strip_optional_parameters(template: &str) -> String {
+    let Some((base, query)) = template.split_once('?') else {
+        return template.to_string();
+    };
+    let query: Vec<&str> = query
+        .split('&')
+        .filter(|parameter| !parameter.contains('{'))
+        .collect();
+    format!("{base}?{}", query.join("&"))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_is_encoded_into_template() {
+        let engine = &default_engines()[2];
+
+        assert_eq!(
+            engine.search_url("rust & c++").map(String::from),
+            Some(String::from(
+                "https://en.wikipedia.org/wiki/Special:Search?search=rust+%26+c%2B%2B"
+            ))
+        );
+    }
+
+    #[test]
+    fn keyword_picks_engine() {
+        let engines = default_engines();
+        let registry = SearchEngines::new(&engines, "Google");
+
+        let (engine, query) = registry.keyword_search("w  rust lang ").unwrap();
+        assert_eq!(engine.name, "Wikipedia");
+        assert_eq!(query, "rust lang");
+        assert!(registry.keyword_search("w ").is_none());
+        assert!(registry.keyword_search("x rust").is_none());
+        assert!(registry.keyword_search("w").is_none());
+    }
+
+    #[test]
+    fn missing_default_falls_back_to_first_engine() {
+        let engines = default_engines();
+
+        assert_eq!(
+            SearchEngines::new(&engines, "DuckDuckGo").default_engine(),
+            Some(&engines[1])
+        );
+        assert_eq!(
+            SearchEngines::new(&engines, "Removed").default_engine(),
+            Some(&engines[0])
+        );
+        assert_eq!(SearchEngines::new(&[], "Google").default_engine(), None);
+    }
+
+    #[test]
+    fn opensearch_description_is_imported() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Crates &amp; Docs</ShortName>
+  <Description>Search crates.io</Description>
+  <Url type="application/x-suggestions+json" template="https://crates.io/suggest?q={searchTerms}"/>
+  <Url type="text/html" method="get"
+       template="https://crates.io/search?q={searchTerms}&amp;page={startPage?}"/>
+</OpenSearchDescription>"#;
+
+        assert_eq!(
+            SearchEngine::from_opensearch(xml),
+            Ok(SearchEngine::new(
+                "Crates & Docs",
+                "https://crates.io/search?q=%s",
+                Some("https://crates.io/suggest?q=%s"),
+                None,
+            ))
+        );
+    }
+
+    #[test]
+    fn opensearch_without_results_url_is_rejected() {
+        let xml = "<OpenSearchDescription><ShortName>Empty</ShortName></OpenSearchDescription>";
+
+        assert_eq!(
+            SearchEngine::from_opensearch(xml),
+            Err(OpenSearchError::MissingUrl)
+        );
+    }
+}
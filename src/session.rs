@@ -0,0 +1,265 @@
+// SPDX-License-Identifier: MIT
+
+//! The open windows, their tabs and where each has been, saved to pick up where the last run
+//! left off.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::history::Transition;
+use crate::storage;
+
+/// Most pages a tab remembers, the oldest are forgotten first.
+const MAX_ENTRIES: usize = 50;
+
+/// Most closed tabs kept to reopen, the oldest are forgotten first.
+const MAX_CLOSED_TABS: usize = 25;
+
+/// Every browser window, the main one first.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub windows: Vec<Window>,
+}
+
+/// Every tab of a window in nav bar order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Window {
+    pub tabs: Vec<Tab>,
+    /// Position of the active tab in `tabs`.
+    pub active: usize,
+    /// Where the window was, unknown until the window system reported it.
+    #[serde(default)]
+    pub geometry: Option<Geometry>,
+}
+
+/// The place of a window on screen, in logical pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    /// Top left corner, which not every window system tells.
+    pub position: Option<(f32, f32)>,
+    pub size: (f32, f32),
+}
+
+/// The pages one tab went through, which back and forward move between.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tab {
+    pub entries: Vec<Entry>,
+    /// Position of the page shown in `entries`.
+    pub index: usize,
+    /// Kept compact at the top of the nav bar.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub url: String,
+    pub title: String,
+    /// How far the page was scrolled, only kept for the page shown.
+    #[serde(default)]
+    pub scroll: (f64, f64),
+}
+
+/// A closed tab, kept to reopen it where it was.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClosedTab {
+    pub tab: Tab,
+    /// Position it had in the nav bar.
+    pub position: usize,
+}
+
+/// Recently closed tabs, the most recently closed first.
+#[derive(Clone, Debug, Default)]
+pub struct ClosedTabs {
+    tabs: VecDeque<ClosedTab>,
+}
+
+impl ClosedTabs {
+    pub fn push(&mut self, closed: ClosedTab) {
+        self.tabs.push_front(closed);
+        self.tabs.truncate(MAX_CLOSED_TABS);
+    }
+
+    /// Takes the tab `index` places down the stack out of it to reopen it.
+    pub fn take(&mut self, index: usize) -> Option<ClosedTab> {
+        self.tabs.remove(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ClosedTab> {
+        self.tabs.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+}
+
+impl Session {
+    /// Loads the session saved at `path`, empty if there is none.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        storage::write_atomically(path, &serde_json::to_string(self)?)
+    }
+}
+
+impl Tab {
+    /// The page shown.
+    pub fn current(&self) -> Option<&Entry> {
+        self.entries.get(self.index)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut Entry> {
+        self.entries.get_mut(self.index)
+    }
+
+    /// The page `offset` steps back (negative) or forward from the one shown.
+    pub fn entry_at(&self, offset: isize) -> Option<&Entry> {
+        self.entries.get(self.index.checked_add_signed(offset)?)
+    }
+
+    /// Follows the tab to `url`, reached by `transition`.
+    ///
+    /// Going back or forward moves to the neighbouring entry of that url, anything else
+    /// drops the pages ahead and adds `url` after the one shown.
+    pub fn navigate(&mut self, url: &str, transition: Transition) {
+        if self.current().is_some_and(|entry| entry.url == url) {
+            return;
+        }
+        if transition == Transition::BackForward {
+            for offset in [-1, 1] {
+                if self.entry_at(offset).is_some_and(|entry| entry.url == url) {
+                    self.index = self.index.saturating_add_signed(offset);
+                    return;
+                }
+            }
+        }
+        if transition == Transition::Reload {
+            if let Some(entry) = self.current_mut() {
+                entry.url = url.to_string();
+                return;
+            }
+        }
+
+        self.entries.truncate(self.index + 1);
+        self.entries.push(Entry {
+            url: url.to_string(),
+            title: String::new(),
+            scroll: (0.0, 0.0),
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.index = self.entries.len() - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(tab: &Tab) -> Vec<&str> {
+        tab.entries.iter().map(|entry| entry.url.as_str()).collect()
+    }
+
+    #[test]
+    fn navigating_drops_pages_ahead() {
+        let mut tab = Tab::default();
+        tab.navigate("https://example.com/", Transition::Typed);
+        tab.navigate("https://example.com/a", Transition::Link);
+        tab.navigate("https://example.com/b", Transition::Link);
+
+        tab.navigate("https://example.com/a", Transition::BackForward);
+        assert_eq!(tab.index, 1);
+        assert_eq!(tab.entry_at(1).unwrap().url, "https://example.com/b");
+
+        tab.navigate("https://example.com/c", Transition::Link);
+        assert_eq!(
+            urls(&tab),
+            [
+                "https://example.com/",
+                "https://example.com/a",
+                "https://example.com/c"
+            ]
+        );
+        assert_eq!(tab.index, 2);
+        assert!(tab.entry_at(1).is_none());
+    }
+
+    #[test]
+    fn reload_and_same_url_keep_entry() {
+        let mut tab = Tab::default();
+        tab.navigate("https://example.com/", Transition::Typed);
+        tab.navigate("https://example.com/", Transition::Link);
+        tab.navigate("https://example.com/#top", Transition::Reload);
+
+        assert_eq!(urls(&tab), ["https://example.com/#top"]);
+    }
+
+    #[test]
+    fn oldest_entries_are_forgotten() {
+        let mut tab = Tab::default();
+        for page in 0..=MAX_ENTRIES {
+            tab.navigate(&format!("https://example.com/{page}"), Transition::Link);
+        }
+
+        assert_eq!(tab.entries.len(), MAX_ENTRIES);
+        assert_eq!(tab.entries[0].url, "https://example.com/1");
+        assert_eq!(tab.index, MAX_ENTRIES - 1);
+    }
+
+    #[test]
+    fn closed_tabs_are_bounded_and_most_recent_first() {
+        let mut closed = ClosedTabs::default();
+        for position in 0..=MAX_CLOSED_TABS {
+            closed.push(ClosedTab {
+                tab: Tab::default(),
+                position,
+            });
+        }
+
+        assert_eq!(closed.iter().count(), MAX_CLOSED_TABS);
+        assert_eq!(closed.take(0).unwrap().position, MAX_CLOSED_TABS);
+        assert_eq!(closed.iter().last().unwrap().position, 1);
+    }
+
+    #[test]
+    fn session_survives_reopening() {
+        let path = std::env::temp_dir()
+            .join(format!("astrolabe-session-{}", std::process::id()))
+            .join("session.json");
+        let mut tab = Tab::default();
+        tab.navigate("https://example.com/", Transition::Typed);
+        tab.current_mut().unwrap().scroll = (0.0, 120.0);
+        tab.pinned = true;
+        let session = Session {
+            windows: vec![
+                Window {
+                    tabs: vec![Tab::default(), tab],
+                    active: 1,
+                    geometry: Some(Geometry {
+                        position: None,
+                        size: (1024.0, 768.0),
+                    }),
+                },
+                Window::default(),
+            ],
+        };
+
+        assert_eq!(Session::open(&path).unwrap(), Session::default());
+        session.save(&path).unwrap();
+        let reopened = Session::open(&path).unwrap();
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(reopened, session);
+    }
+}
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: MIT
+
+//! Keyboard shortcuts: the chords each browser action starts with, the user's changes to
+//! them, and which chords the browser keeps from the page.
+//!
+//! Precedence, from first to last:
+//!
+//! 1. A focused browser widget, like the address bar, gets the keys it handles itself.
+//! 2. A chord bound to an action and reserved, see [`is_reserved`], runs the action and never
+//!    reaches the page.
+//! 3. Everything else goes to the page.
+//!
+//! The user's chords replace the defaults of the action they are set for, and take the chord
+//! from any action that had it by default.
+
+use cosmic::iced::keyboard::{key::Named, Key, Modifiers};
+use cosmic::widget::menu::key_bind::{KeyBind, Modifier};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::app::MenuAction;
+
+const SELECT_TAB_CHORDS: [&str; 8] = [
+    "Ctrl+1", "Ctrl+2", "Ctrl+3", "Ctrl+4", "Ctrl+5", "Ctrl+6", "Ctrl+7", "Ctrl+8",
+];
+
+/// Keys named in chords besides characters and `F1` to `F12`.
+const NAMED_KEYS: [(&str, Named); 14] = [
+    ("Tab", Named::Tab),
+    ("Left", Named::ArrowLeft),
+    ("Right", Named::ArrowRight),
+    ("Up", Named::ArrowUp),
+    ("Down", Named::ArrowDown),
+    ("Home", Named::Home),
+    ("End", Named::End),
+    ("PageUp", Named::PageUp),
+    ("PageDown", Named::PageDown),
+    ("Escape", Named::Escape),
+    ("Enter", Named::Enter),
+    ("Space", Named::Space),
+    ("Backspace", Named::Backspace),
+    ("Delete", Named::Delete),
+];
+
+const FUNCTION_KEYS: [Named; 12] = [
+    Named::F1,
+    Named::F2,
+    Named::F3,
+    Named::F4,
+    Named::F5,
+    Named::F6,
+    Named::F7,
+    Named::F8,
+    Named::F9,
+    Named::F10,
+    Named::F11,
+    Named::F12,
+];
+
+/// Every action a shortcut can run, in the order the editor lists them, with its default
+/// chords.
+fn table() -> Vec<(MenuAction, &'static [&'static str])> {
+    let mut table: Vec<(MenuAction, &'static [&'static str])> = vec![
+        (MenuAction::NewTab, &["Ctrl+T"]),
+        (MenuAction::NewWindow, &["Ctrl+N"]),
+        (MenuAction::NewPrivateWindow, &["Ctrl+Shift+N"]),
+        (MenuAction::CloseTab, &["Ctrl+W", "Ctrl+F4"]),
+        (MenuAction::ReopenClosedTab(0), &["Ctrl+Shift+T"]),
+        (MenuAction::NextTab, &["Ctrl+Tab", "Ctrl+PageDown"]),
+        (MenuAction::PreviousTab, &["Ctrl+Shift+Tab", "Ctrl+PageUp"]),
+    ];
+    for (index, chord) in SELECT_TAB_CHORDS.iter().enumerate() {
+        table.push((MenuAction::SelectTab(index), std::slice::from_ref(chord)));
+    }
+    table.extend([
+        (MenuAction::LastTab, &["Ctrl+9"][..]),
+        (MenuAction::FocusAddressBar, &["Ctrl+L", "Alt+D", "F6"]),
+        (MenuAction::Back, &["Alt+Left"]),
+        (MenuAction::Forward, &["Alt+Right"]),
+        (MenuAction::Reload, &["Ctrl+R", "F5"]),
+        (MenuAction::Find, &["Ctrl+F"]),
+        (MenuAction::FindNext, &["Ctrl+G", "F3"]),
+        (MenuAction::FindPrevious, &["Ctrl+Shift+G", "Shift+F3"]),
+        (MenuAction::BookmarkPage, &["Ctrl+D"]),
+        (MenuAction::Bookmarks, &["Ctrl+Shift+O"]),
+        (MenuAction::BookmarksBar, &[]),
+        (MenuAction::History, &["Ctrl+H"]),
+        (MenuAction::Console, &["Ctrl+Shift+J"]),
+        (MenuAction::Inspect, &["Ctrl+Shift+I"]),
+        (MenuAction::SearchEngines, &[]),
+        (MenuAction::Settings, &[]),
+        (MenuAction::Shortcuts, &[]),
+        (MenuAction::About, &[]),
+    ]);
+    table
+}
+
+/// Every action a shortcut can run, in the order the editor lists them.
+pub fn actions() -> Vec<MenuAction> {
+    table().into_iter().map(|(action, _)| action).collect()
+}
+
+/// The name the config knows `action` by.
+pub fn name(action: MenuAction) -> String {
+    match action {
+        MenuAction::About => String::from("about"),
+        MenuAction::Back => String::from("back"),
+        MenuAction::BookmarkPage => String::from("bookmark-page"),
+        MenuAction::Bookmarks => String::from("bookmarks"),
+        MenuAction::BookmarksBar => String::from("bookmarks-bar"),
+        MenuAction::CloseTab => String::from("close-tab"),
+        MenuAction::Console => String::from("console"),
+        MenuAction::Find => String::from("find"),
+        MenuAction::FindNext => String::from("find-next"),
+        MenuAction::FindPrevious => String::from("find-previous"),
+        MenuAction::FocusAddressBar => String::from("focus-address-bar"),
+        MenuAction::Forward => String::from("forward"),
+        MenuAction::History => String::from("history"),
+        MenuAction::Inspect => String::from("inspect"),
+        MenuAction::LastTab => String::from("last-tab"),
+        MenuAction::NewPrivateWindow => String::from("new-private-window"),
+        MenuAction::NewTab => String::from("new-tab"),
+        MenuAction::NewWindow => String::from("new-window"),
+        MenuAction::NextTab => String::from("next-tab"),
+        MenuAction::PreviousTab => String::from("previous-tab"),
+        MenuAction::Reload => String::from("reload"),
+        MenuAction::ReopenClosedTab(_) => String::from("reopen-closed-tab"),
+        MenuAction::SearchEngines => String::from("search-engines"),
+        MenuAction::SelectTab(index) => format!("select-tab-{}", index + 1),
+        MenuAction::Settings => String::from("settings"),
+        MenuAction::Shortcuts => String::from("shortcuts"),
+    }
+}
+
+/// The chords of every action, the defaults overridden by the user's `overrides`.
+///
+/// Overrides are chord lists by action name, see [`parse_list`], an empty list unbinds the
+/// action. Overrides that can't be read keep the defaults.
+pub fn key_binds(overrides: &BTreeMap<String, String>) -> HashMap<KeyBind, MenuAction> {
+    let mut key_binds = HashMap::new();
+    let mut overridden = Vec::new();
+    for (action, defaults) in table() {
+        match overrides
+            .get(&name(action))
+            .and_then(|text| parse_list(text))
+        {
+            Some(chords) => overridden.push((action, chords)),
+            None => {
+                for chord in defaults {
+                    let bind = parse(chord).expect("default chords are valid");
+                    key_binds.insert(bind, action);
+                }
+            }
+        }
+    }
+    // inserted last, so the user's chords win over the defaults
+    for (action, chords) in overridden {
+        for bind in chords {
+            key_binds.insert(bind, action);
+        }
+    }
+    key_binds
+}
+
+/// The chords bound to `action`, in the order they are shown.
+pub fn chords(key_binds: &HashMap<KeyBind, MenuAction>, action: MenuAction) -> Vec<String> {
+    let mut chords: Vec<String> = key_binds
+        .iter()
+        .filter(|(_, bound)| **bound == action)
+        .map(|(bind, _)| format(bind))
+        .collect();
+    // the shortest is usually the one people know
+    chords.sort_by_key(|chord| (chord.len(), chord.clone()));
+    chords
+}
+
+/// Whether the browser keeps `bind` from the page when it is bound to an action.
+///
+/// These are chords with Ctrl, Alt or Super, and function keys. Other keys are typing or
+/// page navigation, which the page always gets.
+pub fn is_reserved(bind: &KeyBind) -> bool {
+    let function_key = matches!(&bind.key, Key::Named(named) if FUNCTION_KEYS.contains(named));
+    function_key
+        || bind
+            .modifiers
+            .iter()
+            .any(|modifier| matches!(modifier, Modifier::Ctrl | Modifier::Alt | Modifier::Super))
+}
+
+/// The action a key press runs instead of reaching the page, if any.
+pub fn reserved_action(
+    key_binds: &HashMap<KeyBind, MenuAction>,
+    modifiers: Modifiers,
+    key: &Key,
+) -> Option<MenuAction> {
+    key_binds
+        .iter()
+        .find(|(bind, _)| is_reserved(bind) && bind.matches(modifiers, key))
+        .map(|(_, action)| *action)
+}
+
+/// Reads a chord like `Ctrl+Shift+T`, `Alt+Left` or `F5`, ignoring case.
+pub fn parse(text: &str) -> Option<KeyBind> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    // `Ctrl++` binds the plus key
+    if text.trim_end().ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
+    }
+    let (key, modifiers) = parts.split_last()?;
+
+    let mut bind_modifiers = Vec::new();
+    for modifier in modifiers {
+        let modifier = match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Modifier::Ctrl,
+            "shift" => Modifier::Shift,
+            "alt" => Modifier::Alt,
+            "super" | "logo" => Modifier::Super,
+            _ => return None,
+        };
+        if !bind_modifiers.contains(&modifier) {
+            bind_modifiers.push(modifier);
+        }
+    }
+
+    let key = if let Some((_, named)) = NAMED_KEYS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+    {
+        Key::Named(*named)
+    } else if let Some(number) = key
+        .strip_prefix(['F', 'f'])
+        .and_then(|number| number.parse::<usize>().ok())
+    {
+        Key::Named(*FUNCTION_KEYS.get(number.checked_sub(1)?)?)
+    } else {
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Key::Character(c.to_lowercase().to_string().into()),
+            _ => return None,
+        }
+    };
+
+    Some(KeyBind {
+        modifiers: bind_modifiers,
+        key,
+    })
+}
+
+/// Reads chords separated by commas, `None` if any can't be read.
+pub fn parse_list(text: &str) -> Option<Vec<KeyBind>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|chord| !chord.is_empty())
+        .map(parse)
+        .collect()
+}
+
+/// Writes `bind` the way [`parse`] reads it.
+pub fn format(bind: &KeyBind) -> String {
+    let mut parts = Vec::new();
+    for (modifier, name) in [
+        (Modifier::Super, "Super"),
+        (Modifier::Ctrl, "Ctrl"),
+        (Modifier::Alt, "Alt"),
+        (Modifier::Shift, "Shift"),
+    ] {
+        if bind.modifiers.contains(&modifier) {
+            parts.push(String::from(name));
+        }
+    }
+    let key = match &bind.key {
+        Key::Character(c) => c.to_uppercase(),
+        Key::Named(named) => match NAMED_KEYS.iter().find(|(_, known)| known == named) {
+            Some((name, _)) => String::from(*name),
+            None => match FUNCTION_KEYS.iter().position(|key| key == named) {
+                Some(index) => format!("F{}", index + 1),
+                None => format!("{named:?}"),
+            },
+        },
+        Key::Unidentified => String::from("?"),
+    };
+    parts.push(key);
+    parts.join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chords_round_trip() {
+        for chord in [
+            "Ctrl+Shift+T",
+            "Alt+Left",
+            "F5",
+            "Shift+F3",
+            "Ctrl+PageDown",
+            "Ctrl++",
+        ] {
+            assert_eq!(format(&parse(chord).unwrap()), chord);
+        }
+        assert_eq!(format(&parse("shift + ctrl + t").unwrap()), "Ctrl+Shift+T");
+        assert!(parse("Hyper+T").is_none());
+        assert!(parse("Ctrl+F13").is_none());
+        assert!(parse("Ctrl+TT").is_none());
+        assert!(parse_list("Ctrl+W, Nope+W").is_none());
+        assert_eq!(parse_list("").unwrap(), []);
+    }
+
+    #[test]
+    fn every_default_chord_is_reserved_and_unique() {
+        let mut seen = Vec::new();
+        for (action, chords) in table() {
+            for chord in chords {
+                let bind = parse(chord).unwrap();
+                assert!(
+                    is_reserved(&bind),
+                    "{chord} of {action:?} would reach the page"
+                );
+                assert!(!seen.contains(&bind), "{chord} is bound twice");
+                seen.push(bind);
+            }
+        }
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_take_their_chords() {
+        let overrides = BTreeMap::from([
+            (String::from("new-tab"), String::from("Ctrl+N")),
+            (String::from("history"), String::from("Ctrl+T")),
+            (String::from("find"), String::new()),
+        ]);
+
+        let key_binds = key_binds(&overrides);
+
+        assert_eq!(chords(&key_binds, MenuAction::NewTab), ["Ctrl+N"]);
+        assert_eq!(chords(&key_binds, MenuAction::History), ["Ctrl+T"]);
+        assert!(chords(&key_binds, MenuAction::Find).is_empty());
+        assert_eq!(chords(&key_binds, MenuAction::Reload), ["F5", "Ctrl+R"]);
+    }
+
+    #[test]
+    fn only_reserved_chords_are_kept_from_the_page() {
+        let overrides = BTreeMap::from([(String::from("find"), String::from("Ctrl+F, /"))]);
+        let key_binds = key_binds(&overrides);
+        let ctrl = Modifiers::CTRL;
+
+        assert_eq!(
+            reserved_action(&key_binds, ctrl, &Key::Character("w".into())),
+            Some(MenuAction::CloseTab)
+        );
+        assert_eq!(
+            reserved_action(
+                &key_binds,
+                ctrl | Modifiers::SHIFT,
+                &Key::Character("T".into())
+            ),
+            Some(MenuAction::ReopenClosedTab(0))
+        );
+        assert_eq!(
+            reserved_action(&key_binds, Modifiers::empty(), &Key::Named(Named::F5)),
+            Some(MenuAction::Reload)
+        );
+        // bound, but typing it in the page must keep working
+        assert_eq!(
+            reserved_action(&key_binds, Modifiers::empty(), &Key::Character("/".into())),
+            None
+        );
+    }
+}
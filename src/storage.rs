@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+
+//! Files the browser keeps under the XDG data directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory name under the data directory, the app id.
+const APP_DIR: &str = "com.bancedev.astrolabe";
+
+/// The directory the browser's files are kept in, `None` when there is no data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR))
+}
+
+/// Replaces `path` with `contents` through a temporary file, so a crash can't truncate it.
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(temporary, path)
+}
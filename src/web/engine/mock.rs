@@ -0,0 +1,496 @@
+//! In-memory [`Engine`] for exercising `WebView` and `AppModel` without Ultralight.
+
+use cosmic::iced::keyboard;
+use cosmic::iced::mouse::{self, Interaction};
+use cosmic::iced::{Point, Size};
+use std::collections::HashMap;
+
+use super::{
+    is_app_url, BridgeFunction, ConsoleLog, ConsoleMessage, Engine, EngineError, EngineEvent,
+    PageType, PixelFormat, SchemeHandler, ViewEvent, ViewId,
+};
+use crate::web::{ImageInfo, Region};
+
+/// A fake view with a scripted url, title and history.
+pub struct MockView {
+    pub id: ViewId,
+    pub url: String,
+    pub title: String,
+    pub size: Size<u32>,
+    /// Physical pixels per logical one, see [`Engine::set_scale`].
+    pub scale: f64,
+    pub cursor: Interaction,
+    /// Set for views opened by [`Engine::open_inspector`].
+    pub inspector: bool,
+    /// Set between [`MockEngine::begin_loading`] and the load finishing or being stopped.
+    pub loading: bool,
+    /// The html the page was made from, for generated pages and those of registered schemes.
+    pub html: Option<String>,
+    frame: ImageInfo,
+    needs_paint: bool,
+    history: Vec<PageType>,
+    history_index: usize,
+    trusted: bool,
+    console: ConsoleLog,
+}
+
+impl MockView {
+    fn new(id: ViewId, size: Size<u32>) -> Self {
+        Self {
+            id,
+            url: String::new(),
+            title: String::new(),
+            size,
+            scale: 1.0,
+            cursor: Interaction::Idle,
+            inspector: false,
+            loading: false,
+            html: None,
+            frame: ImageInfo::blank(size.width, size.height),
+            needs_paint: true,
+            history: Vec::new(),
+            history_index: 0,
+            trusted: false,
+            console: ConsoleLog::default(),
+        }
+    }
+
+    /// Loads instantly, emitting the events Ultralight would for a main frame load.
+    fn load(&mut self, page_type: &PageType, schemes: &Schemes, events: &mut Vec<EngineEvent>) {
+        let (url, html) = match page_type {
+            PageType::Url(url) => (url.clone(), scheme_page(schemes, url)),
+            PageType::Html(html) => (String::from("about:blank"), Some(html.clone())),
+        };
+        let title = html.as_deref().map_or_else(|| url.clone(), html_title);
+        self.trusted = match page_type {
+            PageType::Url(url) => is_app_url(url),
+            PageType::Html(_) => true,
+        };
+        events.push((self.id, ViewEvent::BeginLoading { url: url.clone() }));
+        events.push((self.id, ViewEvent::UrlChanged(url.clone())));
+        events.push((self.id, ViewEvent::TitleChanged(title.clone())));
+        events.push((self.id, ViewEvent::DomReady));
+        events.push((self.id, ViewEvent::FinishLoading));
+        self.url = url;
+        self.title = title;
+        self.html = html;
+        self.loading = false;
+        self.needs_paint = true;
+    }
+
+    fn navigate(&mut self, page_type: PageType, schemes: &Schemes, events: &mut Vec<EngineEvent>) {
+        self.load(&page_type, schemes, events);
+        self.history.truncate(self.history_index + 1);
+        self.history.push(page_type);
+        self.history_index = self.history.len() - 1;
+        events.push((self.id, ViewEvent::HistoryUpdated));
+    }
+}
+
+/// Records everything the [`WebView`](crate::web::WebView) asks of it.
+#[derive(Default)]
+pub struct MockEngine {
+    views: Vec<MockView>,
+    next_id: u64,
+    navigations: Vec<(ViewId, PageType)>,
+    renders: Vec<ViewId>,
+    failing_urls: Vec<String>,
+    events: Vec<EngineEvent>,
+    script_results: HashMap<String, Result<String, String>>,
+    scripts: Vec<(ViewId, String)>,
+    bridge_functions: HashMap<String, BridgeFunction>,
+    schemes: Schemes,
+}
+
+type Schemes = HashMap<String, SchemeHandler>;
+
+impl MockEngine {
+    /// Ids of the live views in creation order.
+    pub fn view_ids(&self) -> Vec<ViewId> {
+        self.views.iter().map(|view| view.id).collect()
+    }
+
+    pub fn mock_view(&self, id: ViewId) -> Option<&MockView> {
+        self.views.iter().find(|view| view.id == id)
+    }
+
+    /// Every page load requested, in order.
+    pub fn navigations(&self) -> &[(ViewId, PageType)] {
+        &self.navigations
+    }
+
+    /// Every view that was rendered, in order.
+    pub fn renders(&self) -> &[ViewId] {
+        &self.renders
+    }
+
+    /// Pretend the page changed its url, e.g. through a redirect.
+    pub fn set_url(&mut self, id: ViewId, url: impl Into<String>) {
+        if let Ok(view) = self.find_view_mut(id) {
+            view.url = url.into();
+            let event = ViewEvent::UrlChanged(view.url.clone());
+            self.events.push((id, event));
+        }
+    }
+
+    /// Pretend the page changed its `document.title`.
+    pub fn set_title(&mut self, id: ViewId, title: impl Into<String>) {
+        if let Ok(view) = self.find_view_mut(id) {
+            view.title = title.into();
+            let event = ViewEvent::TitleChanged(view.title.clone());
+            self.events.push((id, event));
+        }
+    }
+
+    /// Pretend the page started a slow load of `url` that is still in progress.
+    pub fn begin_loading(&mut self, id: ViewId, url: impl Into<String>) {
+        if let Ok(view) = self.find_view_mut(id) {
+            let url = url.into();
+            view.loading = true;
+            view.trusted = is_app_url(&url);
+            let event = ViewEvent::BeginLoading { url };
+            self.events.push((id, event));
+        }
+    }
+
+    /// Make every later load of `url` fail, like an unreachable host would.
+    pub fn fail_url(&mut self, url: impl Into<String>) {
+        self.failing_urls.push(url.into());
+    }
+
+    /// Answer `source` with `result`, either JSON or an exception message.
+    pub fn set_script_result(&mut self, source: impl Into<String>, result: Result<&str, &str>) {
+        let result = result.map(String::from).map_err(String::from);
+        self.script_results.insert(source.into(), result);
+    }
+
+    /// Every script evaluated, in order.
+    pub fn scripts(&self) -> &[(ViewId, String)] {
+        &self.scripts
+    }
+
+    /// Pretend the page logged `message`.
+    pub fn log(&mut self, id: ViewId, message: ConsoleMessage) {
+        if let Ok(view) = self.find_view_mut(id) {
+            view.console.push(message);
+        }
+    }
+
+    /// Pretend the page called `window.astrolabe.<name>(args)`.
+    ///
+    /// Returns `None` when the page may not see the bridge or the function is unknown.
+    pub fn call_bridge(&mut self, id: ViewId, name: &str, args: &[&str]) -> Option<String> {
+        if !self.mock_view(id)?.trusted {
+            return None;
+        }
+        let function = self.bridge_functions.get(name)?;
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let result = function(&args);
+        self.events.push((
+            id,
+            ViewEvent::BridgeCall {
+                name: name.to_string(),
+                args,
+            },
+        ));
+        Some(result)
+    }
+
+    /// The view a frame was rendered from, see [`MockEngine::request_render`].
+    pub fn frame_owner(frame: &ImageInfo) -> Option<ViewId> {
+        frame.pixels.first().map(|&byte| ViewId(u64::from(byte)))
+    }
+
+    fn find_view(&self, id: ViewId) -> Result<&MockView, EngineError> {
+        self.mock_view(id).ok_or(EngineError::UnknownView(id))
+    }
+
+    fn find_view_mut(&mut self, id: ViewId) -> Result<&mut MockView, EngineError> {
+        self.views
+            .iter_mut()
+            .find(|view| view.id == id)
+            .ok_or(EngineError::UnknownView(id))
+    }
+
+    fn view_and_events(
+        &mut self,
+        id: ViewId,
+    ) -> Result<(&mut MockView, &Schemes, &mut Vec<EngineEvent>), EngineError> {
+        let view = self
+            .views
+            .iter_mut()
+            .find(|view| view.id == id)
+            .ok_or(EngineError::UnknownView(id))?;
+        Ok((view, &self.schemes, &mut self.events))
+    }
+
+    /// Fails the urls set to fail and those of registered schemes without a page.
+    fn check_load(&self, page_type: &PageType) -> Result<(), EngineError> {
+        let PageType::Url(url) = page_type else {
+            return Ok(());
+        };
+        let reason = if self.failing_urls.contains(url) {
+            "host unreachable"
+        } else if is_registered(&self.schemes, url) && scheme_page(&self.schemes, url).is_none() {
+            "no such page"
+        } else {
+            return Ok(());
+        };
+        Err(EngineError::LoadFailed {
+            url: url.clone(),
+            reason: String::from(reason),
+        })
+    }
+}
+
+impl Engine for MockEngine {
+    fn update(&mut self) {}
+
+    fn take_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn take_errors(&mut self) -> Vec<EngineError> {
+        Vec::new()
+    }
+
+    fn is_busy(&self) -> bool {
+        self.views
+            .iter()
+            .any(|view| view.loading || view.needs_paint)
+    }
+
+    fn render(&mut self) -> Result<(), EngineError> {
+        let changed: Vec<ViewId> = self
+            .views
+            .iter()
+            .filter(|view| view.needs_paint)
+            .map(|view| view.id)
+            .collect();
+        for id in changed {
+            self.request_render(id)?;
+        }
+        Ok(())
+    }
+
+    /// Fills the frame with the low byte of the view id so tests can tell frames apart, in
+    /// physical pixels like Ultralight.
+    fn request_render(&mut self, id: ViewId) -> Result<(), EngineError> {
+        let view = self.find_view_mut(id)?;
+        view.needs_paint = false;
+        let width = (view.size.width as f64 * view.scale).round() as u32;
+        let height = (view.size.height as f64 * view.scale).round() as u32;
+        view.frame.update(
+            &vec![id.0 as u8; width as usize * height as usize * 4],
+            PixelFormat::Rgba,
+            width as usize * 4,
+            width,
+            height,
+            Region::all(width, height),
+        );
+        self.renders.push(id);
+        Ok(())
+    }
+
+    fn new_view(
+        &mut self,
+        size: Size<u32>,
+        content: Option<PageType>,
+    ) -> Result<ViewId, EngineError> {
+        if let Some(page_type) = &content {
+            self.check_load(page_type)?;
+        }
+        let id = ViewId(self.next_id);
+        self.next_id += 1;
+
+        let mut view = MockView::new(id, size);
+        if let Some(page_type) = content {
+            view.navigate(page_type.clone(), &self.schemes, &mut self.events);
+            self.navigations.push((id, page_type));
+        }
+        self.views.push(view);
+        Ok(id)
+    }
+
+    fn remove_view(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.find_view(id)?;
+        self.views.retain(|view| view.id != id);
+        Ok(())
+    }
+
+    fn focus(&mut self) {}
+
+    fn unfocus(&self) {}
+
+    fn resize_view(&mut self, id: ViewId, size: Size<u32>) -> Result<(), EngineError> {
+        let view = self.find_view_mut(id)?;
+        if view.size != size {
+            view.size = size;
+            view.needs_paint = true;
+        }
+        Ok(())
+    }
+
+    fn set_scale(&mut self, id: ViewId, scale: f64) -> Result<(), EngineError> {
+        self.find_view_mut(id).map(|view| {
+            view.scale = scale;
+            view.needs_paint = true;
+        })
+    }
+
+    fn open_inspector(&mut self, id: ViewId, size: Size<u32>) -> Result<ViewId, EngineError> {
+        let inspected = self.find_view(id)?.url.clone();
+        let inspector_id = ViewId(self.next_id);
+        self.next_id += 1;
+
+        let mut view = MockView::new(inspector_id, size);
+        view.url = String::from("inspector://");
+        view.title = format!("Web Inspector - {inspected}");
+        view.inspector = true;
+        self.views.push(view);
+        Ok(inspector_id)
+    }
+
+    fn handle_keyboard_event(
+        &mut self,
+        id: ViewId,
+        _event: keyboard::Event,
+    ) -> Result<(), EngineError> {
+        self.find_view(id).map(|_| ())
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        id: ViewId,
+        _point: Point,
+        event: mouse::Event,
+    ) -> Result<(), EngineError> {
+        match event {
+            mouse::Event::ButtonReleased(mouse::Button::Forward) => self.go_forward(id),
+            mouse::Event::ButtonReleased(mouse::Button::Back) => self.go_back(id),
+            _ => self.find_view(id).map(|_| ()),
+        }
+    }
+
+    fn scroll(&mut self, id: ViewId, _delta: mouse::ScrollDelta) -> Result<(), EngineError> {
+        self.find_view(id).map(|_| ())
+    }
+
+    fn goto(&mut self, id: ViewId, page_type: PageType) -> Result<(), EngineError> {
+        self.check_load(&page_type)?;
+        let (view, schemes, events) = self.view_and_events(id)?;
+        view.navigate(page_type.clone(), schemes, events);
+        self.navigations.push((id, page_type));
+        Ok(())
+    }
+
+    fn refresh(&mut self, id: ViewId) -> Result<(), EngineError> {
+        let (view, schemes, events) = self.view_and_events(id)?;
+        if let Some(page_type) = view.history.get(view.history_index).cloned() {
+            view.load(&page_type, schemes, events);
+            self.navigations.push((id, page_type));
+        }
+        Ok(())
+    }
+
+    fn go_forward(&mut self, id: ViewId) -> Result<(), EngineError> {
+        let (view, schemes, events) = self.view_and_events(id)?;
+        if view.history_index + 1 < view.history.len() {
+            view.history_index += 1;
+            let page_type = view.history[view.history_index].clone();
+            view.load(&page_type, schemes, events);
+        }
+        Ok(())
+    }
+
+    fn go_back(&mut self, id: ViewId) -> Result<(), EngineError> {
+        let (view, schemes, events) = self.view_and_events(id)?;
+        if view.history_index > 0 {
+            view.history_index -= 1;
+            let page_type = view.history[view.history_index].clone();
+            view.load(&page_type, schemes, events);
+        }
+        Ok(())
+    }
+
+    fn stop(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.find_view_mut(id)?.loading = false;
+        Ok(())
+    }
+
+    fn can_go_back(&self, id: ViewId) -> Result<bool, EngineError> {
+        self.find_view(id).map(|view| view.history_index > 0)
+    }
+
+    fn can_go_forward(&self, id: ViewId) -> Result<bool, EngineError> {
+        self.find_view(id)
+            .map(|view| view.history_index + 1 < view.history.len())
+    }
+
+    fn is_loading(&self, id: ViewId) -> Result<bool, EngineError> {
+        self.find_view(id).map(|view| view.loading)
+    }
+
+    fn console_messages(&self, id: ViewId) -> Result<Vec<ConsoleMessage>, EngineError> {
+        self.find_view(id).map(|view| view.console.to_vec())
+    }
+
+    fn clear_console(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.find_view_mut(id).map(|view| view.console.clear())
+    }
+
+    fn register_bridge_function(&mut self, name: &str, function: BridgeFunction) {
+        self.bridge_functions.insert(name.to_string(), function);
+    }
+
+    fn register_scheme(&mut self, scheme: &str, handler: SchemeHandler) {
+        self.schemes.insert(scheme.to_ascii_lowercase(), handler);
+    }
+
+    /// Unscripted sources evaluate to `null`.
+    fn evaluate_script(&mut self, id: ViewId, source: &str) -> Result<String, EngineError> {
+        self.find_view(id)?;
+        self.scripts.push((id, source.to_string()));
+        match self.script_results.get(source) {
+            Some(Ok(json)) => Ok(json.clone()),
+            Some(Err(exception)) => Err(EngineError::Script(exception.clone())),
+            None => Ok(String::from("null")),
+        }
+    }
+
+    fn get_url(&self, id: ViewId) -> Result<String, EngineError> {
+        self.find_view(id).map(|view| view.url.clone())
+    }
+
+    fn get_title(&self, id: ViewId) -> Result<String, EngineError> {
+        self.find_view(id).map(|view| view.title.clone())
+    }
+
+    fn get_cursor(&self, id: ViewId) -> Interaction {
+        self.mock_view(id)
+            .map(|view| view.cursor)
+            .unwrap_or(Interaction::Idle)
+    }
+
+    fn get_view(&self, id: ViewId) -> Result<&ImageInfo, EngineError> {
+        self.find_view(id).map(|view| &view.frame)
+    }
+}
+
+fn is_registered(schemes: &Schemes, url: &str) -> bool {
+    url.split_once("://")
+        .is_some_and(|(scheme, _)| schemes.contains_key(&scheme.to_ascii_lowercase()))
+}
+
+/// The html a registered scheme serves for `url`.
+fn scheme_page(schemes: &Schemes, url: &str) -> Option<String> {
+    let (scheme, _) = url.split_once("://")?;
+    schemes.get(&scheme.to_ascii_lowercase())?(url)
+}
+
+fn html_title(html: &str) -> String {
+    html.split_once("<title>")
+        .and_then(|(_, rest)| rest.split_once("</title>"))
+        .map(|(title, _)| title.trim().to_string())
+        .unwrap_or_default()
+}
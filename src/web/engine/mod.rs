@@ -3,7 +3,14 @@ use cosmic::iced::keyboard;
 use cosmic::iced::mouse::{self, Interaction};
 use cosmic::iced::Point;
 use cosmic::iced::Size;
+use std::collections::VecDeque;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::sync::Arc;
 
+#[cfg(test)]
+pub mod mock;
 pub mod ultralight;
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -17,30 +24,229 @@ pub enum PixelFormat {
     Bgra,
 }
 
-pub type ViewId = usize;
+/// Identifies a view of an [`Engine`], which never hands the same id out twice. Only engines
+/// make them, so a tab position can't pass for one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct ViewId(u64);
+
+#[cfg(test)]
+impl ViewId {
+    /// An id for tests of code that only keeps them, without an engine.
+    pub fn from_raw(raw: u64) -> Self {
+        ViewId(raw)
+    }
+}
+
+impl fmt::Display for ViewId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Reads an id back from its [`Display`](fmt::Display) form, as tab drags carry it.
+impl FromStr for ViewId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(ViewId)
+    }
+}
+
+/// Notifications an [`Engine`] pushes about one of its views.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViewEvent {
+    BeginLoading {
+        url: String,
+    },
+    FinishLoading,
+    FailLoading {
+        url: String,
+        description: String,
+        error_code: i32,
+    },
+    DomReady,
+    TitleChanged(String),
+    UrlChanged(String),
+    TooltipChanged(String),
+    CursorChanged(Interaction),
+    HistoryUpdated,
+    /// A trusted page called `window.astrolabe.<name>` with JSON arguments.
+    BridgeCall {
+        name: String,
+        args: Vec<String>,
+    },
+}
+
+/// A [`ViewEvent`] tagged with the view it happened in.
+pub type EngineEvent = (ViewId, ViewEvent);
+
+/// Severity of a console message, least severe first.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ConsoleLevel {
+    #[default]
+    Debug,
+    Log,
+    Info,
+    Warning,
+    Error,
+}
+
+/// A message a page logged or the engine reported for it, e.g. a script error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    /// Url of the script or document the message came from.
+    pub source: String,
+    pub line: u32,
+    pub message: String,
+}
+
+/// The most recent console messages of a view, oldest dropped first.
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleLog {
+    messages: VecDeque<ConsoleMessage>,
+}
+
+impl ConsoleLog {
+    pub const CAPACITY: usize = 500;
+
+    pub fn push(&mut self, message: ConsoleMessage) {
+        if self.messages.len() == Self::CAPACITY {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    pub fn to_vec(&self) -> Vec<ConsoleMessage> {
+        self.messages.iter().cloned().collect()
+    }
+}
+
+/// Native function exposed to trusted internal pages as `window.astrolabe.<name>`.
+///
+/// Receives the call's arguments as JSON and returns a JSON value.
+pub type BridgeFunction = Arc<dyn Fn(&[String]) -> String + Send + Sync>;
+
+/// Serves the html of the page at an address of a registered scheme, `None` when there is no
+/// such page.
+pub type SchemeHandler = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Whether `url` is one of the app's own pages, which may use the native bridge.
+///
+/// Blank pages aren't, any page can open one. Generated pages are trusted by the engine
+/// loading them instead.
+pub fn is_app_url(url: &str) -> bool {
+    url.starts_with("astrolabe:")
+}
+
+/// Errors an [`Engine`] reports instead of bringing down every open tab.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EngineError {
+    /// No view has this id, it may have been closed.
+    UnknownView(ViewId),
+    /// The page could not be loaded.
+    LoadFailed { url: String, reason: String },
+    /// The renderer could not create, paint or drive a view.
+    Renderer(String),
+    /// The system clipboard could not be read or written.
+    Clipboard(String),
+    /// Evaluated script threw, holds the exception message.
+    Script(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::UnknownView(id) => write!(f, "no view with id {id}"),
+            EngineError::LoadFailed { url, reason } => write!(f, "failed to load {url}: {reason}"),
+            EngineError::Renderer(reason) => write!(f, "renderer error: {reason}"),
+            EngineError::Clipboard(reason) => write!(f, "clipboard error: {reason}"),
+            EngineError::Script(exception) => write!(f, "script error: {exception}"),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
 
 pub trait Engine {
     fn update(&mut self);
-    fn render(&mut self, size: Size<u32>);
-    fn request_render(&mut self, id: ViewId, size: Size<u32>);
-    fn new_view(&mut self, size: Size<u32>, content: Option<PageType>) -> ViewId;
-    fn remove_view(&mut self, id: ViewId);
+    /// Drains the events views have pushed since the last call.
+    fn take_events(&mut self) -> Vec<EngineEvent>;
+    /// Drains the errors met outside of any call since the last one, like the clipboard
+    /// failing for a page.
+    fn take_errors(&mut self) -> Vec<EngineError>;
+    /// Whether a view is loading or has changes to paint, like a running animation.
+    ///
+    /// Engines that aren't busy only need [`Engine::update`] now and then.
+    fn is_busy(&self) -> bool;
+    /// Paints the views that changed since they were last painted.
+    fn render(&mut self) -> Result<(), EngineError>;
+    /// Repaints view `id` whether it changed or not.
+    fn request_render(&mut self, id: ViewId) -> Result<(), EngineError>;
+    fn new_view(
+        &mut self,
+        size: Size<u32>,
+        content: Option<PageType>,
+    ) -> Result<ViewId, EngineError>;
+    fn remove_view(&mut self, id: ViewId) -> Result<(), EngineError>;
 
     fn focus(&mut self);
     fn unfocus(&self);
-    fn resize(&mut self, size: Size<u32>);
+    /// Sizes view `id` in logical pixels, each view keeps its size until it is changed.
+    fn resize_view(&mut self, id: ViewId, size: Size<u32>) -> Result<(), EngineError>;
+    /// Has view `id` paint `scale` physical pixels per logical one.
+    ///
+    /// Sizes and input points stay in logical pixels, frames come in physical ones.
+    fn set_scale(&mut self, id: ViewId, scale: f64) -> Result<(), EngineError>;
+
+    /// Opens the Web Inspector for `id` in a view of its own and returns that view.
+    ///
+    /// The inspector view is rendered and driven like any other, [`Engine::remove_view`]
+    /// closes it.
+    fn open_inspector(&mut self, id: ViewId, size: Size<u32>) -> Result<ViewId, EngineError>;
+
+    fn handle_keyboard_event(
+        &mut self,
+        id: ViewId,
+        event: keyboard::Event,
+    ) -> Result<(), EngineError>;
+    fn handle_mouse_event(
+        &mut self,
+        id: ViewId,
+        point: Point,
+        event: mouse::Event,
+    ) -> Result<(), EngineError>;
+    fn scroll(&mut self, id: ViewId, delta: mouse::ScrollDelta) -> Result<(), EngineError>;
+
+    fn goto(&mut self, id: ViewId, page_type: PageType) -> Result<(), EngineError>;
+    fn refresh(&mut self, id: ViewId) -> Result<(), EngineError>;
+    fn go_forward(&mut self, id: ViewId) -> Result<(), EngineError>;
+    fn go_back(&mut self, id: ViewId) -> Result<(), EngineError>;
+    /// Cancels the page load in progress, if any.
+    fn stop(&mut self, id: ViewId) -> Result<(), EngineError>;
+    fn can_go_back(&self, id: ViewId) -> Result<bool, EngineError>;
+    fn can_go_forward(&self, id: ViewId) -> Result<bool, EngineError>;
+    fn is_loading(&self, id: ViewId) -> Result<bool, EngineError>;
+
+    /// Exposes `function` on `window.astrolabe` of trusted pages loaded from now on.
+    fn register_bridge_function(&mut self, name: &str, function: BridgeFunction);
+    /// Serves the pages at `scheme://` addresses with `handler`. They keep their address and
+    /// load, reload and go back and forward like any other page.
+    fn register_scheme(&mut self, scheme: &str, handler: SchemeHandler);
 
-    fn handle_keyboard_event(&mut self, id: ViewId, event: keyboard::Event);
-    fn handle_mouse_event(&mut self, id: ViewId, point: Point, event: mouse::Event);
-    fn scroll(&mut self, id: ViewId, delta: mouse::ScrollDelta);
+    /// The console messages the view has kept, oldest first.
+    fn console_messages(&self, id: ViewId) -> Result<Vec<ConsoleMessage>, EngineError>;
+    fn clear_console(&mut self, id: ViewId) -> Result<(), EngineError>;
 
-    fn goto(&mut self, id: ViewId, page_type: PageType);
-    fn refresh(&mut self, id: ViewId);
-    fn go_forward(&mut self, id: ViewId);
-    fn go_back(&mut self, id: ViewId);
+    /// Runs `source` in the page and returns its completion value serialized as JSON.
+    fn evaluate_script(&mut self, id: ViewId, source: &str) -> Result<String, EngineError>;
 
-    fn get_url(&self, id: ViewId) -> String;
-    fn get_title(&self, id: ViewId) -> String;
+    fn get_url(&self, id: ViewId) -> Result<String, EngineError>;
+    fn get_title(&self, id: ViewId) -> Result<String, EngineError>;
     fn get_cursor(&self, id: ViewId) -> Interaction;
-    fn get_view(&self, id: ViewId) -> &ImageInfo;
+    fn get_view(&self, id: ViewId) -> Result<&ImageInfo, EngineError>;
 }
@@ -2,15 +2,20 @@ use clipboard_rs::{Clipboard, ClipboardContext};
 use cosmic::iced::keyboard::{self};
 use cosmic::iced::mouse::{self, ScrollDelta};
 use cosmic::iced::{Point, Size};
-use rand::Rng;
 use smol_str::SmolStr;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, RwLock};
 use std::{env::var, path::Path};
 use ul_next::{
     config::Config,
-    event::{self, KeyEventCreationInfo, MouseEvent, ScrollEvent},
+    event::{self, KeyEventCreationInfo, MouseButton, MouseEvent, MouseEventType, ScrollEvent},
+    javascript::{JSObject, JSPropertyAttributes, JSValue},
     key_code::VirtualKeyCode,
     platform,
     renderer::Renderer,
@@ -18,24 +23,34 @@ use ul_next::{
     window::Cursor,
 };
 
-use super::{Engine, PageType, PixelFormat, ViewId};
-use crate::web::ImageInfo;
+use super::{
+    is_app_url, BridgeFunction, ConsoleLevel, ConsoleLog, ConsoleMessage, Engine, EngineError,
+    EngineEvent, PageType, PixelFormat, SchemeHandler, ViewEvent, ViewId,
+};
+use crate::web::{ImageInfo, Region};
 
 struct UlClipboard {
     ctx: ClipboardContext,
+    errors: Sender<EngineError>,
 }
 
 impl platform::Clipboard for UlClipboard {
     fn clear(&mut self) {}
 
     fn read_plain_text(&mut self) -> Option<String> {
-        Some(self.ctx.get_text().unwrap_or("".to_string()))
+        match self.ctx.get_text() {
+            Ok(text) => Some(text),
+            Err(err) => {
+                let _ = self.errors.send(EngineError::Clipboard(err.to_string()));
+                Some(String::new())
+            }
+        }
     }
 
     fn write_plain_text(&mut self, text: &str) {
-        self.ctx
-            .set_text(text.into())
-            .expect("Failed to set contents of clipboard");
+        if let Err(err) = self.ctx.set_text(text.into()) {
+            let _ = self.errors.send(EngineError::Clipboard(err.to_string()));
+        }
     }
 }
 
@@ -46,21 +61,67 @@ pub struct View {
     cursor: Arc<RwLock<mouse::Interaction>>,
     last_frame: ImageInfo,
     was_loading: bool,
+    // in physical pixels
     cursor_pos: Point,
+    // in logical pixels, the surface has `scale` times as many
+    size: Size<u32>,
+    scale: f64,
+    trust: Trust,
+    console: Arc<RwLock<ConsoleLog>>,
 }
 
 impl View {
-    fn update_cursor_pos(&mut self) {
-        let cursor_pos = self.cursor_pos;
-        self.view.fire_mouse_event(
-            MouseEvent::new(
-                ul_next::event::MouseEventType::MouseMoved,
-                cursor_pos.x as i32,
-                cursor_pos.y as i32,
-                ul_next::event::MouseButton::None,
-            )
-            .expect("Ultralight failed to fire mouse input"),
-        );
+    fn update_cursor_pos(&mut self) -> Result<(), EngineError> {
+        fire_mouse_event(
+            &self.view,
+            MouseEventType::MouseMoved,
+            self.cursor_pos,
+            MouseButton::None,
+        )
+    }
+
+    /// Loads `page_type`, with the addresses of registered schemes already mapped to
+    /// [`SCHEME_ROOT`].
+    fn load(&mut self, page_type: PageType) -> Result<(), EngineError> {
+        let generated = matches!(page_type, PageType::Html(_));
+        self.trust.generated.store(generated, Ordering::Relaxed);
+        let result = match page_type {
+            PageType::Url(url) => self.view.load_url(&url).map_err(|_| page_url(&url)),
+            PageType::Html(html) => self
+                .view
+                .load_html(&html)
+                .map_err(|_| String::from("about:blank")),
+        };
+        result.map_err(|url| EngineError::LoadFailed {
+            url,
+            reason: String::from("the page could not be passed to the renderer"),
+        })?;
+        self.was_loading = true;
+        Ok(())
+    }
+
+    /// Copies what was painted since the last frame out of the surface.
+    fn save_frame(&mut self) -> Result<(), EngineError> {
+        let mut surface = self
+            .view
+            .surface()
+            .ok_or_else(|| EngineError::Renderer(String::from("view has no surface")))?;
+        let (width, height) = (surface.width(), surface.height());
+        let stride = surface.row_bytes() as usize;
+        let bounds = surface.dirty_bounds();
+        let dirty = Region {
+            x: bounds.left.max(0) as u32,
+            y: bounds.top.max(0) as u32,
+            width: (bounds.right - bounds.left.max(0)).max(0) as u32,
+            height: (bounds.bottom - bounds.top.max(0)).max(0) as u32,
+        };
+        if let Some(pixels) = surface.lock_pixels() {
+            self.last_frame
+                .update(&pixels, PixelFormat::Bgra, stride, width, height, dirty);
+            self.was_loading = false;
+        }
+        surface.clear_dirty_bounds();
+        Ok(())
     }
 }
 
@@ -69,17 +130,41 @@ pub struct Ultralight {
     renderer: Renderer,
     view_config: view::ViewConfig,
     views: Vec<View>,
+    next_view_id: u64,
+    event_sender: Sender<EngineEvent>,
+    event_receiver: Receiver<EngineEvent>,
+    error_receiver: Receiver<EngineError>,
+    bridge_functions: BridgeFunctions,
+    schemes: Schemes,
 }
 
+type BridgeFunctions = Arc<RwLock<Vec<(String, BridgeFunction)>>>;
+type Schemes = Arc<RwLock<HashMap<String, SchemeHandler>>>;
+
+/// Where pages of registered schemes live in the file system Ultralight reads from.
+/// Ultralight only loads `file:`, `data:` and web addresses, so `scheme://rest` is loaded
+/// as `{SCHEME_ROOT}scheme/rest` and mapped back in everything the view reports.
+const SCHEME_ROOT: &str = "file:///astrolabe-schemes/";
+
 impl Default for Ultralight {
     fn default() -> Self {
         let config = Config::start().build().expect("Failed to start Ultralight");
         platform::enable_platform_fontloader();
-        platform::enable_platform_filesystem(platform_filesystem())
-            .expect("Failed to get platform filesystem");
-        platform::set_clipboard(UlClipboard {
-            ctx: ClipboardContext::new().expect("Failed to get ownership of clipboard"),
+        let schemes = Schemes::default();
+        platform::set_filesystem(SchemeFileSystem {
+            dir: platform_filesystem(),
+            schemes: schemes.clone(),
         });
+        let (error_sender, error_receiver) = mpsc::channel();
+        match ClipboardContext::new() {
+            Ok(ctx) => platform::set_clipboard(UlClipboard {
+                ctx,
+                errors: error_sender,
+            }),
+            Err(err) => {
+                let _ = error_sender.send(EngineError::Clipboard(err.to_string()));
+            }
+        }
 
         let renderer = Renderer::create(config).expect("Failed to create ultralight renderer");
         let view_config = view::ViewConfig::start()
@@ -88,21 +173,29 @@ impl Default for Ultralight {
             .is_accelerated(false)
             .build()
             .unwrap();
+        let (event_sender, event_receiver) = mpsc::channel();
 
         Self {
             renderer,
             view_config,
             views: Vec::new(),
+            next_view_id: 0,
+            event_sender,
+            event_receiver,
+            error_receiver,
+            bridge_functions: BridgeFunctions::default(),
+            schemes,
         }
     }
 }
 
 impl Ultralight {
-    /// Creates a new Ultralight adapter
-    pub fn new(font: &str, scale: f64) -> Self {
+    /// Creates a new Ultralight adapter with `font` as the standard font, views are scaled one
+    /// by one with [`Engine::set_scale`].
+    pub fn new(font: &str) -> Self {
         Self {
             view_config: view::ViewConfig::start()
-                .initial_device_scale(scale)
+                .initial_device_scale(1.0)
                 .font_family_standard(font)
                 // iced_webview does not currently support acceleration
                 .is_accelerated(false)
@@ -112,71 +205,52 @@ impl Ultralight {
         }
     }
 
-    fn get_view(&self, id: ViewId) -> &View {
+    fn get_view(&self, id: ViewId) -> Result<&View, EngineError> {
         self.views
             .iter()
             .find(|&view| view.id == id)
-            .expect("The requested View id was not found")
+            .ok_or(EngineError::UnknownView(id))
     }
 
-    fn get_view_mut(&mut self, id: ViewId) -> &mut View {
+    fn get_view_mut(&mut self, id: ViewId) -> Result<&mut View, EngineError> {
         self.views
             .iter_mut()
             .find(|view| view.id == id)
-            .expect("The requested View id was not found")
-    }
-}
-
-impl Engine for Ultralight {
-    fn update(&mut self) {
-        self.renderer.update();
+            .ok_or(EngineError::UnknownView(id))
     }
 
-    fn render(&mut self, size: Size<u32>) {
-        // for each view save frame
-        for view in self.views.iter_mut() {
-            view.update_cursor_pos();
-            if view.view.needs_paint() || view.was_loading && !view.view.is_loading() {
-                if let Some(pixels) = view.view.surface().unwrap().lock_pixels() {
-                    view.last_frame =
-                        ImageInfo::new(pixels.to_vec(), PixelFormat::Bgra, size.width, size.height);
-                    view.was_loading = false;
-                }
-            }
+    /// `page_type` with the address of a registered scheme mapped to where Ultralight can
+    /// load it from.
+    fn engine_page(&self, page_type: PageType) -> PageType {
+        match page_type {
+            PageType::Url(url) => PageType::Url(engine_url(&self.schemes, &url)),
+            html => html,
         }
     }
 
-    fn request_render(&mut self, id: ViewId, size: Size<u32>) {
-        self.get_view_mut(id).update_cursor_pos();
-        self.get_view(id).view.set_needs_paint(true);
-        self.renderer.render();
-        if let Some(pixels) = self.get_view(id).view.surface().unwrap().lock_pixels() {
-            self.get_view_mut(id).last_frame =
-                ImageInfo::new(pixels.to_vec(), PixelFormat::Bgra, size.width, size.height);
-            self.get_view_mut(id).was_loading = false
-        }
+    /// Ids count up and are never reused, so a closed view can't be mistaken for a new one.
+    fn next_view_id(&mut self) -> ViewId {
+        let id = ViewId(self.next_view_id);
+        self.next_view_id += 1;
+        id
     }
 
-    fn new_view(&mut self, size: Size<u32>, page_type: Option<PageType>) -> ViewId {
-        let id = rand::thread_rng().gen();
-
-        let view = self
-            .renderer
-            .create_view(size.width, size.height, &self.view_config, None)
-            .expect("Failed to create view");
-
-        // TODO: debug why new views are slanted unless do + 10/ - 10
-        // maybe causes the fuzzyness
-        view.resize(size.width + 10, size.height - 10);
-
-        let surface = view.surface().expect("Failed to get surface of new view");
-        // RGBA - ensure it has the right diamentions
-        debug_assert!(surface.row_bytes() / size.width == 4);
+    /// Hooks up the cursor, event, console and bridge callbacks of a freshly created view.
+    fn wrap_view(
+        &self,
+        id: ViewId,
+        view: view::View,
+        size: Size<u32>,
+    ) -> Result<View, EngineError> {
+        // frames are copied out of the surface, which may pad its rows
+        view.surface()
+            .ok_or_else(|| EngineError::Renderer(String::from("new view has no surface")))?;
 
         let cursor = Arc::new(RwLock::new(mouse::Interaction::Idle));
         let cb_cursor = cursor.clone();
+        let sender = self.event_sender.clone();
         view.set_change_cursor_callback(move |_view, cursor_update| {
-            *cb_cursor.write().expect("Failed to write cursor status") = match cursor_update {
+            let interaction = match cursor_update {
                 Cursor::None => mouse::Interaction::Idle,
                 Cursor::Pointer => mouse::Interaction::Idle,
                 Cursor::Hand => mouse::Interaction::Pointer,
@@ -193,53 +267,143 @@ impl Engine for Ultralight {
                 Cursor::ZoomOut => mouse::Interaction::ZoomIn,
                 _ => mouse::Interaction::Pointer,
             };
+            *cb_cursor.write().expect("Failed to write cursor status") = interaction;
+            let _ = sender.send((id, ViewEvent::CursorChanged(interaction)));
         });
+        let trust = Trust::default();
+        forward_view_events(&view, id, &self.event_sender, &trust);
 
-        let view = View {
+        let console = Arc::new(RwLock::new(ConsoleLog::default()));
+        let cb_console = console.clone();
+        view.set_add_console_message_callback(
+            move |_view, _source, level, message, line, _column, source_id| {
+                let level = match level {
+                    view::ConsoleMessageLevel::Debug => ConsoleLevel::Debug,
+                    view::ConsoleMessageLevel::Log => ConsoleLevel::Log,
+                    view::ConsoleMessageLevel::Info => ConsoleLevel::Info,
+                    view::ConsoleMessageLevel::Warning => ConsoleLevel::Warning,
+                    view::ConsoleMessageLevel::Error => ConsoleLevel::Error,
+                };
+                if let Ok(mut console) = cb_console.write() {
+                    console.push(ConsoleMessage {
+                        level,
+                        source: source_id,
+                        line,
+                        message,
+                    });
+                }
+            },
+        );
+
+        let cb_trusted = trust.trusted.clone();
+        let functions = self.bridge_functions.clone();
+        let sender = self.event_sender.clone();
+        view.set_window_object_ready_callback(move |view, _frame_id, is_main_frame, _url| {
+            if is_main_frame && cb_trusted.load(Ordering::Relaxed) {
+                install_bridge(view, id, &functions, &sender);
+            }
+        });
+
+        Ok(View {
             id,
             view,
             cursor,
             last_frame: ImageInfo::blank(size.width, size.height),
             was_loading: true,
             cursor_pos: Point::default(),
-        };
-        if let Some(page_type) = page_type {
-            match page_type {
-                PageType::Url(url) => view.view.load_url(&url).expect("Failed to load url"),
-                PageType::Html(html) => view
-                    .view
-                    .load_html(&html)
-                    .expect("Failed to load custom html"),
+            size,
+            scale: 1.0,
+            trust,
+            console,
+        })
+    }
+}
+
+impl Engine for Ultralight {
+    fn update(&mut self) {
+        self.renderer.update();
+    }
+
+    fn take_events(&mut self) -> Vec<EngineEvent> {
+        self.event_receiver.try_iter().collect()
+    }
+
+    fn take_errors(&mut self) -> Vec<EngineError> {
+        self.error_receiver.try_iter().collect()
+    }
+
+    fn is_busy(&self) -> bool {
+        self.views
+            .iter()
+            .any(|view| view.was_loading || view.view.needs_paint() || view.view.is_loading())
+    }
+
+    fn render(&mut self) -> Result<(), EngineError> {
+        // painting clears `needs_paint`, so note the views to copy out beforehand
+        let changed: Vec<bool> = self
+            .views
+            .iter()
+            .map(|view| view.view.needs_paint() || view.was_loading && !view.view.is_loading())
+            .collect();
+        if !changed.contains(&true) {
+            return Ok(());
+        }
+        self.renderer.render();
+        // one view failing still leaves the frames of the others to copy out
+        let mut result = Ok(());
+        for (view, changed) in self.views.iter_mut().zip(changed) {
+            if changed {
+                if let Err(err) = view.save_frame() {
+                    if result.is_ok() {
+                        result = Err(err);
+                    }
+                }
             }
+        }
+        result
+    }
+
+    fn request_render(&mut self, id: ViewId) -> Result<(), EngineError> {
+        let view = self.get_view_mut(id)?;
+        view.update_cursor_pos()?;
+        view.view.set_needs_paint(true);
+        self.renderer.render();
+        self.get_view_mut(id)?.save_frame()
+    }
+
+    fn new_view(
+        &mut self,
+        size: Size<u32>,
+        page_type: Option<PageType>,
+    ) -> Result<ViewId, EngineError> {
+        let id = self.next_view_id();
+
+        let view = self
+            .renderer
+            .create_view(size.width, size.height, &self.view_config, None)
+            .ok_or_else(|| EngineError::Renderer(String::from("failed to create view")))?;
+        let mut view = self.wrap_view(id, view, size)?;
+        if let Some(page_type) = page_type {
+            view.load(self.engine_page(page_type))?;
             view.view.set_needs_paint(true);
         }
         self.views.push(view);
-        id
+        Ok(id)
     }
 
-    fn remove_view(&mut self, id: ViewId) {
+    fn remove_view(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.get_view(id)?;
         self.views.retain(|view| view.id != id);
+        Ok(())
     }
 
-    fn goto(&mut self, id: ViewId, page_type: PageType) {
-        *self
-            .get_view(id)
-            .cursor
-            .write()
-            .expect("Failed cursor poisoned") = mouse::Interaction::Working;
-        match page_type {
-            PageType::Url(url) => self
-                .get_view_mut(id)
-                .view
-                .load_url(&url)
-                .expect("Failed to load url"),
-            PageType::Html(html) => self
-                .get_view_mut(id)
-                .view
-                .load_html(&html)
-                .expect("Failed to load given html"),
+    fn goto(&mut self, id: ViewId, page_type: PageType) -> Result<(), EngineError> {
+        let page_type = self.engine_page(page_type);
+        let view = self.get_view_mut(id)?;
+        if let Ok(mut cursor) = view.cursor.write() {
+            *cursor = mouse::Interaction::Working;
         }
-        self.get_view_mut(id).was_loading = true;
+        view.load(page_type)
     }
 
     fn focus(&mut self) {
@@ -250,15 +414,58 @@ impl Engine for Ultralight {
         self.views.iter().for_each(|view| view.view.unfocus());
     }
 
-    fn resize(&mut self, size: Size<u32>) {
-        self.views.iter().for_each(|view| {
-            view.view.resize(size.width, size.height);
-            view.view.surface().unwrap().resize(size.width, size.height);
-            view.view.set_needs_paint(true);
-        })
+    fn resize_view(&mut self, id: ViewId, size: Size<u32>) -> Result<(), EngineError> {
+        let view = self.get_view_mut(id)?;
+        if view.size == size {
+            return Ok(());
+        }
+        view.size = size;
+        resize_view(&view.view, physical(size, view.scale))
+    }
+
+    fn set_scale(&mut self, id: ViewId, scale: f64) -> Result<(), EngineError> {
+        let view = self.get_view_mut(id)?;
+        if view.scale == scale {
+            return Ok(());
+        }
+        view.cursor_pos = Point::new(
+            (view.cursor_pos.x as f64 / view.scale * scale) as f32,
+            (view.cursor_pos.y as f64 / view.scale * scale) as f32,
+        );
+        view.scale = scale;
+        view.view.set_device_scale(scale);
+        resize_view(&view.view, physical(view.size, scale))
+    }
+
+    fn open_inspector(&mut self, id: ViewId, size: Size<u32>) -> Result<ViewId, EngineError> {
+        // an unknown view costs neither an id nor a renderer view
+        self.get_view(id)?;
+        let id_inspector = self.next_view_id();
+        let inspector = self
+            .renderer
+            .create_view(size.width, size.height, &self.view_config, None)
+            .ok_or_else(|| EngineError::Renderer(String::from("failed to create inspector")))?;
+
+        // Ultralight asks for the view to draw the inspector into through this callback,
+        // hand it a handle to ours so we keep painting and driving it ourselves
+        let handoff = Rc::new(RefCell::new(Some(inspector.clone())));
+        let inspected = &self.get_view(id)?.view;
+        inspected.set_create_inspector_view_callback(move |_view, _is_local, _inspected_url| {
+            handoff.borrow_mut().take()
+        });
+        inspected.create_local_inspector_view();
+
+        let view = self.wrap_view(id_inspector, inspector, size)?;
+        view.view.set_needs_paint(true);
+        self.views.push(view);
+        Ok(id_inspector)
     }
 
-    fn handle_keyboard_event(&mut self, id: ViewId, event: keyboard::Event) {
+    fn handle_keyboard_event(
+        &mut self,
+        id: ViewId,
+        event: keyboard::Event,
+    ) -> Result<(), EngineError> {
         let key_event = match event {
             keyboard::Event::KeyPressed {
                 key,
@@ -294,120 +501,434 @@ impl Engine for Ultralight {
             }
         };
 
+        let view = self.get_view_mut(id)?;
         if let Some(key_event) = key_event {
-            self.get_view_mut(id).view.fire_key_event(key_event);
+            view.view.fire_key_event(key_event);
         }
+        Ok(())
     }
 
-    fn handle_mouse_event(&mut self, id: ViewId, point: Point, event: mouse::Event) {
+    fn handle_mouse_event(
+        &mut self,
+        id: ViewId,
+        point: Point,
+        event: mouse::Event,
+    ) -> Result<(), EngineError> {
+        // Ultralight takes points in the physical pixels of the surface
+        let scale = self.get_view(id)?.scale as f32;
+        let point = Point::new(point.x * scale, point.y * scale);
         match event {
             mouse::Event::ButtonReleased(mouse::Button::Forward) => self.go_forward(id),
             mouse::Event::ButtonReleased(mouse::Button::Back) => self.go_back(id),
-            mouse::Event::ButtonPressed(mouse::Button::Left) => {
-                self.get_view_mut(id).view.fire_mouse_event(
-                    MouseEvent::new(
-                        ul_next::event::MouseEventType::MouseDown,
-                        point.x as i32,
-                        point.y as i32,
-                        ul_next::event::MouseButton::Left,
-                    )
-                    .expect("Ultralight failed to fire mouse input"),
-                );
-            }
-            mouse::Event::ButtonReleased(mouse::Button::Left) => {
-                self.get_view_mut(id).view.fire_mouse_event(
-                    MouseEvent::new(
-                        ul_next::event::MouseEventType::MouseUp,
-                        point.x as i32,
-                        point.y as i32,
-                        ul_next::event::MouseButton::Left,
-                    )
-                    .expect("Ultralight failed to fire mouse input"),
-                );
-            }
-            mouse::Event::ButtonPressed(mouse::Button::Right) => {
-                self.get_view_mut(id).view.fire_mouse_event(
-                    MouseEvent::new(
-                        ul_next::event::MouseEventType::MouseDown,
-                        point.x as i32,
-                        point.y as i32,
-                        ul_next::event::MouseButton::Right,
-                    )
-                    .expect("Ultralight failed to fire mouse input"),
-                );
-            }
-            mouse::Event::ButtonReleased(mouse::Button::Right) => {
-                self.get_view_mut(id).view.fire_mouse_event(
-                    MouseEvent::new(
-                        ul_next::event::MouseEventType::MouseUp,
-                        point.x as i32,
-                        point.y as i32,
-                        ul_next::event::MouseButton::Right,
-                    )
-                    .expect("Ultralight failed to fire mouse input"),
-                );
-            }
+            mouse::Event::ButtonPressed(mouse::Button::Left) => fire_mouse_event(
+                &self.get_view(id)?.view,
+                MouseEventType::MouseDown,
+                point,
+                MouseButton::Left,
+            ),
+            mouse::Event::ButtonReleased(mouse::Button::Left) => fire_mouse_event(
+                &self.get_view(id)?.view,
+                MouseEventType::MouseUp,
+                point,
+                MouseButton::Left,
+            ),
+            mouse::Event::ButtonPressed(mouse::Button::Right) => fire_mouse_event(
+                &self.get_view(id)?.view,
+                MouseEventType::MouseDown,
+                point,
+                MouseButton::Right,
+            ),
+            mouse::Event::ButtonReleased(mouse::Button::Right) => fire_mouse_event(
+                &self.get_view(id)?.view,
+                MouseEventType::MouseUp,
+                point,
+                MouseButton::Right,
+            ),
             mouse::Event::CursorMoved { position: _ } => {
-                self.get_view_mut(id).cursor_pos = point;
+                let view = self.get_view_mut(id)?;
+                view.cursor_pos = point;
+                view.update_cursor_pos()
             }
             mouse::Event::WheelScrolled { delta } => self.scroll(id, delta),
             mouse::Event::CursorLeft => {
                 self.unfocus();
+                Ok(())
             }
             mouse::Event::CursorEntered => {
                 self.focus();
+                Ok(())
             }
-            _ => (),
+            _ => Ok(()),
         }
     }
 
-    fn refresh(&mut self, id: ViewId) {
-        self.get_view_mut(id).view.reload();
+    fn refresh(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.get_view_mut(id)?.view.reload();
+        Ok(())
     }
 
-    fn go_forward(&mut self, id: ViewId) {
-        self.get_view_mut(id).view.go_forward();
+    fn go_forward(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.get_view_mut(id)?.view.go_forward();
+        Ok(())
     }
 
-    fn go_back(&mut self, id: ViewId) {
-        self.get_view_mut(id).view.go_back();
+    fn go_back(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.get_view_mut(id)?.view.go_back();
+        Ok(())
     }
 
-    fn scroll(&mut self, id: ViewId, delta: mouse::ScrollDelta) {
-        let scroll_event = match delta {
-            ScrollDelta::Lines { x, y } => ScrollEvent::new(
-                ul_next::event::ScrollEventType::ScrollByPixel,
-                x as i32 * 100,
-                y as i32 * 100,
-            )
-            .unwrap(),
-            ScrollDelta::Pixels { x, y } => ScrollEvent::new(
-                ul_next::event::ScrollEventType::ScrollByPixel,
-                x as i32,
-                y as i32,
-            )
-            .unwrap(),
+    fn stop(&mut self, id: ViewId) -> Result<(), EngineError> {
+        self.get_view_mut(id)?.view.stop();
+        Ok(())
+    }
+
+    fn can_go_back(&self, id: ViewId) -> Result<bool, EngineError> {
+        Ok(self.get_view(id)?.view.can_go_back())
+    }
+
+    fn can_go_forward(&self, id: ViewId) -> Result<bool, EngineError> {
+        Ok(self.get_view(id)?.view.can_go_forward())
+    }
+
+    fn is_loading(&self, id: ViewId) -> Result<bool, EngineError> {
+        Ok(self.get_view(id)?.view.is_loading())
+    }
+
+    fn scroll(&mut self, id: ViewId, delta: mouse::ScrollDelta) -> Result<(), EngineError> {
+        let scale = self.get_view(id)?.scale as f32;
+        let (x, y) = match delta {
+            ScrollDelta::Lines { x, y } => (x * 100.0, y * 100.0),
+            ScrollDelta::Pixels { x, y } => (x, y),
         };
-        self.get_view_mut(id).view.fire_scroll_event(scroll_event);
+        let (x, y) = ((x * scale) as i32, (y * scale) as i32);
+        let scroll_event = ScrollEvent::new(ul_next::event::ScrollEventType::ScrollByPixel, x, y)
+            .map_err(|_| {
+            EngineError::Renderer(String::from("failed to create scroll event"))
+        })?;
+        self.get_view_mut(id)?.view.fire_scroll_event(scroll_event);
+        Ok(())
+    }
+
+    fn console_messages(&self, id: ViewId) -> Result<Vec<ConsoleMessage>, EngineError> {
+        Ok(self
+            .get_view(id)?
+            .console
+            .read()
+            .map(|console| console.to_vec())
+            .unwrap_or_default())
+    }
+
+    fn clear_console(&mut self, id: ViewId) -> Result<(), EngineError> {
+        if let Ok(mut console) = self.get_view(id)?.console.write() {
+            console.clear();
+        }
+        Ok(())
     }
 
-    fn get_url(&self, id: ViewId) -> String {
-        self.get_view(id).view.url().unwrap_or_default()
+    fn register_bridge_function(&mut self, name: &str, function: BridgeFunction) {
+        if let Ok(mut functions) = self.bridge_functions.write() {
+            functions.retain(|(existing, _)| existing != name);
+            functions.push((name.to_string(), function));
+        }
     }
 
-    fn get_title(&self, id: ViewId) -> String {
-        self.get_view(id).view.title().unwrap_or_default()
+    fn register_scheme(&mut self, scheme: &str, handler: SchemeHandler) {
+        if let Ok(mut schemes) = self.schemes.write() {
+            schemes.insert(scheme.to_ascii_lowercase(), handler);
+        }
+    }
+
+    fn evaluate_script(&mut self, id: ViewId, source: &str) -> Result<String, EngineError> {
+        // indirect eval keeps the page's global scope, stringify gives callers plain JSON
+        let script = format!(
+            "(function() {{ \
+                const json = JSON.stringify((0, eval)({})); \
+                return json === undefined ? 'null' : json; \
+            }})()",
+            js_string_literal(source)
+        );
+        self.get_view(id)?
+            .view
+            .evaluate_script(&script)
+            .map_err(|_| EngineError::Renderer(String::from("failed to pass script to view")))?
+            .map_err(EngineError::Script)
+    }
+
+    fn get_url(&self, id: ViewId) -> Result<String, EngineError> {
+        Ok(page_url(&self.get_view(id)?.view.url().unwrap_or_default()))
+    }
+
+    fn get_title(&self, id: ViewId) -> Result<String, EngineError> {
+        Ok(self.get_view(id)?.view.title().unwrap_or_default())
     }
 
     fn get_cursor(&self, id: ViewId) -> mouse::Interaction {
-        match self.get_view(id).cursor.read() {
-            Ok(cursor) => *cursor,
-            Err(_) => mouse::Interaction::Working,
+        match self.get_view(id).map(|view| view.cursor.read()) {
+            Ok(Ok(cursor)) => *cursor,
+            _ => mouse::Interaction::Working,
         }
     }
 
-    fn get_view(&self, id: ViewId) -> &ImageInfo {
-        &self.get_view(id).last_frame
+    fn get_view(&self, id: ViewId) -> Result<&ImageInfo, EngineError> {
+        Ok(&self.get_view(id)?.last_frame)
+    }
+}
+
+/// Whether the page in a view is one of ours, shared with the view's callbacks.
+#[derive(Clone, Default)]
+struct Trust {
+    /// Set while the main frame shows one of our pages, which get the native bridge.
+    trusted: Arc<AtomicBool>,
+    /// Set from loading generated html until its load begins. Those pages have no address
+    /// to tell them apart from blank pages any site can open.
+    generated: Arc<AtomicBool>,
+}
+
+impl Trust {
+    /// Decides on the page a main frame load of `url` brings.
+    fn begin_loading(&self, url: &str) {
+        let ours = self.generated.swap(false, Ordering::Relaxed) || is_app_url(url);
+        self.trusted.store(ours, Ordering::Relaxed);
+    }
+}
+
+/// Feeds the view's load, title, url, tooltip and history callbacks into the event channel.
+fn forward_view_events(view: &view::View, id: ViewId, sender: &Sender<EngineEvent>, trust: &Trust) {
+    let tx = sender.clone();
+    let trust = trust.clone();
+    view.set_begin_loading_callback(move |_view, _frame_id, is_main_frame, url| {
+        if is_main_frame {
+            let url = page_url(&url);
+            trust.begin_loading(&url);
+            let _ = tx.send((id, ViewEvent::BeginLoading { url }));
+        }
+    });
+
+    let tx = sender.clone();
+    view.set_finish_loading_callback(move |_view, _frame_id, is_main_frame, _url| {
+        if is_main_frame {
+            let _ = tx.send((id, ViewEvent::FinishLoading));
+        }
+    });
+
+    let tx = sender.clone();
+    view.set_fail_loading_callback(
+        move |_view, _frame_id, is_main_frame, url, description, _error_domain, error_code| {
+            if is_main_frame {
+                let _ = tx.send((
+                    id,
+                    ViewEvent::FailLoading {
+                        url: page_url(&url),
+                        description,
+                        error_code,
+                    },
+                ));
+            }
+        },
+    );
+
+    let tx = sender.clone();
+    view.set_dom_ready_callback(move |_view, _frame_id, is_main_frame, _url| {
+        if is_main_frame {
+            let _ = tx.send((id, ViewEvent::DomReady));
+        }
+    });
+
+    let tx = sender.clone();
+    view.set_change_title_callback(move |_view, title| {
+        let _ = tx.send((id, ViewEvent::TitleChanged(title)));
+    });
+
+    let tx = sender.clone();
+    view.set_change_url_callback(move |_view, url| {
+        let _ = tx.send((id, ViewEvent::UrlChanged(page_url(&url))));
+    });
+
+    let tx = sender.clone();
+    view.set_change_tooltip_callback(move |_view, tooltip| {
+        let _ = tx.send((id, ViewEvent::TooltipChanged(tooltip)));
+    });
+
+    let tx = sender.clone();
+    view.set_update_history_callback(move |_view| {
+        let _ = tx.send((id, ViewEvent::HistoryUpdated));
+    });
+}
+
+/// Defines `window.astrolabe` with every registered bridge function.
+///
+/// Calls run the Rust function for its return value and are also pushed as
+/// [`ViewEvent::BridgeCall`] so the app can react to them.
+fn install_bridge(
+    view: &view::View,
+    id: ViewId,
+    functions: &BridgeFunctions,
+    sender: &Sender<EngineEvent>,
+) {
+    let Ok(functions) = functions.read() else {
+        return;
+    };
+    let ctx = view.lock_js_context();
+    let bridge = JSObject::new(&ctx);
+
+    for (name, function) in functions.iter() {
+        let call_name = name.clone();
+        let function = function.clone();
+        let sender = sender.clone();
+        let js_function = JSObject::new_function_with_callback(&ctx, move |ctx, _this, args| {
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| {
+                    arg.to_json_string(0)
+                        .map(|json| json.to_string())
+                        .unwrap_or_else(|_| String::from("null"))
+                })
+                .collect();
+            let result = function(&args);
+            let _ = sender.send((
+                id,
+                ViewEvent::BridgeCall {
+                    name: call_name.clone(),
+                    args,
+                },
+            ));
+            Ok(JSValue::new_from_json(ctx, &result).unwrap_or_else(|| JSValue::new_null(ctx)))
+        });
+        let _ = bridge.set_property(name, &js_function, JSPropertyAttributes::default());
+    }
+
+    let _ = ctx.global_object().set_property(
+        "astrolabe",
+        &bridge,
+        JSPropertyAttributes::new()
+            .read_only(true)
+            .dont_delete(true),
+    );
+}
+
+/// Quotes `text` so it can be spliced into a script as a string literal.
+fn js_string_literal(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\u{2028}' => literal.push_str("\\u2028"),
+            '\u{2029}' => literal.push_str("\\u2029"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// `size` in the physical pixels of a view painting `scale` of them per logical one.
+fn physical(size: Size<u32>, scale: f64) -> Size<u32> {
+    Size::new(
+        (size.width as f64 * scale).round() as u32,
+        (size.height as f64 * scale).round() as u32,
+    )
+}
+
+fn resize_view(view: &view::View, size: Size<u32>) -> Result<(), EngineError> {
+    view.resize(size.width, size.height);
+    view.surface()
+        .ok_or_else(|| EngineError::Renderer(String::from("view has no surface")))?
+        .resize(size.width, size.height);
+    view.set_needs_paint(true);
+    Ok(())
+}
+
+fn fire_mouse_event(
+    view: &view::View,
+    ty: MouseEventType,
+    point: Point,
+    button: MouseButton,
+) -> Result<(), EngineError> {
+    let event = MouseEvent::new(ty, point.x as i32, point.y as i32, button)
+        .map_err(|_| EngineError::Renderer(String::from("failed to create mouse event")))?;
+    view.fire_mouse_event(event);
+    Ok(())
+}
+
+/// Where Ultralight loads `scheme://rest` from when `scheme` is registered, `url` itself
+/// otherwise.
+fn engine_url(schemes: &Schemes, url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    match schemes.read() {
+        Ok(schemes) if schemes.contains_key(&scheme) => format!("{SCHEME_ROOT}{scheme}/{rest}"),
+        _ => url.to_string(),
+    }
+}
+
+/// The address `url` was loaded as, undoing [`engine_url`].
+fn page_url(url: &str) -> String {
+    url.strip_prefix(SCHEME_ROOT)
+        .and_then(|path| path.split_once('/'))
+        .map(|(scheme, rest)| format!("{scheme}://{rest}"))
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Ultralight's files: its resources from `dir`, and the pages of registered schemes from
+/// their handlers under [`SCHEME_ROOT`].
+struct SchemeFileSystem {
+    dir: PathBuf,
+    schemes: Schemes,
+}
+
+impl SchemeFileSystem {
+    /// The html of the scheme page at `path`, relative to the file system root.
+    fn page(&self, path: &str) -> Option<String> {
+        let root = SCHEME_ROOT.strip_prefix("file:///")?;
+        let (scheme, _) = path.strip_prefix(root)?.split_once('/')?;
+        let handler = self.schemes.read().ok()?.get(scheme)?.clone();
+        handler(&page_url(&format!("file:///{path}")))
+    }
+
+    fn is_page(path: &str) -> bool {
+        format!("file:///{path}").starts_with(SCHEME_ROOT)
+    }
+}
+
+impl platform::FileSystem for SchemeFileSystem {
+    fn file_exists(&mut self, path: &str) -> bool {
+        if Self::is_page(path) {
+            self.page(path).is_some()
+        } else {
+            self.dir.join(path).is_file()
+        }
+    }
+
+    fn get_file_mime_type(&mut self, path: &str) -> String {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str());
+        let mime = match extension {
+            _ if Self::is_page(path) => "text/html",
+            Some("html" | "htm") => "text/html",
+            Some("css") => "text/css",
+            Some("js") => "application/javascript",
+            Some("svg") => "image/svg+xml",
+            Some("png") => "image/png",
+            _ => "application/octet-stream",
+        };
+        String::from(mime)
+    }
+
+    fn get_file_charset(&mut self, _path: &str) -> String {
+        String::from("utf-8")
+    }
+
+    fn open_file(&mut self, path: &str) -> Option<Vec<u8>> {
+        if Self::is_page(path) {
+            self.page(path).map(String::into_bytes)
+        } else {
+            std::fs::read(self.dir.join(path)).ok()
+        }
     }
 }
 
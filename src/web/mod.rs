@@ -1,68 +1,189 @@
+use bytes::{Bytes, BytesMut};
+use cosmic::iced::ContentFit;
 use cosmic::widget::image;
 
 pub mod engine;
-pub use engine::{Engine, PageType, PixelFormat, ViewId};
+pub use engine::{
+    BridgeFunction, ConsoleLevel, ConsoleMessage, Engine, EngineError, PageType, PixelFormat,
+    SchemeHandler, ViewEvent, ViewId,
+};
 
 mod webview;
 pub use view::{Action, WebView};
 pub use webview::view;
 
+#[cfg(test)]
+pub use engine::mock::MockEngine;
 pub use engine::ultralight::Ultralight;
 
-#[derive(Clone, Debug, PartialEq)]
+/// The last frame of a view as RGBA pixels, with the image handle drawing it.
+#[derive(Clone, Debug)]
 pub struct ImageInfo {
-    pixels: Vec<u8>,
+    pixels: Bytes,
     width: u32,
     height: u32,
+    // shares `pixels`, made once per frame so drawing doesn't upload the frame again
+    handle: image::Handle,
 }
 
-impl Default for ImageInfo {
-    fn default() -> Self {
+/// A rectangle of a frame in pixels, like the part of it that changed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// All of a `width` by `height` frame.
+    pub fn all(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// The part of the region inside a `width` by `height` frame.
+    fn clamp(self, width: u32, height: u32) -> Self {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
         Self {
-            pixels: vec![255; (Self::WIDTH as usize * Self::HEIGHT as usize) * 4],
-            width: Self::WIDTH,
-            height: Self::HEIGHT,
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
         }
     }
 }
 
+impl Default for ImageInfo {
+    fn default() -> Self {
+        Self::blank(Self::WIDTH, Self::HEIGHT)
+    }
+}
+
 impl ImageInfo {
     const WIDTH: u32 = 800;
     const HEIGHT: u32 = 800;
 
-    fn new(mut pixels: Vec<u8>, format: PixelFormat, width: u32, height: u32) -> Self {
-        assert_eq!(pixels.len() % 4, 0);
-
-        let pixels = match format {
-            PixelFormat::Rgba => pixels,
-            PixelFormat::Bgra => {
-                pixels.chunks_exact_mut(4).for_each(|chunk| {
-                    chunk.swap(0, 2);
-                });
-                pixels
-            }
-        };
-
+    fn from_pixels(pixels: Bytes, width: u32, height: u32) -> Self {
         Self {
+            handle: image::Handle::from_rgba(width, height, pixels.clone()),
             pixels,
             width,
             height,
         }
     }
 
+    /// The frame stretched over the widget, which is its size in logical pixels.
     fn as_image(&self) -> image::Image<image::Handle> {
-        image::Image::new(image::Handle::from_rgba(
-            self.width,
-            self.height,
-            self.pixels.clone(),
-        ))
+        image::Image::new(self.handle.clone()).content_fit(ContentFit::Fill)
     }
 
     fn blank(width: u32, height: u32) -> Self {
-        Self {
-            pixels: vec![255; (width as usize * height as usize) * 4],
-            width,
-            height,
+        let pixels = vec![255; (width as usize * height as usize) * 4];
+        Self::from_pixels(pixels.into(), width, height)
+    }
+
+    /// Copies the `dirty` part of a `width` by `height` frame, whose rows start `stride`
+    /// bytes apart in `source`. A frame of another size is copied whole.
+    ///
+    /// Returns whether the frame changed, the handle is only replaced when it did.
+    fn update(
+        &mut self,
+        source: &[u8],
+        format: PixelFormat,
+        stride: usize,
+        width: u32,
+        height: u32,
+        dirty: Region,
+    ) -> bool {
+        let resized = (width, height) != (self.width, self.height);
+        let dirty = if resized {
+            Region::all(width, height)
+        } else {
+            dirty.clamp(width, height)
+        };
+        if dirty.is_empty() && !resized {
+            return false;
         }
+
+        let mut pixels = if resized {
+            BytesMut::zeroed(width as usize * height as usize * 4)
+        } else {
+            // without the handle the buffer is usually ours alone and is reused, not copied
+            self.handle = image::Handle::from_rgba(0, 0, Bytes::new());
+            BytesMut::from(std::mem::take(&mut self.pixels))
+        };
+        let row_bytes = width as usize * 4;
+        let start = dirty.x as usize * 4;
+        let end = start + dirty.width as usize * 4;
+        for y in dirty.y as usize..(dirty.y + dirty.height) as usize {
+            let Some(from) = source.get(y * stride + start..y * stride + end) else {
+                break;
+            };
+            let to = &mut pixels[y * row_bytes + start..y * row_bytes + end];
+            to.copy_from_slice(from);
+            if matches!(format, PixelFormat::Bgra) {
+                to.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+            }
+        }
+
+        *self = Self::from_pixels(pixels.freeze(), width, height);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_skips_row_padding_and_swaps_channels() {
+        let mut frame = ImageInfo::blank(2, 2);
+        // two BGRA pixels per row, then four bytes of padding
+        let source = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+            9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+        ];
+
+        assert!(frame.update(&source, PixelFormat::Bgra, 12, 2, 2, Region::all(2, 2)));
+
+        assert_eq!(
+            &frame.pixels[..],
+            [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+
+    #[test]
+    fn update_copies_only_the_dirty_region() {
+        let mut frame = ImageInfo::blank(2, 2);
+        let handle = frame.handle.id();
+        let source = [0; 16];
+
+        assert!(!frame.update(&source, PixelFormat::Rgba, 8, 2, 2, Region::default()));
+        assert_eq!(frame.handle.id(), handle);
+
+        let dirty = Region {
+            x: 1,
+            y: 1,
+            width: 5,
+            height: 5,
+        };
+        assert!(frame.update(&source, PixelFormat::Rgba, 8, 2, 2, dirty));
+        assert_ne!(frame.handle.id(), handle);
+        assert_eq!(&frame.pixels[..12], [255; 12]);
+        assert_eq!(&frame.pixels[12..], [0; 4]);
+
+        assert!(frame.update(&[7; 12], PixelFormat::Rgba, 4, 1, 3, Region::default()));
+        assert_eq!((frame.width, frame.height), (1, 3));
+        assert_eq!(&frame.pixels[..], [7; 12]);
     }
 }
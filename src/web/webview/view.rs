@@ -15,24 +15,50 @@ use cosmic::iced::{Event, Length, Rectangle};
 use cosmic::iced::{Point, Size};
 use cosmic::theme::Theme;
 use cosmic::Element;
+use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 
-use crate::startpage;
-use crate::web::{engine, ImageInfo, PageType, ViewId};
+use crate::pages;
+use crate::web::{engine, ConsoleMessage, EngineError, ImageInfo, PageType, ViewEvent, ViewId};
+
+/// Update interval while the engine is busy and right after input.
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest an idle engine goes without an update.
+const MAX_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
 #[allow(missing_docs)]
 #[derive(Debug, Clone, PartialEq)]
 
 pub enum Action {
-    ChangeView(u32),
-    CloseView(u32),
+    ChangeView(ViewId),
+    CloseView(ViewId),
     CreateView(PageType),
+    EvaluateScript(String),
     GoBack,
     GoForward,
     GoToUrl(Url),
+    /// Routes an action from the inspector window's widget to the inspector view.
+    Inspector(Box<Action>),
+    OpenInspector,
+    CloseInspector,
+    /// Shows a page the browser generated in the current view.
+    LoadHtml(String),
     Refresh,
+    /// Reloads a view, which needn't be the current one.
+    RefreshView(ViewId),
+    /// Has the current view paint this many physical pixels per logical one, for the scale
+    /// factor of the window showing it.
+    Rescale(f64),
+    /// Scales a view like [`Action::Rescale`], which needn't be the current one.
+    RescaleView(ViewId, f64),
+    /// Changes view like [`Action::ChangeView`], to one laid out in a page area of this size,
+    /// like a view of another window than the current one's.
+    ShowView(ViewId, Size<u32>),
     SendKeyboardEvent(keyboard::Event),
     SendMouseEvent(mouse::Event, Point),
+    Stop,
     Update,
     Resize(Size<u32>),
 }
@@ -43,34 +69,19 @@ where
 {
     engine: Engine,
     view_size: Size<u32>,
-    current_view_index: Option<usize>,
-    view_ids: Vec<ViewId>,
-    on_close_view: Option<Message>,
-    on_create_view: Option<Message>,
-    on_url_change: Option<Box<dyn Fn(String) -> Message>>,
-    on_change_view: Option<Message>,
-    url: String,
-    on_title_change: Option<Box<dyn Fn(String) -> Message>>,
-    title: String,
-}
-
-impl<Engine: engine::Engine + Default, Message: Send + Clone + 'static> WebView<Engine, Message> {
-    fn get_current_view_id(&self) -> ViewId {
-        *self
-            .view_ids
-            .get(
-                self.current_view_index
-                    .expect("The current view index is not set."),
-            )
-            .expect("Could find view index for view, may be closed.")
-    }
-
-    fn index_as_view_id(&self, index: u32) -> usize {
-        *self
-            .view_ids
-            .get(index as usize)
-            .expect("Failed to find index")
-    }
+    current_view: Option<ViewId>,
+    // (inspected view, inspector view)
+    inspector: Option<(ViewId, ViewId)>,
+    inspector_size: Size<u32>,
+    on_close_view: Option<Box<dyn Fn(ViewId) -> Message>>,
+    on_create_view: Option<Box<dyn Fn(ViewId) -> Message>>,
+    on_change_view: Option<Box<dyn Fn(ViewId) -> Message>>,
+    on_view_event: Option<Box<dyn Fn(ViewId, ViewEvent) -> Message>>,
+    on_script_result: Option<Box<dyn Fn(Result<String, EngineError>) -> Message>>,
+    on_error: Option<Box<dyn Fn(EngineError) -> Message>>,
+    fallback_frame: ImageInfo,
+    // updates in a row that found the engine idle, see `update_interval`
+    idle_updates: u32,
 }
 
 impl<Engine: engine::Engine + Default, Message: Send + Clone + 'static> Default
@@ -83,15 +94,20 @@ impl<Engine: engine::Engine + Default, Message: Send + Clone + 'static> Default
                 width: 1920,
                 height: 1080,
             },
-            current_view_index: None,
-            view_ids: Vec::new(),
+            current_view: None,
+            inspector: None,
+            inspector_size: Size {
+                width: 1024,
+                height: 768,
+            },
             on_close_view: None,
             on_create_view: None,
-            on_url_change: None,
             on_change_view: None,
-            url: String::new(),
-            on_title_change: None,
-            title: String::new(),
+            on_view_event: None,
+            on_script_result: None,
+            on_error: None,
+            fallback_frame: ImageInfo::default(),
+            idle_updates: 0,
         }
     }
 }
@@ -101,187 +117,446 @@ impl<Engine: engine::Engine + Default, Message: Send + Clone + 'static> WebView<
         Self::default()
     }
 
-    pub fn on_create_view(mut self, on_create_view: Message) -> Self {
-        self.on_create_view = Some(on_create_view);
+    /// Receives the id of every view [`Action::CreateView`] opened.
+    pub fn on_create_view(mut self, on_create_view: impl Fn(ViewId) -> Message + 'static) -> Self {
+        self.on_create_view = Some(Box::new(on_create_view));
         self
     }
 
-    pub fn on_close_view(mut self, on_close_view: Message) -> Self {
-        self.on_close_view = Some(on_close_view);
+    pub fn on_close_view(mut self, on_close_view: impl Fn(ViewId) -> Message + 'static) -> Self {
+        self.on_close_view = Some(Box::new(on_close_view));
         self
     }
 
-    pub fn on_url_change(mut self, on_url_change: impl Fn(String) -> Message + 'static) -> Self {
-        self.on_url_change = Some(Box::new(on_url_change));
+    pub fn on_change_view(mut self, on_change_view: impl Fn(ViewId) -> Message + 'static) -> Self {
+        self.on_change_view = Some(Box::new(on_change_view));
         self
     }
 
-    pub fn on_change_view(mut self, on_change_view: Message) -> Self {
-        self.on_change_view = Some(on_change_view);
+    /// Forwards engine events for every view, tagged with the view they happened in.
+    pub fn on_view_event(
+        mut self,
+        on_view_event: impl Fn(ViewId, ViewEvent) -> Message + 'static,
+    ) -> Self {
+        self.on_view_event = Some(Box::new(on_view_event));
         self
     }
 
-    pub fn on_title_change(
+    /// Receives the JSON result of [`Action::EvaluateScript`] or the exception it threw.
+    pub fn on_script_result(
         mut self,
-        on_title_change: impl Fn(String) -> Message + 'static,
+        on_script_result: impl Fn(Result<String, EngineError>) -> Message + 'static,
     ) -> Self {
-        self.on_title_change = Some(Box::new(on_title_change));
+        self.on_script_result = Some(Box::new(on_script_result));
+        self
+    }
+
+    /// Receives the errors the engine runs into, which are dropped without it.
+    pub fn on_error(mut self, on_error: impl Fn(EngineError) -> Message + 'static) -> Self {
+        self.on_error = Some(Box::new(on_error));
         self
     }
 
     pub fn update(&mut self, action: Action) -> Task<Message> {
         let mut tasks = Vec::new();
 
-        if self.current_view_index.is_some() {
-            if let Some(on_url_change) = &self.on_url_change {
-                let url = self.engine.get_url(self.get_current_view_id());
-                if self.url != url {
-                    self.url = url.clone();
-                    tasks.push(cosmic::Task::done(on_url_change(url)).map(cosmic::Action::from))
+        let result = match action {
+            Action::ChangeView(id) => self.change_view(id, self.view_size, &mut tasks),
+            Action::ShowView(id, size) => self.change_view(id, size, &mut tasks),
+            Action::CloseView(id) => {
+                let mut result = self.engine.remove_view(id);
+                // whoever closed the current view picks the next one with `ChangeView`
+                if self.current_view == Some(id) {
+                    self.current_view = None;
                 }
-            }
-            if let Some(on_title_change) = &self.on_title_change {
-                let title = self.engine.get_title(self.get_current_view_id());
-                if self.title != title {
-                    self.title = title.clone();
-                    tasks.push(cosmic::Task::done(on_title_change(title)).map(cosmic::Action::from))
+                if matches!(self.inspector, Some((inspected, _)) if inspected == id) {
+                    result = result.and(self.close_inspector());
                 }
-            }
-        }
-
-        match action {
-            Action::ChangeView(index) => {
-                {
-                    self.view_size.width += 10;
-                    self.view_size.height -= 10;
-                    self.engine.resize(self.view_size);
-                    self.view_size.width -= 10;
-                    self.view_size.height += 10;
-                    self.engine.resize(self.view_size);
-                    self.engine
-                        .request_render(self.index_as_view_id(index), self.view_size);
-                }
-                self.current_view_index = Some(index as usize);
 
-                if let Some(on_change_view) = &self.on_change_view {
-                    tasks.push(cosmic::Task::done(on_change_view.clone()).map(cosmic::Action::from))
+                if let Some(on_close_view) = &self.on_close_view {
+                    tasks.push(cosmic::Task::done(on_close_view(id)).map(cosmic::Action::from))
                 }
+                result
             }
-            Action::CloseView(index) => {
-                let id = self.index_as_view_id(index);
-                self.view_ids.remove(index as usize);
-                self.engine.remove_view(id);
-
-                // only change view if current or lower is closed
-                if let Some(cur_idx) = self.current_view_index {
-                    if index as usize <= cur_idx {
-                        let cur_idx = if cur_idx == 0 { 0 } else { cur_idx - 1 };
-                        {
-                            self.view_size.width += 10;
-                            self.view_size.height -= 10;
-                            self.engine.resize(self.view_size);
-                            self.view_size.width -= 10;
-                            self.view_size.height += 10;
-                            self.engine.resize(self.view_size);
-                            self.engine.request_render(
-                                self.index_as_view_id((cur_idx) as u32),
-                                self.view_size,
-                            );
+            Action::CreateView(page_type) => {
+                let created = match self.engine.new_view(self.view_size, Some(page_type)) {
+                    // the tab is still expected, so open it on an error page instead
+                    Err(error @ EngineError::LoadFailed { .. }) => {
+                        let created = self.engine.new_view(self.view_size, None);
+                        if let Ok(id) = created {
+                            if let Err(unshown) = self.show_error_page(id, &error) {
+                                tasks.extend(self.error_task(unshown));
+                            }
+                            tasks.extend(self.error_task(error));
                         }
-                        self.current_view_index = Some((cur_idx) as usize);
+                        created
                     }
-                }
+                    created => created,
+                };
 
-                if let Some(on_close_view) = &self.on_close_view {
-                    tasks.push(cosmic::Task::done(on_close_view.clone()).map(cosmic::Action::from))
-                }
+                created.map(|id| {
+                    if let Some(on_create_view) = &self.on_create_view {
+                        tasks.push(cosmic::Task::done(on_create_view(id)).map(cosmic::Action::from))
+                    }
+                })
             }
-            Action::CreateView(page_type) => {
-                let id = self.engine.new_view(self.view_size, Some(page_type));
-                self.view_ids.push(id);
-
-                if let Some(on_create_view) = &self.on_create_view {
-                    tasks.push(cosmic::Task::done(on_create_view.clone()).map(cosmic::Action::from))
+            Action::EvaluateScript(source) => match self.current_view {
+                Some(id) => {
+                    let result = self.evaluate_script(id, &source);
+                    match &self.on_script_result {
+                        Some(on_script_result) => {
+                            tasks.push(
+                                cosmic::Task::done(on_script_result(result))
+                                    .map(cosmic::Action::from),
+                            );
+                            Ok(())
+                        }
+                        None => result.map(|_| ()),
+                    }
                 }
-            }
-            Action::GoBack => {
-                self.engine.go_back(self.get_current_view_id());
-            }
-            Action::GoForward => {
-                self.engine.go_forward(self.get_current_view_id());
-            }
+                None => Ok(()),
+            },
+            Action::GoBack => self.on_current(|engine, id| engine.go_back(id)),
+            Action::GoForward => self.on_current(|engine, id| engine.go_forward(id)),
             Action::GoToUrl(url) => {
-                self.engine
-                    .goto(self.get_current_view_id(), PageType::Url(url.to_string()));
+                self.on_current(|engine, id| engine.goto(id, PageType::Url(url.to_string())))
             }
-            Action::Refresh => {
-                self.engine.refresh(self.get_current_view_id());
+            Action::Inspector(action) => match (*action, self.inspector) {
+                (Action::SendKeyboardEvent(event), Some((_, inspector))) => {
+                    self.engine.handle_keyboard_event(inspector, event)
+                }
+                (Action::SendMouseEvent(event, point), Some((_, inspector))) => {
+                    self.engine.handle_mouse_event(inspector, point, event)
+                }
+                (Action::Resize(size), Some((_, inspector))) => {
+                    self.inspector_size = size;
+                    self.engine.resize_view(inspector, size)
+                }
+                (Action::Rescale(scale), Some((_, inspector))) => {
+                    self.engine.set_scale(inspector, scale)
+                }
+                _ => Ok(()),
+            },
+            Action::OpenInspector => match (self.inspector, self.current_view) {
+                (None, Some(inspected)) => self
+                    .engine
+                    .open_inspector(inspected, self.inspector_size)
+                    .map(|inspector| self.inspector = Some((inspected, inspector))),
+                _ => Ok(()),
+            },
+            Action::CloseInspector => self.close_inspector(),
+            Action::LoadHtml(html) => {
+                self.on_current(|engine, id| engine.goto(id, PageType::Html(html)))
             }
+            Action::Refresh => self.on_current(|engine, id| engine.refresh(id)),
+            Action::RefreshView(id) => self.engine.refresh(id),
+            Action::Rescale(scale) => self.on_current(|engine, id| engine.set_scale(id, scale)),
+            Action::RescaleView(id, scale) => self.engine.set_scale(id, scale),
             Action::SendKeyboardEvent(event) => {
-                self.engine
-                    .handle_keyboard_event(self.get_current_view_id(), event);
+                self.on_current(|engine, id| engine.handle_keyboard_event(id, event))
             }
-            Action::SendMouseEvent(point, event) => {
-                self.engine
-                    .handle_mouse_event(self.get_current_view_id(), event, point);
+            Action::SendMouseEvent(event, point) => {
+                self.on_current(|engine, id| engine.handle_mouse_event(id, point, event))
             }
+            Action::Stop => self.on_current(|engine, id| engine.stop(id)),
             Action::Update => {
                 self.engine.update();
-                if self.current_view_index.is_some() {
-                    self.engine
-                        .request_render(self.get_current_view_id(), self.view_size);
+                if let Err(error) = self.engine.render() {
+                    tasks.extend(self.error_task(error));
+                }
+                for error in self.engine.take_errors() {
+                    tasks.extend(self.error_task(error));
                 }
+                if self.engine.is_busy() {
+                    self.idle_updates = 0;
+                } else {
+                    self.idle_updates = self.idle_updates.saturating_add(1);
+                }
+                self.forward_events(&mut tasks);
                 return Task::batch(tasks);
             }
             Action::Resize(size) => {
                 self.view_size = size;
-                self.engine.resize(size);
+                self.on_current(|engine, id| engine.resize_view(id, size))
             }
         };
 
-        if self.current_view_index.is_some() {
-            self.engine
-                .request_render(self.get_current_view_id(), self.view_size);
+        if let Err(error) = result {
+            tasks.extend(self.report(error));
+        }
+        // whatever the user did may have started work in the page
+        self.idle_updates = 0;
+
+        if let Err(error) = self.engine.render() {
+            tasks.extend(self.report(error));
         }
 
+        self.forward_events(&mut tasks);
         Task::batch(tasks)
     }
 
+    /// Shows the current view, or a blank frame until one is chosen with [`Action::ChangeView`].
     pub fn view(&self) -> Element<Action> {
-        WebViewWidget::new(
-            self.engine.get_view(self.get_current_view_id()),
-            self.engine.get_cursor(self.get_current_view_id()),
-        )
+        match self.current_view {
+            Some(id) => WebViewWidget::new(
+                self.engine.get_view(id).unwrap_or(&self.fallback_frame),
+                self.engine.get_cursor(id),
+                self.view_size,
+            ),
+            None => WebViewWidget::new(&self.fallback_frame, Interaction::Idle, self.view_size),
+        }
         .into()
     }
 
-    pub fn init(&mut self) {
-        let id = self.engine.new_view(
-            self.view_size,
-            // TODO: put a homepage app here
-            Some(PageType::Html(startpage::get_startpage())),
-        );
-        self.view_ids.push(id);
-        self.current_view_index = Some(0);
+    /// Draws view `id` like [`WebView::view`] shows the current one, for pages on screen in
+    /// windows without focus. It neither takes input nor asks to be resized.
+    pub fn view_of(&self, id: ViewId) -> Element<Action> {
+        WebViewWidget {
+            interactive: false,
+            ..WebViewWidget::new(
+                self.engine.get_view(id).unwrap_or(&self.fallback_frame),
+                Interaction::Idle,
+                self.view_size,
+            )
+        }
+        .into()
+    }
+
+    /// The inspector opened by [`Action::OpenInspector`], its input is routed back to it.
+    pub fn inspector_view(&self) -> Option<Element<Action>> {
+        let (_, inspector) = self.inspector?;
+        let widget: Element<Action> = WebViewWidget::new(
+            self.engine
+                .get_view(inspector)
+                .unwrap_or(&self.fallback_frame),
+            self.engine.get_cursor(inspector),
+            self.inspector_size,
+        )
+        .into();
+        Some(widget.map(|action| Action::Inspector(Box::new(action))))
+    }
+
+    pub fn has_inspector(&self) -> bool {
+        self.inspector.is_some()
+    }
+
+    /// Exposes `function` as `window.astrolabe.<name>` on trusted internal pages.
+    pub fn register_bridge_function(
+        &mut self,
+        name: &str,
+        function: impl Fn(&[String]) -> String + Send + Sync + 'static,
+    ) {
+        self.engine
+            .register_bridge_function(name, Arc::new(function));
+    }
+
+    /// Serves the pages at `scheme://` addresses with `handler`, see
+    /// [`engine::Engine::register_scheme`].
+    pub fn register_scheme(
+        &mut self,
+        scheme: &str,
+        handler: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) {
+        self.engine.register_scheme(scheme, Arc::new(handler));
+    }
+
+    /// Runs `source` in the view, see [`engine::Engine::evaluate_script`].
+    pub fn evaluate_script(&mut self, id: ViewId, source: &str) -> Result<String, EngineError> {
+        self.engine.evaluate_script(id, source)
+    }
+
+    /// Console messages of the current view, oldest first.
+    pub fn console_messages(&self) -> Vec<ConsoleMessage> {
+        self.current_view
+            .and_then(|id| self.engine.console_messages(id).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn clear_console(&mut self) -> Result<(), EngineError> {
+        self.on_current(|engine, id| engine.clear_console(id))
+    }
+
+    /// The view shown by [`WebView::view`], if one was chosen.
+    pub fn current_view(&self) -> Option<ViewId> {
+        self.current_view
+    }
+
+    pub fn get_current_view_url(&self) -> String {
+        self.current_view
+            .map(|id| self.get_url(id))
+            .unwrap_or_default()
     }
 
     pub fn get_current_view_title(&self) -> String {
-        self.engine.get_title(self.get_current_view_id())
+        self.current_view
+            .map(|id| self.get_title(id))
+            .unwrap_or_default()
     }
 
-    pub fn get_view_title(&self, index: u32) -> String {
-        self.engine.get_title(self.index_as_view_id(index))
+    /// Whether the engine has work for every frame, like a page loading or animating.
+    pub fn is_busy(&self) -> bool {
+        self.engine.is_busy()
+    }
+
+    /// How long until the next [`Action::Update`] is due.
+    ///
+    /// The engine only runs page timers and network callbacks while updated, so idle pages
+    /// are still updated, less often the longer they stay idle.
+    pub fn update_interval(&self) -> Duration {
+        if self.engine.is_busy() {
+            return MIN_UPDATE_INTERVAL;
+        }
+        MIN_UPDATE_INTERVAL
+            .saturating_mul(1 << self.idle_updates.min(8))
+            .min(MAX_UPDATE_INTERVAL)
+    }
+
+    /// Whether view `id` has a page to go back to.
+    pub fn view_can_go_back(&self, id: ViewId) -> bool {
+        self.engine.can_go_back(id).unwrap_or_default()
+    }
+
+    pub fn view_can_go_forward(&self, id: ViewId) -> bool {
+        self.engine.can_go_forward(id).unwrap_or_default()
+    }
+
+    /// Whether view `id` is loading a page.
+    pub fn view_is_loading(&self, id: ViewId) -> bool {
+        self.engine.is_loading(id).unwrap_or_default()
+    }
+
+    pub fn get_url(&self, id: ViewId) -> String {
+        self.engine.get_url(id).unwrap_or_default()
+    }
+
+    pub fn get_title(&self, id: ViewId) -> String {
+        self.engine.get_title(id).unwrap_or_default()
+    }
+
+    #[cfg(test)]
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    #[cfg(test)]
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Runs `f` against the current view, doing nothing while there is none.
+    fn on_current(
+        &mut self,
+        f: impl FnOnce(&mut Engine, ViewId) -> Result<(), EngineError>,
+    ) -> Result<(), EngineError> {
+        match self.current_view {
+            Some(id) => f(&mut self.engine, id),
+            None => Ok(()),
+        }
+    }
+
+    /// Shows view `id` from now on, laid out in a page area of `size`.
+    fn change_view(
+        &mut self,
+        id: ViewId,
+        size: Size<u32>,
+        tasks: &mut Vec<Task<Message>>,
+    ) -> Result<(), EngineError> {
+        // views left in the background catch up with resizes once shown again
+        let result = self
+            .engine
+            .resize_view(id, size)
+            .and_then(|()| self.engine.request_render(id));
+        if result.is_ok() {
+            self.current_view = Some(id);
+            self.view_size = size;
+            if let Some(on_change_view) = &self.on_change_view {
+                tasks.push(cosmic::Task::done(on_change_view(id)).map(cosmic::Action::from))
+            }
+        }
+        result
+    }
+
+    fn close_inspector(&mut self) -> Result<(), EngineError> {
+        match self.inspector.take() {
+            Some((_, inspector)) => self.engine.remove_view(inspector),
+            None => Ok(()),
+        }
+    }
+
+    fn forward_events(&mut self, tasks: &mut Vec<Task<Message>>) {
+        for (id, event) in self.engine.take_events() {
+            // the inspector is not a page the app knows about
+            if matches!(self.inspector, Some((_, inspector)) if inspector == id) {
+                continue;
+            }
+
+            if let ViewEvent::FailLoading {
+                url, description, ..
+            } = &event
+            {
+                let error = EngineError::LoadFailed {
+                    url: url.clone(),
+                    reason: description.clone(),
+                };
+                if let Err(unshown) = self.show_error_page(id, &error) {
+                    tasks.extend(self.error_task(unshown));
+                }
+            }
+
+            if let Some(on_view_event) = &self.on_view_event {
+                tasks.push(cosmic::Task::done(on_view_event(id, event)).map(cosmic::Action::from));
+            }
+        }
+    }
+
+    /// Turns an engine error into an error page for failed loads and a message for the app.
+    fn report(&mut self, error: EngineError) -> Option<Task<Message>> {
+        let unshown = match (&error, self.current_view) {
+            (EngineError::LoadFailed { .. }, Some(id)) => self.show_error_page(id, &error).err(),
+            _ => None,
+        };
+        let tasks: Vec<_> = [Some(error), unshown]
+            .into_iter()
+            .flatten()
+            .filter_map(|error| self.error_task(error))
+            .collect();
+        Some(Task::batch(tasks))
+    }
+
+    /// Shows why the load failed in view `id`, for a [`EngineError::LoadFailed`] `error`.
+    fn show_error_page(&mut self, id: ViewId, error: &EngineError) -> Result<(), EngineError> {
+        match error {
+            EngineError::LoadFailed { url, reason } => {
+                let page = PageType::Html(pages::error(url, reason));
+                self.engine.goto(id, page)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn error_task(&self, error: EngineError) -> Option<Task<Message>> {
+        let on_error = self.on_error.as_ref()?;
+        Some(cosmic::Task::done(on_error(error)).map(cosmic::Action::from))
     }
 }
 
 struct WebViewWidget<'a> {
     image_info: &'a ImageInfo,
     cursor: Interaction,
+    // the view's size in logical pixels, its frame is in physical ones
+    size: Size<u32>,
+    // sends input and resizes, views of windows without focus are only drawn
+    interactive: bool,
 }
 
 impl<'a> WebViewWidget<'a> {
-    fn new(image_info: &'a ImageInfo, cursor: Interaction) -> Self {
-        Self { image_info, cursor }
+    fn new(image_info: &'a ImageInfo, cursor: Interaction, size: Size<u32>) -> Self {
+        Self {
+            image_info,
+            cursor,
+            size,
+            interactive: true,
+        }
     }
 }
 
@@ -339,8 +614,11 @@ where
         shell: &mut Shell<'_, Action>,
         _viewport: &Rectangle,
     ) -> event::Status {
+        if !self.interactive {
+            return Status::Ignored;
+        }
         let size = Size::new(layout.bounds().width as u32, layout.bounds().height as u32);
-        if self.image_info.width != size.width || self.image_info.height != size.height {
+        if self.size != size {
             shell.publish(Action::Resize(size));
         }
 
@@ -384,3 +662,436 @@ where
         Self::new(widget)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::engine::ConsoleLog;
+    use crate::web::{ConsoleLevel, MockEngine};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const START_PAGE: &str = "<title>New Tab</title>";
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Message {
+        Created,
+        Closed,
+        Event,
+    }
+
+    /// A web view with a start page and `count - 1` sites, showing the start page.
+    fn webview_with_views(count: usize) -> (WebView<MockEngine, Message>, Vec<ViewId>) {
+        let mut webview = WebView::new()
+            .on_create_view(|_| Message::Created)
+            .on_close_view(|_| Message::Closed);
+        let _ = webview.update(Action::CreateView(PageType::Html(START_PAGE.to_string())));
+        for i in 1..count {
+            let _ = webview.update(Action::CreateView(PageType::Url(format!(
+                "https://example.com/{i}"
+            ))));
+        }
+        let ids = webview.engine.view_ids();
+        let _ = webview.update(Action::ChangeView(ids[0]));
+        (webview, ids)
+    }
+
+    fn current_frame_owner(webview: &WebView<MockEngine, Message>) -> Option<ViewId> {
+        webview
+            .engine
+            .get_view(webview.current_view()?)
+            .ok()
+            .and_then(MockEngine::frame_owner)
+    }
+
+    #[test]
+    fn no_view_is_shown_until_one_is_chosen() {
+        let mut webview: WebView<MockEngine, Message> = WebView::new();
+
+        let _ = webview.update(Action::GoBack);
+        let _ = webview.update(Action::Update);
+        let _ = webview.update(Action::CreateView(PageType::Html(START_PAGE.to_string())));
+
+        assert_eq!(webview.current_view(), None);
+        assert_eq!(webview.get_current_view_title(), "");
+        assert_eq!(webview.engine.navigations().len(), 1);
+    }
+
+    #[test]
+    fn create_view_keeps_current_view() {
+        let (webview, ids) = webview_with_views(3);
+
+        assert_eq!(ids.len(), 3);
+        assert_eq!(webview.current_view(), Some(ids[0]));
+        assert_eq!(webview.get_current_view_title(), "New Tab");
+        assert_eq!(webview.get_title(ids[2]), "https://example.com/2");
+    }
+
+    #[test]
+    fn view_ids_are_not_reused() {
+        let (mut webview, ids) = webview_with_views(2);
+
+        let _ = webview.update(Action::CloseView(ids[1]));
+        let _ = webview.update(Action::CreateView(PageType::Url(String::from(
+            "https://example.org/",
+        ))));
+
+        let new = *webview.engine.view_ids().last().unwrap();
+        assert!(!ids.contains(&new));
+    }
+
+    #[test]
+    fn change_view_renders_requested_view() {
+        let (mut webview, ids) = webview_with_views(3);
+
+        let _ = webview.update(Action::ChangeView(ids[2]));
+
+        assert_eq!(webview.current_view(), Some(ids[2]));
+        assert_eq!(webview.engine.renders().last(), Some(&ids[2]));
+        assert_eq!(current_frame_owner(&webview), Some(ids[2]));
+    }
+
+    #[test]
+    fn change_to_unknown_view_keeps_current_view() {
+        let (mut webview, ids) = webview_with_views(2);
+
+        let _ = webview.update(Action::ChangeView(ids[1] + 100));
+
+        assert_eq!(webview.current_view(), Some(ids[0]));
+    }
+
+    #[test]
+    fn close_other_view_keeps_current_view() {
+        let (mut webview, ids) = webview_with_views(3);
+        let _ = webview.update(Action::ChangeView(ids[2]));
+
+        let _ = webview.update(Action::CloseView(ids[0]));
+
+        assert_eq!(webview.current_view(), Some(ids[2]));
+        assert_eq!(webview.engine.view_ids(), vec![ids[1], ids[2]]);
+        assert_eq!(current_frame_owner(&webview), Some(ids[2]));
+    }
+
+    #[test]
+    fn close_current_view_leaves_choice_to_caller() {
+        let (mut webview, ids) = webview_with_views(3);
+        let _ = webview.update(Action::ChangeView(ids[2]));
+
+        let _ = webview.update(Action::CloseView(ids[2]));
+        assert_eq!(webview.current_view(), None);
+
+        let _ = webview.update(Action::ChangeView(ids[1]));
+        assert_eq!(webview.current_view(), Some(ids[1]));
+        assert_eq!(current_frame_owner(&webview), Some(ids[1]));
+    }
+
+    #[test]
+    fn events_are_forwarded_for_background_views() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let log = events.clone();
+        let (webview, ids) = webview_with_views(2);
+        let mut webview = webview.on_view_event(move |id, event| {
+            log.borrow_mut().push((id, event));
+            Message::Event
+        });
+        webview
+            .engine
+            .set_url(ids[1], "https://example.com/redirected");
+        webview.engine.set_title(ids[1], "Redirected");
+
+        let _ = webview.update(Action::Update);
+
+        assert_eq!(webview.current_view(), Some(ids[0]));
+        assert_eq!(
+            events.borrow()[events.borrow().len() - 2..],
+            [
+                (
+                    ids[1],
+                    ViewEvent::UrlChanged(String::from("https://example.com/redirected"))
+                ),
+                (ids[1], ViewEvent::TitleChanged(String::from("Redirected"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn scaled_views_paint_physical_pixels() {
+        let (mut webview, ids) = webview_with_views(2);
+
+        let _ = webview.update(Action::Resize(Size::new(400, 300)));
+        let _ = webview.update(Action::Rescale(1.5));
+        let _ = webview.update(Action::RescaleView(ids[1], 2.0));
+
+        let frame = webview.engine.get_view(ids[0]).unwrap();
+        assert_eq!((frame.width, frame.height), (600, 450));
+        assert_eq!(webview.engine.mock_view(ids[1]).unwrap().scale, 2.0);
+    }
+
+    #[test]
+    fn idle_engine_is_updated_less_and_less_often() {
+        let (mut webview, ids) = webview_with_views(1);
+
+        let _ = webview.update(Action::Update);
+        assert!(!webview.is_busy());
+        let first = webview.update_interval();
+        for _ in 0..20 {
+            let _ = webview.update(Action::Update);
+        }
+        assert!(first > MIN_UPDATE_INTERVAL);
+        assert_eq!(webview.update_interval(), MAX_UPDATE_INTERVAL);
+
+        webview
+            .engine
+            .begin_loading(ids[0], "https://example.com/slow");
+        let _ = webview.update(Action::Update);
+        assert!(webview.is_busy());
+        assert_eq!(webview.update_interval(), MIN_UPDATE_INTERVAL);
+
+        let _ = webview.update(Action::Stop);
+        assert!(!webview.is_busy());
+        assert_eq!(webview.update_interval(), MIN_UPDATE_INTERVAL);
+    }
+
+    #[test]
+    fn navigation_reports_loading_events() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let log = events.clone();
+        let (webview, ids) = webview_with_views(1);
+        let mut webview = webview.on_view_event(move |id, event| {
+            log.borrow_mut().push((id, event));
+            Message::Event
+        });
+        let _ = webview.update(Action::Update);
+        events.borrow_mut().clear();
+
+        let _ = webview.update(Action::GoToUrl(Url::parse("https://example.org/").unwrap()));
+
+        let events = events.borrow();
+        assert_eq!(
+            events.first(),
+            Some(&(
+                ids[0],
+                ViewEvent::BeginLoading {
+                    url: String::from("https://example.org/")
+                }
+            ))
+        );
+        assert!(events.contains(&(ids[0], ViewEvent::FinishLoading)));
+    }
+
+    #[test]
+    fn background_views_are_resized_once_shown() {
+        let (mut webview, ids) = webview_with_views(2);
+        let size = Size::new(640, 480);
+
+        let _ = webview.update(Action::Resize(size));
+        assert_eq!(webview.engine.mock_view(ids[0]).unwrap().size, size);
+        assert_ne!(webview.engine.mock_view(ids[1]).unwrap().size, size);
+
+        let _ = webview.update(Action::ChangeView(ids[1]));
+        assert_eq!(webview.engine.mock_view(ids[1]).unwrap().size, size);
+        let frame = webview.engine.get_view(ids[1]).unwrap();
+        assert_eq!((frame.width, frame.height), (640, 480));
+    }
+
+    #[test]
+    fn shown_views_take_the_size_they_are_shown_at() {
+        let (mut webview, ids) = webview_with_views(2);
+        let size = Size::new(800, 600);
+
+        let _ = webview.update(Action::ShowView(ids[1], size));
+        assert_eq!(webview.current_view(), Some(ids[1]));
+        assert_eq!(webview.engine.mock_view(ids[1]).unwrap().size, size);
+
+        // switching tabs in the same window keeps its size
+        let _ = webview.update(Action::ChangeView(ids[0]));
+        assert_eq!(webview.engine.mock_view(ids[0]).unwrap().size, size);
+    }
+
+    #[test]
+    fn navigation_targets_current_view() {
+        let (mut webview, ids) = webview_with_views(2);
+        let _ = webview.update(Action::ChangeView(ids[1]));
+        let url = Url::parse("https://example.org/").unwrap();
+
+        let _ = webview.update(Action::GoToUrl(url.clone()));
+        let _ = webview.update(Action::GoBack);
+
+        assert_eq!(
+            webview.engine.navigations().last(),
+            Some(&(ids[1], PageType::Url(url.to_string())))
+        );
+        assert_eq!(webview.get_url(ids[1]), "https://example.com/1");
+    }
+
+    #[test]
+    fn history_state_follows_current_view() {
+        let (mut webview, ids) = webview_with_views(2);
+        let _ = webview.update(Action::ChangeView(ids[1]));
+        assert!(!webview.view_can_go_back(ids[1]));
+
+        let url = Url::parse("https://example.org/").unwrap();
+        let _ = webview.update(Action::GoToUrl(url));
+        assert!(webview.view_can_go_back(ids[1]));
+        assert!(!webview.view_can_go_forward(ids[1]));
+
+        let _ = webview.update(Action::GoBack);
+        assert!(!webview.view_can_go_back(ids[1]));
+        assert!(webview.view_can_go_forward(ids[1]));
+
+        let _ = webview.update(Action::ChangeView(ids[0]));
+        assert!(!webview.view_can_go_forward(ids[0]));
+    }
+
+    #[test]
+    fn stop_cancels_current_load() {
+        let (mut webview, ids) = webview_with_views(2);
+        webview.engine.begin_loading(ids[0], "https://example.org/");
+        webview.engine.begin_loading(ids[1], "https://example.net/");
+        assert!(webview.view_is_loading(ids[0]));
+
+        let _ = webview.update(Action::Stop);
+
+        assert!(!webview.view_is_loading(ids[0]));
+        assert!(webview.engine.mock_view(ids[1]).unwrap().loading);
+    }
+
+    #[test]
+    fn evaluate_script_reports_result_for_current_view() {
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let log = results.clone();
+        let (webview, ids) = webview_with_views(2);
+        let mut webview = webview.on_script_result(move |result| {
+            log.borrow_mut().push(result);
+            Message::Event
+        });
+        let _ = webview.update(Action::ChangeView(ids[1]));
+        webview
+            .engine
+            .set_script_result("document.title", Ok("\"Example\""));
+        webview
+            .engine
+            .set_script_result("missing()", Err("ReferenceError"));
+
+        let _ = webview.update(Action::EvaluateScript(String::from("document.title")));
+        let _ = webview.update(Action::EvaluateScript(String::from("missing()")));
+
+        assert_eq!(
+            *results.borrow(),
+            [
+                Ok(String::from("\"Example\"")),
+                Err(EngineError::Script(String::from("ReferenceError"))),
+            ]
+        );
+        assert_eq!(
+            webview.engine.scripts()[0],
+            (ids[1], String::from("document.title"))
+        );
+    }
+
+    #[test]
+    fn bridge_is_only_exposed_to_internal_pages() {
+        let (mut webview, ids) = webview_with_views(2);
+        webview.register_bridge_function("echo", |args| args.join(","));
+
+        assert_eq!(
+            webview.engine.call_bridge(ids[0], "echo", &["1", "\"a\""]),
+            Some(String::from("1,\"a\""))
+        );
+        assert_eq!(webview.engine.call_bridge(ids[1], "echo", &["1"]), None);
+
+        // a blank page the internal page navigates to is not ours
+        webview.engine.begin_loading(ids[0], "about:blank");
+        assert_eq!(webview.engine.call_bridge(ids[0], "echo", &["1"]), None);
+    }
+
+    #[test]
+    fn console_keeps_most_recent_messages_per_view() {
+        let (mut webview, ids) = webview_with_views(2);
+        let message = |line| ConsoleMessage {
+            level: ConsoleLevel::Log,
+            source: String::from("https://example.com/app.js"),
+            line,
+            message: format!("line {line}"),
+        };
+        for line in 0..ConsoleLog::CAPACITY as u32 + 10 {
+            webview.engine.log(ids[0], message(line));
+        }
+
+        let messages = webview.console_messages();
+        assert_eq!(messages.len(), ConsoleLog::CAPACITY);
+        assert_eq!(messages.first(), Some(&message(10)));
+
+        let _ = webview.update(Action::ChangeView(ids[1]));
+        assert!(webview.console_messages().is_empty());
+    }
+
+    #[test]
+    fn failed_load_shows_error_page() {
+        let (mut webview, _) = webview_with_views(1);
+        let url = Url::parse("https://unreachable.example/").unwrap();
+        webview.engine.fail_url(url.as_str());
+
+        let _ = webview.update(Action::GoToUrl(url));
+
+        assert_eq!(webview.get_current_view_title(), "Problem loading page");
+    }
+
+    #[test]
+    fn failed_create_view_still_opens_tab() {
+        let (mut webview, _) = webview_with_views(1);
+        webview.engine.fail_url("https://unreachable.example/");
+
+        let _ = webview.update(Action::CreateView(PageType::Url(String::from(
+            "https://unreachable.example/",
+        ))));
+
+        let ids = webview.engine.view_ids();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(webview.get_title(ids[1]), "Problem loading page");
+    }
+
+    #[test]
+    fn inspector_is_sized_on_its_own() {
+        let (mut webview, ids) = webview_with_views(2);
+        let _ = webview.update(Action::ChangeView(ids[1]));
+
+        let _ = webview.update(Action::OpenInspector);
+        let (inspected, inspector) = webview.inspector.unwrap();
+        let resize = Action::Resize(Size::new(800, 600));
+        let _ = webview.update(Action::Inspector(Box::new(resize)));
+        let _ = webview.update(Action::Resize(Size::new(640, 480)));
+        let _ = webview.update(Action::Update);
+
+        assert_eq!(inspected, ids[1]);
+        assert_eq!(webview.current_view(), Some(ids[1]));
+        let view = webview.engine.mock_view(inspector).unwrap();
+        assert!(view.inspector);
+        assert_eq!(view.size, Size::new(800, 600));
+        assert!(webview.engine.renders().contains(&inspector));
+    }
+
+    #[test]
+    fn closing_inspected_view_closes_inspector() {
+        let (mut webview, ids) = webview_with_views(2);
+        let _ = webview.update(Action::OpenInspector);
+        let (_, inspector) = webview.inspector.unwrap();
+
+        let _ = webview.update(Action::CloseView(ids[0]));
+
+        assert!(!webview.has_inspector());
+        assert!(webview.engine.mock_view(inspector).is_none());
+    }
+
+    #[test]
+    fn closed_view_is_an_error() {
+        let (mut webview, ids) = webview_with_views(2);
+
+        let _ = webview.update(Action::CloseView(ids[1]));
+
+        assert_eq!(
+            webview.engine.get_url(ids[1]),
+            Err(EngineError::UnknownView(ids[1]))
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT
+
+//! What each browser window has of its own: its tabs, address bar, find bar and where it
+//! is on screen.
+
+use cosmic::iced::Size;
+use cosmic::widget::{self, icon, nav_bar};
+
+use crate::omnibox;
+use crate::session;
+use crate::web;
+
+/// The tabs and bars of one browser window.
+pub struct BrowserWindow {
+    /// Tabs, in the nav bar for the main window and a tab bar for the others.
+    pub nav: nav_bar::Model,
+    /// Text of the address bar, `None` without tabs.
+    pub url: Option<String>,
+    /// Pages offered under the address bar while typing.
+    pub suggestions: omnibox::Suggestions,
+    pub search_id: widget::Id,
+    /// Text searched for in the page while the find bar is open.
+    pub find: Option<String>,
+    pub find_id: widget::Id,
+    /// The last search found nothing.
+    pub find_failed: bool,
+    /// Size of the page area in logical pixels, once laid out.
+    pub view_size: Option<Size<u32>>,
+    /// Where the window is on screen, as last reported.
+    pub geometry: Option<session::Geometry>,
+    /// Physical pixels per logical one, 1 until the window says otherwise.
+    pub scale: f64,
+    /// Keeps its tabs out of the history, the session and the site icons.
+    pub private: bool,
+}
+
+impl BrowserWindow {
+    pub fn new() -> Self {
+        Self {
+            nav: nav_bar::Model::default(),
+            url: None,
+            suggestions: omnibox::Suggestions::default(),
+            search_id: widget::Id::unique(),
+            find: None,
+            find_id: widget::Id::unique(),
+            find_failed: false,
+            view_size: None,
+            geometry: None,
+            scale: 1.0,
+            private: false,
+        }
+    }
+
+    /// The view of the active tab.
+    pub fn view(&self) -> Option<web::ViewId> {
+        self.nav.active_data::<web::ViewId>().copied()
+    }
+
+    /// The tabs with the views they show, in tab bar order.
+    pub fn tabs(&self) -> Vec<(nav_bar::Id, web::ViewId)> {
+        self.nav
+            .iter()
+            .filter_map(|tab| Some((tab, *self.nav.data::<web::ViewId>(tab)?)))
+            .collect()
+    }
+
+    /// The tab showing `view`.
+    pub fn tab_for_view(&self, view: web::ViewId) -> Option<nav_bar::Id> {
+        self.nav
+            .iter()
+            .find(|&tab| self.nav.data::<web::ViewId>(tab) == Some(&view))
+    }
+
+    /// Adds a tab for `view` at the end.
+    pub fn insert_tab(
+        &mut self,
+        view: web::ViewId,
+        title: String,
+        icon: icon::Icon,
+    ) -> nav_bar::Id {
+        self.nav
+            .insert()
+            .text(title.clone())
+            .data(TabTitle(title))
+            .data::<web::ViewId>(view)
+            .icon(icon)
+            .closable()
+            .id()
+    }
+
+    /// The title of the page `tab` shows, which pinned tabs keep out of the nav bar.
+    pub fn tab_title(&self, tab: nav_bar::Id) -> &str {
+        self.nav
+            .data::<TabTitle>(tab)
+            .map_or("", |title| title.0.as_str())
+    }
+
+    /// The title of the page the active tab shows.
+    pub fn active_title(&self) -> &str {
+        self.tab_title(self.nav.active())
+    }
+
+    pub fn set_tab_title(&mut self, tab: nav_bar::Id, title: String) {
+        let text = if self.is_pinned(tab) {
+            String::new()
+        } else {
+            title.clone()
+        };
+        self.nav.text_set(tab, text);
+        self.nav.data_set(tab, TabTitle(title));
+    }
+
+    pub fn is_pinned(&self, tab: nav_bar::Id) -> bool {
+        self.nav.data::<Pinned>(tab).is_some()
+    }
+
+    /// Pins `tab` to the end of the pinned tabs, or unpins it to just after them.
+    pub fn set_pinned(&mut self, tab: nav_bar::Id, pinned: bool) {
+        if pinned {
+            self.nav.data_set(tab, Pinned);
+        } else {
+            self.nav.data_remove::<Pinned>(tab);
+        }
+        self.nav.closable_set(tab, !pinned);
+        let title = self.tab_title(tab).to_string();
+        self.set_tab_title(tab, title);
+        let position = self.nav.position(tab).map_or(0, usize::from);
+        self.move_tab(tab, position);
+    }
+
+    /// Moves `tab` to `position`, or as close as it gets while pinned tabs stay on top.
+    pub fn move_tab(&mut self, tab: nav_bar::Id, position: usize) {
+        let pinned = self
+            .nav
+            .iter()
+            .filter(|&other| other != tab && self.is_pinned(other))
+            .count();
+        let last = self.nav.iter().count().saturating_sub(1);
+        let position = if self.is_pinned(tab) {
+            position.min(pinned)
+        } else {
+            position.clamp(pinned, last.max(pinned))
+        };
+        self.nav.position_set(tab, position as u16);
+    }
+
+    /// The tab taking over from `tab` once it goes: the one before it, or after the first.
+    pub fn successor(&self, tab: nav_bar::Id) -> Option<nav_bar::Id> {
+        let tabs: Vec<nav_bar::Id> = self.nav.iter().collect();
+        let position = tabs.iter().position(|&other| other == tab)?;
+        tabs.get(if position == 0 { 1 } else { position - 1 })
+            .copied()
+    }
+}
+
+/// Marks a pinned tab in the nav bar model.
+struct Pinned;
+
+/// The title of a tab's page, kept apart from its nav bar text since pinned tabs show none.
+struct TabTitle(String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_with_tabs(count: usize) -> (BrowserWindow, Vec<nav_bar::Id>) {
+        let mut window = BrowserWindow::new();
+        let tabs = (0..count as u64)
+            .map(|view| {
+                let icon = icon::from_name("text-html-symbolic").icon();
+                window.insert_tab(web::ViewId::from_raw(view), format!("Tab {view}"), icon)
+            })
+            .collect();
+        (window, tabs)
+    }
+
+    #[test]
+    fn successor_is_the_tab_before_or_after_the_first() {
+        let (window, tabs) = window_with_tabs(3);
+
+        assert_eq!(window.successor(tabs[0]), Some(tabs[1]));
+        assert_eq!(window.successor(tabs[2]), Some(tabs[1]));
+        let (alone, tabs) = window_with_tabs(1);
+        assert_eq!(alone.successor(tabs[0]), None);
+    }
+
+    #[test]
+    fn pinned_tabs_keep_their_title_out_of_the_tab_bar() {
+        let (mut window, tabs) = window_with_tabs(3);
+
+        window.set_pinned(tabs[2], true);
+        assert_eq!(window.nav.position(tabs[2]).map(usize::from), Some(0));
+        assert_eq!(window.nav.text(tabs[2]), Some(""));
+        assert_eq!(window.tab_title(tabs[2]), "Tab 2");
+
+        window.move_tab(tabs[0], 0);
+        assert_eq!(window.nav.position(tabs[0]).map(usize::from), Some(1));
+        window.set_pinned(tabs[2], false);
+        assert_eq!(window.nav.text(tabs[2]), Some("Tab 2"));
+        assert_eq!(window.tabs().len(), 3);
+    }
+}